@@ -1,6 +1,7 @@
 use rustc_version::{version, Version};
 
 fn main() {
+    println!("cargo:rustc-check-cfg=cfg(const_generics)");
     if version().unwrap() >= Version::parse("1.51.0").unwrap() {
         println!("cargo:rustc-cfg=const_generics");
     }
@@ -22,10 +22,11 @@ async fn main() {
         .await
         .unwrap();
 
-    let mut row = MyUserData::default();
-    row.id = Uuid::new_v4();
-    row.user_data = "some important stuff!".to_string();
-    row.created_at = Utc::now().into();
+    let row = MyUserData {
+        id: Uuid::new_v4(),
+        user_data: "some important stuff!".to_string(),
+        created_at: Utc::now().into(),
+    };
 
     client
         .insert_native_block("insert into my_user_data format native", vec![row])
@@ -5,7 +5,9 @@ use indexmap::IndexMap;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 use crate::{
+    convert::RowRef,
     io::{ClickhouseRead, ClickhouseWrite},
+    protocol::DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION,
     types::{DeserializerState, SerializerState, Type},
     values::Value,
 };
@@ -129,7 +131,7 @@ impl Block {
         }
     }
 
-    pub fn take_iter_rows(&mut self) -> BlockRowValueIter {
+    pub fn take_iter_rows(&mut self) -> BlockRowValueIter<'_> {
         let mut column_data = IndexMap::new();
         std::mem::swap(&mut self.column_data, &mut column_data);
         let mut out = Vec::with_capacity(self.rows as usize);
@@ -140,6 +142,29 @@ impl Block {
         BlockRowValueIter { column_data: out }
     }
 
+    /// Deserializes each row of this block into `T`, borrowing strings and other values from the block instead of copying them.
+    pub fn deserialize_rows<'a, T: RowRef<'a>>(&'a self) -> impl Iterator<Item = Result<T>> + 'a {
+        let columns = self
+            .column_data
+            .iter()
+            .map(|(name, values)| {
+                let type_ = self.column_types.get(name).unwrap();
+                (&**name, type_.strip_low_cardinality(), &values[..])
+            })
+            .collect::<Vec<_>>();
+        let mut row = Vec::with_capacity(columns.len());
+        (0..self.rows as usize).map(move |index| {
+            row.clear();
+            for (name, type_, values) in &columns {
+                let value = values
+                    .get(index)
+                    .ok_or_else(|| anyhow!("row and column length mismatch"))?;
+                row.push((*name, *type_, value));
+            }
+            T::deserialize_row_ref(&row)
+        })
+    }
+
     pub fn into_iter_rows(self) -> BlockRowIntoIter {
         let column_types = self.column_types;
         BlockRowIntoIter {
@@ -174,7 +199,15 @@ impl Block {
         for _ in 0..columns {
             let name = reader.read_string().await?;
             let type_name = reader.read_string().await?;
-            let type_ = Type::from_str(&*type_name)?;
+            let type_ = Type::from_str(&type_name)?;
+            if revision >= DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION
+                && reader.read_u8().await? != 0
+            {
+                return Err(anyhow!(
+                    "custom serialization of column {} is not supported",
+                    name
+                ));
+            }
             block.column_types.insert(name.clone(), type_.clone());
             let mut state = DeserializerState {};
             let row_data = if rows > 0 {
@@ -203,8 +236,12 @@ impl Block {
         writer.write_var_uint(joined.len() as u64).await?;
         writer.write_var_uint(self.rows).await?;
         for (name, (type_, data)) in joined {
-            writer.write_string(&*name).await?;
-            writer.write_string(&*type_.to_string()).await?;
+            writer.write_string(name).await?;
+            writer.write_string(&type_.to_string()).await?;
+            if revision >= DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION {
+                // no custom serialization
+                writer.write_u8(0).await?;
+            }
             if data.len() != self.rows as usize {
                 return Err(anyhow!("row and column length mismatch"));
             }
@@ -1,8 +1,9 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use anyhow::anyhow;
+use anyhow::Context;
 use anyhow::Result;
-use futures::{stream, Stream, StreamExt};
+use futures::{future, stream, FutureExt, Stream, StreamExt};
 use indexmap::IndexMap;
 use protocol::CompressionMethod;
 use tokio::{
@@ -18,21 +19,52 @@ use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
     block::{Block, BlockInfo},
-    convert::Row,
+    convert::{check_block_columns, FromSql, Row},
+    inserter::{InsertErrorPolicy, InsertReport, InsertRowError, Inserter, InserterOptions},
     internal_client_in::InternalClientIn,
     internal_client_out::{
-        ClientHello, ClientInfo, InternalClientOut, Query, QueryKind, QueryProcessingStage,
+        ClientHello, ClientInfo, InternalClientOut, Query, QueryProcessingStage,
     },
     io::{ClickhouseRead, ClickhouseWrite},
-    protocol::{self, ServerPacket},
+    params::QueryParams,
+    progress::Progress,
+    protocol::{self, QueryKind, ServerPacket, TableColumns},
+    schema::{ColumnInfo, ColumnRow, TableInfo, TableRow},
+    select::Select,
+    types::Type,
+    values::Value,
 };
 use log::*;
 
+/// A packet in response to a query, routed to the query by the client actor.
+pub(crate) enum QueryResponse {
+    Data(Block),
+    Progress(Progress),
+    /// Server exception, ending the response.
+    Error(anyhow::Error),
+}
+
+pub(crate) type ResponseReceiver = mpsc::Receiver<QueryResponse>;
+
+struct PendingQuery {
+    id: u64,
+    sender: mpsc::Sender<QueryResponse>,
+    compression: CompressionMethod,
+    columns: Option<oneshot::Sender<TableColumns>>,
+}
+
 struct InnerClient<R: ClickhouseRead, W: ClickhouseWrite> {
     input: InternalClientIn<R>,
     output: InternalClientOut<W>,
     options: ClientOptions,
-    pending_queries: VecDeque<mpsc::Sender<Block>>,
+    pending_queries: VecDeque<PendingQuery>,
+    // compression of the last query sent, used for any data sent along with it
+    send_compression: CompressionMethod,
+    next_query_id: u64,
+    // insert still accepting data blocks, the connection is reserved for it until it ends
+    open_insert: Option<u64>,
+    // queries received while an insert is open, sent once it ends
+    deferred: VecDeque<ClientRequest>,
 }
 
 impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
@@ -40,14 +72,48 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
         Self {
             input: InternalClientIn::new(reader),
             output: InternalClientOut::new(writer),
+            send_compression: options.compression,
             options,
             pending_queries: VecDeque::new(),
+            next_query_id: 0,
+            open_insert: None,
+            deferred: VecDeque::new(),
         }
     }
 
     async fn handle_request(&mut self, request: ClientRequest) -> Result<()> {
+        if self.open_insert.is_some() && matches!(request.data, ClientRequestData::Query { .. }) {
+            self.deferred.push_back(request);
+            return Ok(());
+        }
         match request.data {
-            ClientRequestData::Query { query, response } => {
+            ClientRequestData::Query {
+                query,
+                parameters,
+                compression,
+                columns,
+                insert,
+                response,
+            } => {
+                if !parameters.is_empty()
+                    && self.output.server_hello.revision_version
+                        < protocol::DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS
+                {
+                    // fail only this query, before anything is written to the connection
+                    let (sender, receiver) = mpsc::channel(1);
+                    sender
+                        .try_send(QueryResponse::Error(anyhow!(
+                            "server does not support query parameters"
+                        )))
+                        .ok();
+                    response.send(receiver).ok();
+                    return Ok(());
+                }
+                let compression = compression.unwrap_or(self.options.compression);
+                let settings: &[(&str, &str)] = match compression {
+                    CompressionMethod::ZSTD => &[("network_compression_method", "ZSTD")],
+                    _ => &[],
+                };
                 self.output
                     .send_query(Query {
                         id: "",
@@ -56,6 +122,7 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
                             initial_user: "",
                             initial_query_id: "",
                             initial_address: "0.0.0.0:0",
+                            initial_query_start_time_microseconds: 0,
                             os_user: "",
                             client_hostname: "localhost",
                             client_name: "ClickHouseclient",
@@ -66,35 +133,50 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
                             distributed_depth: 1,
                             client_version_patch: 1,
                             open_telemetry: None,
+                            collaborate_with_initiator: 0,
+                            count_participating_replicas: 0,
+                            number_of_current_replica: 0,
                         },
+                        settings,
                         stage: QueryProcessingStage::Complete,
-                        compression: CompressionMethod::default(),
-                        query: &*query,
+                        compression,
+                        query: &query,
+                        parameters: &parameters,
                     })
                     .await?;
 
+                let id = self.next_query_id;
+                self.next_query_id += 1;
+                if insert {
+                    self.open_insert = Some(id);
+                }
                 let (sender, receiver) = mpsc::channel(32);
                 response.send(receiver).ok();
-                self.pending_queries.push_back(sender);
+                self.pending_queries.push_back(PendingQuery {
+                    id,
+                    sender,
+                    compression,
+                    columns,
+                });
+                self.send_compression = compression;
                 self.output
-                    .send_data(
-                        &Block {
-                            info: BlockInfo::default(),
-                            rows: 0,
-                            column_types: IndexMap::new(),
-                            column_data: IndexMap::new(),
-                        },
-                        CompressionMethod::default(),
-                        "",
-                        false,
-                    )
+                    .send_data(&empty_block(), compression, "", false)
                     .await?;
             }
             ClientRequestData::SendData { block, response } => {
+                if self.open_insert.is_none() {
+                    // the insert already ended, i.e. aborted by a server exception
+                    response.send(Err(anyhow!("no insert in progress"))).ok();
+                    return Ok(());
+                }
                 self.output
-                    .send_data(&block, CompressionMethod::default(), "", false)
+                    .send_data(&block, self.send_compression, "", false)
                     .await?;
-                response.send(()).ok();
+                if block.column_types.is_empty() {
+                    // empty block ends the insert
+                    self.open_insert = None;
+                }
+                response.send(Ok(())).ok();
             }
         }
         Ok(())
@@ -107,19 +189,42 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
             }
             ServerPacket::Data(block) => {
                 if let Some(current) = self.pending_queries.front() {
-                    current.send(block.block).await.ok();
+                    current
+                        .sender
+                        .send(QueryResponse::Data(block.block))
+                        .await
+                        .ok();
                 } else {
                     return Err(anyhow!("received data block, but no pending queries"));
                 }
             }
             ServerPacket::Exception(e) => {
-                return Err(e.emit());
+                // an exception ends the response to the current query, the connection remains usable
+                if let Some(current) = self.pending_queries.pop_front() {
+                    self.end_query(current.id);
+                    current
+                        .sender
+                        .send(QueryResponse::Error(e.emit()))
+                        .await
+                        .ok();
+                } else {
+                    return Err(e.emit());
+                }
+            }
+            ServerPacket::Progress(progress) => {
+                if let Some(current) = self.pending_queries.front() {
+                    current
+                        .sender
+                        .send(QueryResponse::Progress(progress))
+                        .await
+                        .ok();
+                }
             }
-            ServerPacket::Progress(_) => {}
             ServerPacket::Pong => {}
             ServerPacket::EndOfStream => {
-                if self.pending_queries.pop_front().is_some() {
+                if let Some(current) = self.pending_queries.pop_front() {
                     // drop sender
+                    self.end_query(current.id);
                 } else {
                     return Err(anyhow!("received end of stream, but no pending queries"));
                 }
@@ -129,13 +234,28 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
             ServerPacket::Extremes(_) => {}
             ServerPacket::TablesStatusResponse(_) => {}
             ServerPacket::Log(_) => {}
-            ServerPacket::TableColumns(_) => {}
+            ServerPacket::TableColumns(columns) => {
+                if let Some(sender) = self
+                    .pending_queries
+                    .front_mut()
+                    .and_then(|x| x.columns.take())
+                {
+                    sender.send(columns).ok();
+                }
+            }
             ServerPacket::PartUUIDs(_) => {}
+            ServerPacket::ProfileEvents(_) => {}
             ServerPacket::ReadTaskRequest => {}
         }
         Ok(())
     }
 
+    fn end_query(&mut self, id: u64) {
+        if self.open_insert == Some(id) {
+            self.open_insert = None;
+        }
+    }
+
     async fn run_inner(mut self, mut input: Receiver<ClientRequest>) -> Result<()> {
         self.output
             .send_hello(ClientHello {
@@ -144,11 +264,20 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
                 password: &self.options.password,
             })
             .await?;
-        let hello_response = self.input.receive_hello().await?;
+        let mut hello_response = self.input.receive_hello().await?;
+        hello_response.revision_version = hello_response
+            .revision_version
+            .min(protocol::DBMS_TCP_PROTOCOL_VERSION);
         self.input.server_hello = hello_response.clone();
         self.output.server_hello = hello_response.clone();
+        self.output.send_addendum("").await?;
 
         loop {
+            self.input.compression = self
+                .pending_queries
+                .front()
+                .map(|x| x.compression)
+                .unwrap_or(CompressionMethod::None);
             select! {
                 request = input.recv() => {
                     if request.is_none() {
@@ -161,6 +290,12 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
                     self.receive_packet(packet).await?;
                 },
             }
+            while self.open_insert.is_none() {
+                match self.deferred.pop_front() {
+                    Some(request) => self.handle_request(request).await?,
+                    None => break,
+                }
+            }
         }
     }
 
@@ -171,14 +306,257 @@ impl<R: ClickhouseRead, W: ClickhouseWrite> InnerClient<R, W> {
     }
 }
 
+/// Row of a single column, for [`Client::query_scalar`].
+struct Scalar<T>(T);
+
+impl<T: FromSql> Row for Scalar<T> {
+    fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self> {
+        if map.len() != 1 {
+            return Err(anyhow!(
+                "unexpected column count, got {} expecting 1",
+                map.len()
+            ));
+        }
+        let (_, type_, value) = map.into_iter().next().unwrap();
+        Ok(Scalar(T::from_sql(type_, value)?))
+    }
+
+    fn serialize_row(self) -> Result<Vec<(&'static str, Value)>> {
+        Err(anyhow!("scalar rows cannot be serialized"))
+    }
+}
+
+/// Stream of the data blocks of a query response, ending with the server exception if there is one.
+fn response_blocks(receiver: ResponseReceiver) -> impl Stream<Item = Result<Block>> {
+    ReceiverStream::new(receiver).filter_map(|response| {
+        future::ready(match response {
+            QueryResponse::Data(block) => Some(Ok(block)),
+            QueryResponse::Progress(_) => None,
+            QueryResponse::Error(e) => Some(Err(e)),
+        })
+    })
+}
+
+/// Stream of the data blocks of a query response, logging any server exception.
+fn response_blocks_logged(receiver: ResponseReceiver) -> impl Stream<Item = Block> {
+    response_blocks(receiver).filter_map(|block| {
+        future::ready(match block {
+            Ok(block) => Some(block),
+            Err(e) => {
+                error!("clickhouse query failed: {:?}", e);
+                None
+            }
+        })
+    })
+}
+
+/// Takes the responses received so far for an insert in progress, returning the server exception if the server aborted it.
+/// Draining them between blocks also keeps the client actor from blocking on a full response channel.
+pub(crate) fn take_insert_error(receiver: &mut ResponseReceiver) -> Option<anyhow::Error> {
+    while let Some(Some(response)) = receiver.recv().now_or_never() {
+        if let QueryResponse::Error(e) = response {
+            return Some(e);
+        }
+    }
+    None
+}
+
+fn empty_block() -> Block {
+    Block {
+        info: BlockInfo::default(),
+        rows: 0,
+        column_types: IndexMap::new(),
+        column_data: IndexMap::new(),
+    }
+}
+
+/// A row serialized by [`Row::serialize_row`].
+pub(crate) type SerializedRow = Vec<(&'static str, Value)>;
+
+/// Columns accepted by an insert, taken from the header block and table metadata sent by the server.
+pub(crate) struct InsertSchema {
+    header: Block,
+    table: String,
+    // columns the server fills in from a default expression when omitted
+    defaulted: HashSet<String>,
+}
+
+impl InsertSchema {
+    fn new(query: &str, header: Block, columns: Option<TableColumns>) -> Result<Self> {
+        let (table, defaulted) = match columns {
+            Some(columns) => {
+                let defaulted = columns
+                    .columns()?
+                    .into_iter()
+                    .filter(|x| x.default_kind.is_some())
+                    .map(|x| x.name)
+                    .collect();
+                (columns.name, defaulted)
+            }
+            None => (insert_table_name(query), HashSet::new()),
+        };
+        Ok(InsertSchema {
+            header,
+            table,
+            defaulted,
+        })
+    }
+
+    /// Coerces the values of `row` to the column types, failing on unknown fields or values of the wrong type.
+    /// Positional rows must have a value for every column.
+    fn validate_row(&self, row: &mut SerializedRow) -> Result<()> {
+        let positional = is_positional(row);
+        if positional && row.len() != self.header.column_types.len() {
+            return Err(anyhow!(
+                "row has {} values but table {} has {} columns",
+                row.len(),
+                self.table,
+                self.header.column_types.len()
+            ));
+        }
+        for (i, (key, value)) in row.iter_mut().enumerate() {
+            let (key, type_) = if positional {
+                self.header.column_types.get_index(i).unwrap()
+            } else {
+                self.header
+                    .column_types
+                    .get_key_value(*key)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "field `{}` has no matching column in table {}",
+                            key,
+                            self.table
+                        )
+                    })?
+            };
+            *value = type_.coerce_value(std::mem::replace(value, Value::Null));
+            type_
+                .validate_value(value)
+                .with_context(|| format!("invalid value for column `{}`", key))?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `row` was serialized from a tuple or tuple struct, and is matched to columns by position.
+fn is_positional(row: &SerializedRow) -> bool {
+    row.first().is_some_and(|(name, _)| name.is_empty())
+}
+
+/// Best effort extraction of the table name from an insert query, for error messages.
+fn insert_table_name(query: &str) -> String {
+    let mut words = query.split_whitespace();
+    while let Some(word) = words.next() {
+        if word.eq_ignore_ascii_case("into") {
+            if let Some(table) = words.next() {
+                let table = table.split('(').next().unwrap_or_default();
+                if !table.is_empty() && !table.eq_ignore_ascii_case("table") {
+                    return table.to_string();
+                }
+            }
+            break;
+        }
+    }
+    "(unknown)".to_string()
+}
+
+/// Builds a block of serialized rows to insert, laid out after the columns of `schema`.
+/// Each row is paired with its index in the insert. Rows that failed to serialize or do not match the schema are handled by `policy`.
+/// Columns omitted by the rows are filled with type defaults, unless the table has a default expression for them and no row provides them, in which case they are left to the server.
+pub(crate) fn build_block(
+    schema: &InsertSchema,
+    rows: Vec<(u64, Result<SerializedRow>)>,
+    policy: &InsertErrorPolicy,
+    skipped: &mut Vec<InsertRowError>,
+) -> Result<Block> {
+    let mut block = Block {
+        info: BlockInfo::default(),
+        rows: 0,
+        column_types: schema.header.column_types.clone(),
+        column_data: schema
+            .header
+            .column_types
+            .keys()
+            .map(|name| (name.clone(), Vec::with_capacity(rows.len())))
+            .collect(),
+    };
+    let mut missing = vec![0u64; block.column_types.len()];
+    for (index, row) in rows {
+        let mut row = match row {
+            Ok(row) => row,
+            Err(error) => {
+                policy.handle(
+                    InsertRowError {
+                        index,
+                        row: None,
+                        error,
+                    },
+                    skipped,
+                )?;
+                continue;
+            }
+        };
+        if let Err(error) = schema.validate_row(&mut row) {
+            policy.handle(
+                InsertRowError {
+                    index,
+                    row: Some(row),
+                    error,
+                },
+                skipped,
+            )?;
+            continue;
+        }
+        if is_positional(&row) {
+            for (column, (_, value)) in block.column_data.values_mut().zip(row) {
+                column.push(value);
+            }
+            block.rows += 1;
+            continue;
+        }
+        let mut row = row.into_iter().collect::<HashMap<_, _>>();
+        for (i, ((name, type_), column)) in block
+            .column_types
+            .iter()
+            .zip(block.column_data.values_mut())
+            .enumerate()
+        {
+            match row.remove(name.as_str()) {
+                Some(value) => column.push(value),
+                None => {
+                    missing[i] += 1;
+                    column.push(type_.default_value());
+                }
+            }
+        }
+        block.rows += 1;
+    }
+    if block.rows > 0 {
+        for (name, missing) in schema.header.column_types.keys().zip(missing) {
+            if missing == block.rows && schema.defaulted.contains(name) {
+                block.column_types.shift_remove(name);
+                block.column_data.shift_remove(name);
+            }
+        }
+    }
+    Ok(block)
+}
+
 enum ClientRequestData {
     Query {
         query: String,
-        response: oneshot::Sender<mpsc::Receiver<Block>>,
+        // quoted values for `{name:Type}` placeholders
+        parameters: Vec<(String, String)>,
+        compression: Option<CompressionMethod>,
+        // receives table metadata sent ahead of the header block of an insert
+        columns: Option<oneshot::Sender<TableColumns>>,
+        // whether data blocks follow, holding the connection until an empty block is sent
+        insert: bool,
+        response: oneshot::Sender<ResponseReceiver>,
     },
     SendData {
         block: Block,
-        response: oneshot::Sender<()>,
+        response: oneshot::Sender<Result<()>>,
     },
 }
 
@@ -190,6 +568,8 @@ struct ClientRequest {
 #[derive(Clone)]
 pub struct Client {
     sender: mpsc::Sender<ClientRequest>,
+    compression: Option<CompressionMethod>,
+    lenient_coercion: bool,
 }
 
 /// Options set for a Clickhouse connection.
@@ -198,6 +578,10 @@ pub struct ClientOptions {
     pub username: String,
     pub password: String,
     pub default_database: String,
+    /// Compression used for data blocks, unless overridden by [`Client::with_compression()`]
+    pub compression: CompressionMethod,
+    /// Deserializes rows in [`Client::query()`] with lenient coercions, see [`crate::with_lenient_coercion`]
+    pub lenient_coercion: bool,
 }
 
 impl Default for ClientOptions {
@@ -206,6 +590,8 @@ impl Default for ClientOptions {
             username: "default".to_string(),
             password: String::new(),
             default_database: String::new(),
+            compression: CompressionMethod::default(),
+            lenient_coercion: false,
         }
     }
 }
@@ -233,31 +619,66 @@ impl Client {
         Ok(Self::connect_stream(read, writer, options))
     }
 
-    fn start<R: ClickhouseRead + 'static, W: ClickhouseWrite>(inner: InnerClient<R, W>) -> Self {
+    fn start<R: ClickhouseRead + 'static, W: ClickhouseWrite + 'static>(
+        inner: InnerClient<R, W>,
+    ) -> Self {
         let (sender, receiver) = mpsc::channel(1024);
+        let lenient_coercion = inner.options.lenient_coercion;
         tokio::spawn(inner.run(receiver));
-        Client { sender }
+        Client {
+            sender,
+            compression: None,
+            lenient_coercion,
+        }
+    }
+
+    /// Returns a handle to the same connection, which uses `compression` for all queries sent through it instead of [`ClientOptions::compression`].
+    pub fn with_compression(&self, compression: CompressionMethod) -> Client {
+        Client {
+            sender: self.sender.clone(),
+            compression: Some(compression),
+            lenient_coercion: self.lenient_coercion,
+        }
     }
 
     /// Sends a query string and read column blocks over a stream.
+    /// The stream ends early if the server sends an exception, which is logged.
     /// You probably want [`Client::query()`]
     pub async fn query_raw(&self, query: &str) -> Result<impl Stream<Item = Block>> {
+        Ok(response_blocks_logged(self.send_query(query).await?))
+    }
+
+    /// Sends a query, returning the receiver for its response.
+    async fn send_query(&self, query: &str) -> Result<ResponseReceiver> {
+        self.send_query_with(query, vec![], false).await
+    }
+
+    /// Sends a query with encoded parameters, returning the receiver for its response.
+    /// An `insert` query holds the connection until its data is ended with an empty block.
+    async fn send_query_with(
+        &self,
+        query: &str,
+        parameters: Vec<(String, String)>,
+        insert: bool,
+    ) -> Result<ResponseReceiver> {
         let (sender, receiver) = oneshot::channel();
         self.sender
             .send(ClientRequest {
                 data: ClientRequestData::Query {
                     query: query.to_string(),
+                    parameters,
+                    compression: self.compression,
+                    columns: None,
+                    insert,
                     response: sender,
                 },
             })
             .await
             .map_err(|_| anyhow!("failed to send query"))?;
-        let receiver = receiver.await?;
-
-        Ok(ReceiverStream::new(receiver))
+        Ok(receiver.await?)
     }
 
-    async fn send_data(&self, block: Block) -> Result<()> {
+    pub(crate) async fn send_data(&self, block: Block) -> Result<()> {
         let (sender, receiver) = oneshot::channel();
         self.sender
             .send(ClientRequest {
@@ -268,7 +689,7 @@ impl Client {
             })
             .await
             .map_err(|_| anyhow!("failed to send block"))?;
-        receiver.await?;
+        receiver.await??;
 
         Ok(())
     }
@@ -281,96 +702,124 @@ impl Client {
         query: &str,
         mut blocks: impl Stream<Item = Block> + Send + Sync + Unpin + 'static,
     ) -> Result<impl Stream<Item = Block>> {
+        let receiver = self.send_query_with(query, vec![], true).await?;
+
+        while let Some(block) = blocks.next().await {
+            self.send_data(block).await?;
+        }
+        self.send_data(empty_block()).await?;
+
+        Ok(response_blocks_logged(receiver))
+    }
+
+    /// Starts an insert query, returning the pending response along with the columns to send.
+    pub(crate) async fn begin_insert(
+        &self,
+        query: &str,
+    ) -> Result<(ResponseReceiver, InsertSchema)> {
         let (sender, receiver) = oneshot::channel();
+        let (columns_sender, mut columns_receiver) = oneshot::channel();
         self.sender
             .send(ClientRequest {
                 data: ClientRequestData::Query {
                     query: query.to_string(),
+                    parameters: vec![],
+                    compression: self.compression,
+                    columns: Some(columns_sender),
+                    insert: true,
                     response: sender,
                 },
             })
             .await
             .map_err(|_| anyhow!("failed to send query"))?;
-        let receiver = receiver.await?;
+        let mut receiver = receiver.await?;
+        let header = loop {
+            match receiver.recv().await {
+                Some(QueryResponse::Data(block)) => break block,
+                Some(QueryResponse::Progress(_)) => continue,
+                Some(QueryResponse::Error(e)) => return Err(e),
+                None => return Err(anyhow!("missing header block from server")),
+            }
+        };
+        // table columns are always sent before the header block, if at all
+        let columns = columns_receiver.try_recv().ok();
+        Ok((receiver, InsertSchema::new(query, header, columns)?))
+    }
 
-        while let Some(block) = blocks.next().await {
-            self.send_data(block).await?;
+    /// Ends an insert query started with [`Client::begin_insert`], waiting for the server to finish processing it.
+    /// Blocks already sent are kept. Fails with the server exception if the server aborted the insert.
+    pub(crate) async fn end_insert(&self, mut receiver: ResponseReceiver) -> Result<()> {
+        // the server already ended the query, so it must not be sent the terminating block
+        if let Some(e) = take_insert_error(&mut receiver) {
+            return Err(e);
         }
-        self.send_data(Block {
-            info: BlockInfo::default(),
-            rows: 0,
-            column_types: IndexMap::new(),
-            column_data: IndexMap::new(),
-        })
-        .await?;
+        self.send_data(empty_block()).await?;
+        while let Some(response) = receiver.recv().await {
+            if let QueryResponse::Error(e) = response {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
 
-        Ok(ReceiverStream::new(receiver))
+    /// Ends an insert query that failed on the client side with `error`, so that the connection remains usable, and returns `error`.
+    pub(crate) async fn abort_insert(
+        &self,
+        receiver: ResponseReceiver,
+        error: anyhow::Error,
+    ) -> anyhow::Error {
+        if let Err(e) = self.end_insert(receiver).await {
+            warn!("failed to end aborted insert: {:?}", e);
+        }
+        error
     }
 
     /// Sends a query string with streaming associated data (i.e. insert) over native protocol.
-    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read and DISCARDED.
+    /// Once all outgoing blocks are written (EOF of `blocks` stream), then any response blocks from Clickhouse are read and DISCARDED,
+    /// failing if the server sends an exception.
+    /// The insert fails on the first row that fails to serialize or does not match the table, see [`Client::insert_native_with_policy`] to skip such rows instead.
     /// Make sure any query you send native data with has a `format native` suffix.
     pub async fn insert_native<T: Row + Send + Sync + 'static>(
         &self,
         query: &str,
-        mut blocks: impl Stream<Item = Vec<T>> + Send + Sync + Unpin + 'static,
+        blocks: impl Stream<Item = Vec<T>> + Send + Sync + Unpin + 'static,
     ) -> Result<()> {
-        let (sender, receiver) = oneshot::channel();
-        self.sender
-            .send(ClientRequest {
-                data: ClientRequestData::Query {
-                    query: query.to_string(),
-                    response: sender,
-                },
-            })
-            .await
-            .map_err(|_| anyhow!("failed to send query"))?;
-        let mut receiver = receiver.await?;
-        let first_block = receiver
-            .recv()
-            .await
-            .ok_or_else(|| anyhow!("missing header block from server"))?;
+        self.insert_native_with_policy(query, blocks, InsertErrorPolicy::Fail)
+            .await?;
+        Ok(())
+    }
+
+    /// Same as [`Client::insert_native`], but rows that fail to serialize or do not match the table are handled according to `policy`.
+    /// Returns a report of the rows inserted and any rows skipped by [`InsertErrorPolicy::Skip`].
+    pub async fn insert_native_with_policy<T: Row + Send + Sync + 'static>(
+        &self,
+        query: &str,
+        mut blocks: impl Stream<Item = Vec<T>> + Send + Sync + Unpin + 'static,
+        policy: InsertErrorPolicy,
+    ) -> Result<InsertReport> {
+        let (mut receiver, schema) = self.begin_insert(query).await?;
+        let mut report = InsertReport::default();
+        let mut index = 0u64;
         while let Some(rows) = blocks.next().await {
-            let mut block = Block {
-                info: BlockInfo::default(),
-                rows: rows.len() as u64,
-                column_types: first_block.column_types.clone(),
-                column_data: IndexMap::new(),
-            };
-            rows.into_iter()
-                .map(|x| x.serialize_row())
-                .filter_map(|x| match x {
-                    Err(e) => {
-                        error!("serialization error during insert (SKIPPED ROWS!): {:?}", e);
-                        None
-                    }
-                    Ok(x) => Some(x),
+            if let Some(e) = take_insert_error(&mut receiver) {
+                return Err(e);
+            }
+            let rows = rows
+                .into_iter()
+                .map(|x| {
+                    index += 1;
+                    (index - 1, x.serialize_row())
                 })
-                .try_for_each(|x| -> Result<()> {
-                    for (key, value) in x {
-                        let type_ = first_block
-                            .column_types
-                            .get(key)
-                            .ok_or_else(|| anyhow!("missing type for data"))?;
-                        type_.validate_value(&value)?;
-                        if let Some(column) = block.column_data.get_mut(key) {
-                            column.push(value);
-                        } else {
-                            block.column_data.insert(key.to_string(), vec![value]);
-                        }
-                    }
-                    Ok(())
-                })?;
+                .collect();
+            let block = match build_block(&schema, rows, &policy, &mut report.skipped) {
+                Ok(block) => block,
+                Err(e) => return Err(self.abort_insert(receiver, e).await),
+            };
+            report.rows += block.rows;
             self.send_data(block).await?;
         }
-        self.send_data(Block {
-            info: BlockInfo::default(),
-            rows: 0,
-            column_types: IndexMap::new(),
-            column_data: IndexMap::new(),
-        })
-        .await?;
-        Ok(())
+        self.end_insert(receiver).await?;
+        Ok(report)
     }
 
     /// Wrapper over [`Client::insert_native`] to send a single block.
@@ -385,17 +834,200 @@ impl Client {
         self.insert_native(query, stream).await
     }
 
+    /// Starts a buffered [`Inserter`] for `query`, which sends rows written to it in blocks according to `options`.
+    /// The INSERT query is only sent once the first block is ready.
+    /// Other queries sent on this connection wait while the INSERT is open, which ends after [`InserterOptions::max_latency`] without rows or [`InserterOptions::rotate_period`]. Use a dedicated [`Client`] for a busy inserter.
+    /// Make sure any query you send native data with has a `format native` suffix.
+    pub fn inserter<T: Row>(&self, query: &str, options: InserterOptions) -> Inserter<T> {
+        Inserter::new(self.clone(), query, options)
+    }
+
     /// Runs a query against Clickhouse, returning a stream of deserialized rows.
     /// Note that no rows are returned until Clickhouse sends a full block (but it usually sends more than one block).
+    /// If the server sends an exception, it is returned as the last item of the stream.
+    /// The columns of the result are checked against [`Row::expected_columns`] first, failing with every mismatch as the only item of the stream.
     pub async fn query<T: Row>(&self, query: &str) -> Result<impl Stream<Item = Result<T>>> {
-        let raw = self.query_raw(query).await?;
-        Ok(raw.flat_map(|mut block| {
-            let blocks = block
-                .take_iter_rows()
-                .filter(|x| !x.is_empty())
-                .map(|m| T::deserialize_row(m))
-                .collect::<Vec<_>>();
+        self.query_with(query, QueryParams::new()).await
+    }
+
+    /// Runs a query against Clickhouse with typed values for its `{name:Type}` placeholders, returning a stream of deserialized rows.
+    /// Values are sent separately from the query text, so they never need escaping. See [`crate::params!`].
+    /// Fails before sending if a placeholder has no value, a value has no placeholder, or a value does not fit its placeholder type.
+    pub async fn query_with<T: Row>(
+        &self,
+        query: &str,
+        params: QueryParams,
+    ) -> Result<impl Stream<Item = Result<T>>> {
+        let parameters = params.encode(query)?;
+        let receiver = self.send_query_with(query, parameters, false).await?;
+        let lenient_coercion = self.lenient_coercion;
+        let mut columns_checked = false;
+        let mut failed = false;
+        Ok(response_blocks(receiver).flat_map(move |block| {
+            if failed {
+                return stream::iter(vec![]);
+            }
+            let mut block = match block {
+                Ok(block) => block,
+                Err(e) => return stream::iter(vec![Err(e)]),
+            };
+            // the first block with columns is checked against the row once, instead of failing on each row
+            if !columns_checked && !block.column_types.is_empty() {
+                columns_checked = true;
+                let checked = if lenient_coercion {
+                    crate::convert::with_lenient_coercion(|| check_block_columns::<T>(&block))
+                } else {
+                    check_block_columns::<T>(&block)
+                };
+                if let Err(e) = checked {
+                    failed = true;
+                    return stream::iter(vec![Err(e)]);
+                }
+            }
+            let mut deserialize = || {
+                block
+                    .take_iter_rows()
+                    .filter(|x| !x.is_empty())
+                    .map(|m| T::deserialize_row(m))
+                    .collect::<Vec<_>>()
+            };
+            let blocks = if lenient_coercion {
+                crate::convert::with_lenient_coercion(deserialize)
+            } else {
+                deserialize()
+            };
             stream::iter(blocks)
         }))
     }
+
+    /// Runs a query against Clickhouse, returning a stream of its non-empty blocks.
+    /// Rows can then be deserialized without copying their values with [`Block::deserialize_rows`], for types implementing [`crate::RowRef`].
+    /// If the server sends an exception, it is returned as the last item of the stream.
+    pub async fn query_blocks(&self, query: &str) -> Result<impl Stream<Item = Result<Block>>> {
+        let receiver = self.send_query(query).await?;
+        Ok(response_blocks(receiver)
+            .filter(|block| future::ready(!matches!(block, Ok(block) if block.rows == 0))))
+    }
+
+    /// Runs a query against Clickhouse, collecting all rows.
+    pub async fn query_collect<T: Row>(&self, query: &str) -> Result<Vec<T>> {
+        self.query::<T>(query)
+            .await?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Runs a query against Clickhouse, returning its only row, or `None` if there are no rows.
+    /// Fails if more than one row is returned.
+    pub async fn query_opt<T: Row>(&self, query: &str) -> Result<Option<T>> {
+        first_row(self.query::<T>(query).await?).await
+    }
+
+    /// Runs a query against Clickhouse, returning its only row.
+    /// Fails unless exactly one row is returned.
+    pub async fn query_one<T: Row>(&self, query: &str) -> Result<T> {
+        self.query_opt(query)
+            .await?
+            .ok_or_else(|| anyhow!("query returned no rows"))
+    }
+
+    /// Starts a `SELECT` query for the columns of `T` from `table`, see [`Select`].
+    pub fn select<T: Row>(&self, table: &str) -> Select<T> {
+        Select::new(self.clone(), table)
+    }
+
+    /// Runs a query against Clickhouse returning a single value, i.e. `select count() from table`.
+    /// Fails unless exactly one row with one column is returned.
+    pub async fn query_scalar<T: FromSql>(&self, query: &str) -> Result<T> {
+        Ok(self.query_one::<Scalar<T>>(query).await?.0)
+    }
+
+    /// Lists the names of all databases.
+    pub async fn list_databases(&self) -> Result<Vec<String>> {
+        Ok(self
+            .query_collect::<(String,)>("select name from system.databases order by name")
+            .await?
+            .into_iter()
+            .map(|(name,)| name)
+            .collect())
+    }
+
+    /// Lists the names of all tables and views in `database`.
+    pub async fn list_tables(&self, database: &str) -> Result<Vec<String>> {
+        let query = crate::sql!(
+            "select name from system.tables where database = {} order by name",
+            database
+        )?;
+        Ok(self
+            .query_collect::<(String,)>(&query)
+            .await?
+            .into_iter()
+            .map(|(name,)| name)
+            .collect())
+    }
+
+    /// Fetches the engine and keys of a table from `system.tables`.
+    pub async fn table_info(&self, database: &str, table: &str) -> Result<TableInfo> {
+        let query = crate::sql!(
+            "select database, name, engine, engine_full, partition_key, sorting_key, primary_key, sampling_key from system.tables where database = {} and name = {}",
+            database,
+            table
+        )?;
+        self.query_opt::<TableRow>(&query)
+            .await?
+            .map(TableInfo::from)
+            .ok_or_else(|| anyhow!("table `{}`.`{}` does not exist", database, table))
+    }
+
+    /// Fetches the columns of a table from `system.columns`, in table order.
+    pub async fn describe_table(&self, database: &str, table: &str) -> Result<Vec<ColumnInfo>> {
+        let query = crate::sql!(
+            "select name, type, default_kind, default_expression, comment, compression_codec, is_in_partition_key, is_in_sorting_key, is_in_primary_key, is_in_sampling_key from system.columns where database = {} and table = {} order by position",
+            database,
+            table
+        )?;
+        let columns = self
+            .query_collect::<ColumnRow>(&query)
+            .await?
+            .into_iter()
+            .map(ColumnInfo::from_row)
+            .collect::<Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            return Err(anyhow!("table `{}`.`{}` does not exist", database, table));
+        }
+        Ok(columns)
+    }
+
+    /// Runs a query against Clickhouse without returning rows, i.e. DDL statements or `INSERT ... SELECT`.
+    /// Waits for the query to complete, returning the number of rows written as reported by Clickhouse.
+    pub async fn execute(&self, query: &str) -> Result<u64> {
+        let mut receiver = self.send_query(query).await?;
+        let mut written_rows = 0;
+        while let Some(response) = receiver.recv().await {
+            match response {
+                QueryResponse::Data(_) => (),
+                QueryResponse::Progress(progress) => {
+                    written_rows += progress.new_written_rows.unwrap_or_default();
+                }
+                QueryResponse::Error(e) => return Err(e),
+            }
+        }
+        Ok(written_rows)
+    }
+}
+
+/// Returns the only row of `rows`, or `None` if there are no rows. Fails if there is more than one row.
+pub(crate) async fn first_row<T>(rows: impl Stream<Item = Result<T>>) -> Result<Option<T>> {
+    let mut rows = Box::pin(rows);
+    let row = match rows.next().await {
+        Some(row) => row?,
+        None => return Ok(None),
+    };
+    match rows.next().await {
+        Some(Err(e)) => Err(e),
+        Some(Ok(_)) => Err(anyhow!("query returned more than one row")),
+        None => Ok(Some(row)),
+    }
 }
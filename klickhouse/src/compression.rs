@@ -1,55 +1,482 @@
-use anyhow::anyhow;
+use std::{
+    convert::TryInto,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
 
-use crate::block::Block;
+use anyhow::*;
+use cityhash_rs::cityhash_102_128;
+use futures::ready;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, ReadBuf};
 
-pub async fn compress_block(block: &Block, revision: u64) -> anyhow::Result<(Vec<u8>, usize)> {
-    let mut raw = vec![];
-    block.write(&mut raw, revision).await?;
-    let raw_len = raw.len();
-    let mut compressed = Vec::<u8>::with_capacity(raw.len() + (raw.len() / 255) + 16 + 1);
-    let out_len = unsafe {
-        lz4::liblz4::LZ4_compress_default(
-            raw.as_ptr() as *const i8,
-            compressed.as_mut_ptr() as *mut i8,
-            raw.len() as i32,
-            compressed.capacity() as i32,
-        )
-    };
-    if out_len <= 0 {
-        return Err(anyhow!("invalid compression state"));
+use crate::{
+    block::Block,
+    io::{ClickhouseRead, ClickhouseWrite},
+    protocol::CompressionMethod,
+};
+
+#[cfg(feature = "lz4-pure")]
+fn compress_lz4(raw: &[u8]) -> Result<Vec<u8>> {
+    Ok(lz4_flex::block::compress(raw))
+}
+
+#[cfg(all(feature = "compression", not(feature = "lz4-pure")))]
+fn compress_lz4(raw: &[u8]) -> Result<Vec<u8>> {
+    Ok(lz4::block::compress(raw, None, false)?)
+}
+
+#[cfg(not(any(feature = "compression", feature = "lz4-pure")))]
+fn compress_lz4(_raw: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "attempted to use LZ4 compression when not compiled with `compression` or `lz4-pure` feature in klickhouse"
+    ))
+}
+
+#[cfg(feature = "lz4-pure")]
+fn decompress_lz4(data: &[u8], decompressed_size: u32) -> Result<Vec<u8>> {
+    let output = lz4_flex::block::decompress(data, decompressed_size as usize)
+        .map_err(|e| anyhow!("malformed compressed block: {}", e))?;
+    if output.len() != decompressed_size as usize {
+        return Err(anyhow!("malformed compressed block"));
+    }
+    Ok(output)
+}
+
+#[cfg(all(feature = "compression", not(feature = "lz4-pure")))]
+fn decompress_lz4(data: &[u8], decompressed_size: u32) -> Result<Vec<u8>> {
+    if decompressed_size > i32::MAX as u32 {
+        return Err(anyhow!("decompressed payload too large!"));
+    }
+    let output = lz4::block::decompress(data, Some(decompressed_size as i32))
+        .map_err(|e| anyhow!("malformed compressed block: {}", e))?;
+    if output.len() != decompressed_size as usize {
+        return Err(anyhow!("malformed compressed block"));
     }
-    if out_len as usize > compressed.capacity() {
-        panic!("buffer overflow in compress_block?");
+    Ok(output)
+}
+
+#[cfg(not(any(feature = "compression", feature = "lz4-pure")))]
+fn decompress_lz4(_data: &[u8], _decompressed_size: u32) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "attempted to use LZ4 compression when not compiled with `compression` or `lz4-pure` feature in klickhouse"
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn compress_zstd(raw: &[u8]) -> Result<Vec<u8>> {
+    // clickhouse defaults to level 1
+    Ok(zstd::bulk::compress(raw, 1)?)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn compress_zstd(_raw: &[u8]) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "attempted to use ZSTD compression when not compiled with `zstd` feature in klickhouse"
+    ))
+}
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(data: &[u8], decompressed_size: u32) -> Result<Vec<u8>> {
+    let output = zstd::bulk::decompress(data, decompressed_size as usize)?;
+    if output.len() != decompressed_size as usize {
+        return Err(anyhow!("malformed compressed block"));
     }
-    unsafe { compressed.set_len(out_len as usize) };
+    Ok(output)
+}
+
+#[cfg(not(feature = "zstd"))]
+fn decompress_zstd(_data: &[u8], _decompressed_size: u32) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "attempted to use ZSTD compression when not compiled with `zstd` feature in klickhouse"
+    ))
+}
 
-    Ok((compressed, raw_len))
+fn compress(compression: CompressionMethod, raw: &[u8]) -> Result<Vec<u8>> {
+    match compression {
+        CompressionMethod::None => Ok(raw.to_vec()),
+        CompressionMethod::LZ4 => compress_lz4(raw),
+        CompressionMethod::ZSTD => compress_zstd(raw),
+    }
 }
 
-pub async fn decompress_block(
+fn decompress(
+    compression: CompressionMethod,
     data: &[u8],
     decompressed_size: u32,
-    revision: u64,
-) -> anyhow::Result<Block> {
-    let mut output = Vec::with_capacity(decompressed_size as usize + 1);
-
-    let out_len = unsafe {
-        lz4::liblz4::LZ4_decompress_safe(
-            data.as_ptr() as *const i8,
-            output.as_mut_ptr() as *mut i8,
-            data.len() as i32,
-            output.capacity() as i32,
-        )
-    };
-    if out_len < 0 {
-        return Err(anyhow!("malformed compressed block"));
+) -> Result<Vec<u8>> {
+    match compression {
+        CompressionMethod::None => {
+            if data.len() != decompressed_size as usize {
+                return Err(anyhow!("malformed compressed block"));
+            }
+            Ok(data.to_vec())
+        }
+        CompressionMethod::LZ4 => decompress_lz4(data, decompressed_size),
+        CompressionMethod::ZSTD => decompress_zstd(data, decompressed_size),
+    }
+}
+
+/// Uncompressed size of each frame written, matching Clickhouse's default `max_compress_block_size`
+pub const MAX_COMPRESS_BLOCK_SIZE: usize = 1024 * 1024;
+
+// 1 GB
+const MAX_FRAME_SIZE: u32 = 0x40000000;
+
+// checksum, method byte, compressed size, decompressed size
+const FRAME_HEADER_SIZE: usize = 16 + 9;
+
+/// Encodes `raw` as a single checksummed, compressed frame.
+fn encode_frame(compression: CompressionMethod, raw: &[u8]) -> Result<Vec<u8>> {
+    let compressed = compress(compression, raw)?;
+    let mut frame = Vec::with_capacity(compressed.len() + FRAME_HEADER_SIZE);
+    frame.extend_from_slice(&[0u8; 16][..]);
+    frame.push(compression.byte());
+    frame.extend_from_slice(&(compressed.len() as u32 + 9).to_le_bytes()[..]);
+    frame.extend_from_slice(&(raw.len() as u32).to_le_bytes()[..]);
+    frame.extend(compressed);
+
+    let hash = cityhash_102_128(&frame[16..]);
+    frame[..8].copy_from_slice(&((hash >> 64) as u64).to_le_bytes()[..]);
+    frame[8..16].copy_from_slice(&(hash as u64).to_le_bytes()[..]);
+    Ok(frame)
+}
+
+/// Verifies and decompresses a complete frame as read from the wire.
+fn decode_frame(frame: &[u8]) -> Result<Vec<u8>> {
+    let checksum = (u64::from_le_bytes(frame[..8].try_into()?) as u128) << 64u128
+        | (u64::from_le_bytes(frame[8..16].try_into()?) as u128);
+    let calc_checksum = cityhash_102_128(&frame[16..]);
+    if calc_checksum != checksum {
+        return Err(anyhow!(
+            "corrupt checksum from clickhouse '{:032X}' vs '{:032X}'",
+            calc_checksum,
+            checksum
+        ));
+    }
+    let compression = CompressionMethod::from_byte(frame[16])?;
+    let decompressed_size = u32::from_le_bytes(frame[21..25].try_into()?);
+    if decompressed_size > MAX_FRAME_SIZE {
+        return Err(anyhow!("decompressed payload too large!"));
+    }
+    decompress(compression, &frame[FRAME_HEADER_SIZE..], decompressed_size)
+}
+
+fn invalid_data(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// Reader over a sequence of compressed frames, yielding the concatenated decompressed data.
+/// Frames are only read when more data is requested, so nothing past the end of a block is consumed.
+pub struct CompressedReader<'a, R: ClickhouseRead> {
+    reader: &'a mut R,
+    decompressed: Vec<u8>,
+    position: usize,
+    frame: Vec<u8>,
+    frame_filled: usize,
+}
+
+impl<'a, R: ClickhouseRead> CompressedReader<'a, R> {
+    pub fn new(reader: &'a mut R) -> Self {
+        CompressedReader {
+            reader,
+            decompressed: vec![],
+            position: 0,
+            frame: vec![0u8; FRAME_HEADER_SIZE],
+            frame_filled: 0,
+        }
+    }
+
+    /// Whether all data read so far has been consumed.
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.decompressed.len() && self.frame_filled == 0
+    }
+
+    /// Reads and decodes the next frame. Returns false on a clean EOF before any frame data.
+    fn poll_frame(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<bool>> {
+        loop {
+            if self.frame_filled == self.frame.len() {
+                if self.frame_filled == FRAME_HEADER_SIZE {
+                    let compressed_size =
+                        u32::from_le_bytes(self.frame[17..21].try_into().unwrap());
+                    if compressed_size > MAX_FRAME_SIZE {
+                        return Poll::Ready(Err(invalid_data(anyhow!(
+                            "compressed payload too large!"
+                        ))));
+                    } else if compressed_size < 9 {
+                        return Poll::Ready(Err(invalid_data(anyhow!(
+                            "compressed payload too small!"
+                        ))));
+                    }
+                    self.frame.resize(16 + compressed_size as usize, 0);
+                }
+                if self.frame_filled == self.frame.len() {
+                    self.decompressed = decode_frame(&self.frame[..]).map_err(invalid_data)?;
+                    self.position = 0;
+                    self.frame.truncate(FRAME_HEADER_SIZE);
+                    self.frame_filled = 0;
+                    return Poll::Ready(Ok(true));
+                }
+            }
+            let mut buf = ReadBuf::new(&mut self.frame[self.frame_filled..]);
+            ready!(Pin::new(&mut *self.reader).poll_read(cx, &mut buf))?;
+            let read = buf.filled().len();
+            if read == 0 {
+                if self.frame_filled == 0 {
+                    return Poll::Ready(Ok(false));
+                }
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+            self.frame_filled += read;
+        }
+    }
+}
+
+impl<'a, R: ClickhouseRead> AsyncRead for CompressedReader<'a, R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        while this.position >= this.decompressed.len() {
+            if !ready!(this.poll_frame(cx))? {
+                return Poll::Ready(Ok(()));
+            }
+        }
+        let available = &this.decompressed[this.position..];
+        let read = available.len().min(buf.remaining());
+        buf.put_slice(&available[..read]);
+        this.position += read;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Writer splitting written data into compressed frames of at most [`MAX_COMPRESS_BLOCK_SIZE`] uncompressed bytes.
+/// Any partial frame is written out on flush.
+pub struct CompressedWriter<'a, W: ClickhouseWrite> {
+    writer: &'a mut W,
+    compression: CompressionMethod,
+    buffer: Vec<u8>,
+    pending: Vec<u8>,
+    pending_position: usize,
+}
+
+impl<'a, W: ClickhouseWrite> CompressedWriter<'a, W> {
+    pub fn new(writer: &'a mut W, compression: CompressionMethod) -> Self {
+        CompressedWriter {
+            writer,
+            compression,
+            buffer: Vec::with_capacity(MAX_COMPRESS_BLOCK_SIZE),
+            pending: vec![],
+            pending_position: 0,
+        }
+    }
+
+    fn encode_buffer(&mut self) -> io::Result<()> {
+        self.pending = encode_frame(self.compression, &self.buffer[..]).map_err(invalid_data)?;
+        self.pending_position = 0;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn poll_pending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.pending_position < self.pending.len() {
+            let written =
+                ready!(Pin::new(&mut *self.writer)
+                    .poll_write(cx, &self.pending[self.pending_position..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.pending_position += written;
+        }
+        self.pending.clear();
+        self.pending_position = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<'a, W: ClickhouseWrite> AsyncWrite for CompressedWriter<'a, W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        if this.buffer.len() >= MAX_COMPRESS_BLOCK_SIZE {
+            this.encode_buffer()?;
+            ready!(this.poll_pending(cx))?;
+        }
+        let written = buf.len().min(MAX_COMPRESS_BLOCK_SIZE - this.buffer.len());
+        this.buffer.extend_from_slice(&buf[..written]);
+        Poll::Ready(Ok(written))
     }
-    if out_len as usize > output.capacity() {
-        panic!("buffer overflow in decompress_block?");
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx))?;
+        if !this.buffer.is_empty() {
+            this.encode_buffer()?;
+            ready!(this.poll_pending(cx))?;
+        }
+        Pin::new(&mut *this.writer).poll_flush(cx)
     }
-    unsafe { output.set_len(out_len as usize) };
 
-    let block = Block::read(&mut &output[..], revision).await?;
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut *self.get_mut().writer).poll_shutdown(cx)
+    }
+}
 
+/// Reads a block sent as one or more checksummed, compressed frames. The compression method is taken from each frame.
+pub async fn read_compressed_data<R: ClickhouseRead>(
+    reader: &mut R,
+    revision: u64,
+) -> Result<Block> {
+    let mut reader = CompressedReader::new(reader);
+    let block = Block::read(&mut reader, revision).await?;
+    if !reader.is_empty() {
+        return Err(anyhow!("trailing data after compressed block"));
+    }
     Ok(block)
 }
+
+/// Writes a block as checksummed, compressed frames of at most [`MAX_COMPRESS_BLOCK_SIZE`] uncompressed bytes each.
+pub async fn write_compressed_data<W: ClickhouseWrite>(
+    writer: &mut W,
+    compression: CompressionMethod,
+    block: &Block,
+    revision: u64,
+) -> Result<()> {
+    let mut writer = CompressedWriter::new(writer, compression);
+    block.write(&mut writer, revision).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::BlockInfo, types::Type, values::Value};
+    use indexmap::IndexMap;
+
+    fn sized_block(rows: usize) -> Block {
+        let mut column_types = IndexMap::new();
+        column_types.insert("value".to_string(), Type::String);
+        let mut column_data = IndexMap::new();
+        column_data.insert(
+            "value".to_string(),
+            (0..rows)
+                .map(|i| Value::String(format!("value {}", i)))
+                .collect(),
+        );
+        Block {
+            info: BlockInfo::default(),
+            rows: rows as u64,
+            column_types,
+            column_data,
+        }
+    }
+
+    fn test_block() -> Block {
+        sized_block(1000)
+    }
+
+    fn count_frames(mut data: &[u8]) -> usize {
+        let mut frames = 0;
+        while !data.is_empty() {
+            let compressed_size = u32::from_le_bytes(data[17..21].try_into().unwrap());
+            data = &data[16 + compressed_size as usize..];
+            frames += 1;
+        }
+        frames
+    }
+
+    async fn roundtrip(compression: CompressionMethod) {
+        let block = test_block();
+        let mut out = vec![];
+        write_compressed_data(&mut out, compression, &block, 0)
+            .await
+            .unwrap();
+        assert_eq!(count_frames(&out[..]), 1);
+        let decoded = read_compressed_data(&mut &out[..], 0).await.unwrap();
+        assert_eq!(decoded.column_data, block.column_data);
+    }
+
+    #[tokio::test]
+    async fn test_multiple_frames() {
+        let block = sized_block(250_000);
+        let mut raw = vec![];
+        block.write(&mut raw, 0).await.unwrap();
+        assert!(raw.len() > 2 * MAX_COMPRESS_BLOCK_SIZE);
+        let mut out = vec![];
+        write_compressed_data(&mut out, CompressionMethod::None, &block, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            count_frames(&out[..]),
+            raw.len().div_ceil(MAX_COMPRESS_BLOCK_SIZE)
+        );
+        // followed by unrelated data, which must not be consumed
+        out.extend_from_slice(&[0xAB, 0xCD][..]);
+        let mut input = &out[..];
+        let decoded = read_compressed_data(&mut input, 0).await.unwrap();
+        assert_eq!(decoded.column_data, block.column_data);
+        assert_eq!(input, &[0xAB, 0xCD][..]);
+    }
+
+    #[tokio::test]
+    async fn test_truncated_frame() {
+        let mut out = vec![];
+        write_compressed_data(&mut out, CompressionMethod::None, &test_block(), 0)
+            .await
+            .unwrap();
+        out.truncate(out.len() - 10);
+        assert!(read_compressed_data(&mut &out[..], 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_roundtrip_none() {
+        roundtrip(CompressionMethod::None).await;
+    }
+
+    #[cfg(any(feature = "compression", feature = "lz4-pure"))]
+    #[tokio::test]
+    async fn test_roundtrip_lz4() {
+        roundtrip(CompressionMethod::LZ4).await;
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_roundtrip_zstd() {
+        roundtrip(CompressionMethod::ZSTD).await;
+    }
+
+    #[tokio::test]
+    async fn test_malformed_lz4() {
+        // valid checksum over a garbage payload
+        let mut frame = vec![CompressionMethod::LZ4.byte()];
+        frame.extend_from_slice(&(9u32 + 8).to_le_bytes()[..]);
+        frame.extend_from_slice(&1000u32.to_le_bytes()[..]);
+        frame.extend_from_slice(&[0xFF; 8][..]);
+        let hash = cityhash_102_128(&frame[..]);
+        let mut out = vec![];
+        out.extend_from_slice(&((hash >> 64) as u64).to_le_bytes()[..]);
+        out.extend_from_slice(&(hash as u64).to_le_bytes()[..]);
+        out.extend(frame);
+        assert!(read_compressed_data(&mut &out[..], 0).await.is_err());
+    }
+
+    #[cfg(any(feature = "compression", feature = "lz4-pure"))]
+    #[tokio::test]
+    async fn test_corrupt_checksum() {
+        let mut out = vec![];
+        write_compressed_data(&mut out, CompressionMethod::LZ4, &test_block(), 0)
+            .await
+            .unwrap();
+        let last = out.len() - 1;
+        out[last] ^= 0xFF;
+        assert!(read_compressed_data(&mut &out[..], 0).await.is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::marker::PhantomData;
+
+use crate::{block::Block, types::Type};
+use anyhow::*;
+
+use super::{Row, SqlType};
+
+/// Columns expected by a [`Row`], see [`Row::expected_columns`].
+#[derive(Debug, Clone, Default)]
+pub struct ExpectedColumns {
+    pub columns: Vec<ExpectedColumn>,
+    /// Whether columns that are not expected are an error, as with `#[klickhouse(deny_unknown_fields)]`.
+    pub deny_unknown: bool,
+}
+
+/// A column expected by a [`Row`].
+#[derive(Debug, Clone)]
+pub struct ExpectedColumn {
+    pub name: &'static str,
+    /// Expected Clickhouse type shown in errors: the `type` attribute of the field if any, otherwise its [`SqlType`] if implemented.
+    pub expected: Option<Type>,
+    /// Rust type of the field, shown in errors if the expected Clickhouse type is unknown.
+    pub rust_type: &'static str,
+    /// Whether the column may be absent, i.e. for fields with a default.
+    pub optional: bool,
+    /// Whether a column of the given type can be deserialized into the field, usually [`crate::FromSql::accepts_type`].
+    pub accepts: fn(&Type) -> bool,
+}
+
+impl ExpectedColumns {
+    /// Compares the expected columns to the actual columns of a query result, listing every missing, unexpected and mismatched column.
+    pub fn diff<'a>(&self, columns: impl IntoIterator<Item = (&'a str, &'a Type)>) -> Vec<String> {
+        let columns = columns.into_iter().collect::<Vec<_>>();
+        let mut out = vec![];
+        for expected in &self.columns {
+            if !expected.optional && !columns.iter().any(|(name, _)| *name == expected.name) {
+                out.push(format!("missing column `{}`", expected.name));
+            }
+        }
+        for (name, type_) in columns {
+            match self.columns.iter().find(|x| x.name == name) {
+                Some(expected) if !(expected.accepts)(type_) => match &expected.expected {
+                    Some(expected_type) => out.push(format!(
+                        "column `{}` has type {}, expected {}",
+                        name, type_, expected_type
+                    )),
+                    None => out.push(format!(
+                        "column `{}` has type {}, which cannot be converted to `{}`",
+                        name, type_, expected.rust_type
+                    )),
+                },
+                None if self.deny_unknown => out.push(format!("unexpected column `{}`", name)),
+                _ => (),
+            }
+        }
+        out
+    }
+}
+
+/// Probe for the [`SqlType`] of a field, used by `#[derive(Row)]` for [`ExpectedColumn::expected`].
+/// `(&ExpectedType::<T>::new()).expected_type()` resolves to [`ExpectedSqlType`] if `T: SqlType`, and to [`ExpectedTypeFallback`] otherwise.
+#[doc(hidden)]
+pub struct ExpectedType<T>(PhantomData<T>);
+
+impl<T> ExpectedType<T> {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        ExpectedType(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait ExpectedSqlType {
+    fn expected_type(&self) -> Option<Type>;
+}
+
+impl<T: SqlType> ExpectedSqlType for ExpectedType<T> {
+    fn expected_type(&self) -> Option<Type> {
+        Some(T::sql_type())
+    }
+}
+
+#[doc(hidden)]
+pub trait ExpectedTypeFallback {
+    fn expected_type(&self) -> Option<Type>;
+}
+
+impl<T> ExpectedTypeFallback for &ExpectedType<T> {
+    fn expected_type(&self) -> Option<Type> {
+        None
+    }
+}
+
+/// Checks the columns of the first block of a query result against the columns expected by `T`.
+pub(crate) fn check_block_columns<T: Row>(block: &Block) -> Result<()> {
+    let expected = match T::expected_columns() {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    let diff = expected.diff(
+        block
+            .column_types
+            .iter()
+            .map(|(name, type_)| (&name[..], type_.strip_low_cardinality())),
+    );
+    if diff.is_empty() {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "query columns do not match row `{}`:\n  {}",
+        std::any::type_name::<T>(),
+        diff.join("\n  ")
+    ))
+}
@@ -1,8 +1,23 @@
+use std::{cell::Cell, collections::BTreeMap, sync::RwLock};
+
 use crate::{types::Type, Value};
 use anyhow::*;
 
+pub(crate) use expected::check_block_columns;
+pub use expected::{
+    ExpectedColumn, ExpectedColumns, ExpectedSqlType, ExpectedType, ExpectedTypeFallback,
+};
+
+mod expected;
+#[cfg(test)]
+mod proptests;
+mod row;
+mod row_ref;
+mod sql_type;
 mod std_deserialize;
 mod std_serialize;
+#[cfg(test)]
+mod tests;
 
 /// A type that can be converted to a raw Clickhouse SQL value.
 pub trait ToSql {
@@ -15,13 +30,102 @@ impl ToSql for Value {
     }
 }
 
+/// A type with a default Clickhouse column type, used for the schema of `#[derive(Row)]` structs.
+/// I.e. `Option<String>` is `Nullable(String)`, and [`crate::DateTime64<3>`] is `DateTime64(3, 'UTC')`.
+pub trait SqlType {
+    fn sql_type() -> Type;
+}
+
 pub fn unexpected_type(type_: &Type) -> anyhow::Error {
     anyhow!("unexpected type: {}", type_.to_string())
 }
 
+pub fn unexpected_value(type_: &Type, value: &Value) -> anyhow::Error {
+    anyhow!("unexpected value for type {}: {:?}", type_, value)
+}
+
+thread_local! {
+    static LENIENT_COERCION: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `f` with lenient coercions enabled for any [`FromSql`] conversion within it.
+/// Lenient conversions accept lossless widening (i.e. `UInt32` into `u64`, `Float32` into `f64`, `Date` into [`crate::DateTime`], `Enum8` into `String`),
+/// and narrowing that is checked at runtime (i.e. `UInt64` into `u32` fails only if the value does not fit).
+/// Also available per field or struct with `#[klickhouse(lenient)]`, or per client with [`crate::ClientOptions::lenient_coercion`].
+pub fn with_lenient_coercion<R>(f: impl FnOnce() -> R) -> R {
+    struct Reset(bool);
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            LENIENT_COERCION.with(|x| x.set(self.0));
+        }
+    }
+
+    let _reset = Reset(LENIENT_COERCION.with(|x| x.replace(true)));
+    f()
+}
+
+/// Whether lenient coercions are enabled for [`FromSql`] conversions on this thread, see [`with_lenient_coercion`].
+pub fn is_lenient_coercion() -> bool {
+    LENIENT_COERCION.with(|x| x.get())
+}
+
+/// Strips `LowCardinality` and `Nullable` from `type_` for a lenient conversion, failing on null values.
+pub(crate) fn lenient_type<'a>(type_: &'a Type, value: &Value) -> Result<&'a Type> {
+    if value == &Value::Null {
+        return Err(anyhow!("unexpected null value for type {}", type_));
+    }
+    Ok(strip_lenient_type(type_))
+}
+
+/// The type seen by a lenient conversion of `type_`, as in [`lenient_type`].
+pub(crate) fn strip_lenient_type(type_: &Type) -> &Type {
+    type_
+        .strip_low_cardinality()
+        .strip_null()
+        .strip_low_cardinality()
+}
+
+/// Column names of a field flattened with `#[klickhouse(flatten(prefix = "..."))]`, declared as a static by `#[derive(Row)]`.
+/// Each prefixed name is built once and leaked, as rows serialize with `'static` column names.
+#[doc(hidden)]
+pub struct PrefixedColumnNames {
+    prefix: &'static str,
+    names: RwLock<BTreeMap<&'static str, &'static str>>,
+}
+
+impl PrefixedColumnNames {
+    pub const fn new(prefix: &'static str) -> Self {
+        PrefixedColumnNames {
+            prefix,
+            names: RwLock::new(BTreeMap::new()),
+        }
+    }
+
+    /// Joins the prefix and `name`.
+    pub fn get(&self, name: &'static str) -> &'static str {
+        if let Some(prefixed) = self.names.read().unwrap().get(name) {
+            return prefixed;
+        }
+        self.names
+            .write()
+            .unwrap()
+            .entry(name)
+            .or_insert_with(|| Box::leak(format!("{}{}", self.prefix, name).into_boxed_str()))
+    }
+}
+
 /// A type that can be converted from a raw Clickhouse SQL value.
 pub trait FromSql: Sized {
     fn from_sql(type_: &Type, value: Value) -> Result<Self>;
+
+    /// Whether values of `type_` can be converted by [`FromSql::from_sql`], judged from the type alone.
+    /// Used to check query results against [`Row::expected_columns`] before any row is deserialized.
+    /// Defaults to accepting any type, leaving mismatches to `from_sql`.
+    fn accepts_type(type_: &Type) -> bool {
+        let _ = type_;
+        true
+    }
 }
 
 impl FromSql for Value {
@@ -30,11 +134,49 @@ impl FromSql for Value {
     }
 }
 
+/// A type that can be borrowed from a raw Clickhouse SQL value held in a [`crate::Block`].
+/// Implemented for `&str`, `&[u8]`, `Cow<str>`, `Cow<[u8]>` and `&Value` (and `Option`s of the string types),
+/// and by cloning the value for every [`FromSql`] type.
+/// `Cow` implements only this trait, not [`FromSql`], as the blanket impl for [`FromSql`] types would overlap with the borrowing impls.
+pub trait FromSqlRef<'a>: Sized {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self>;
+}
+
+/// A row that can be deserialized borrowing from a [`crate::Block`], see [`crate::Block::deserialize_rows`].
+/// Generally this is not implemented manually, but using `#[derive(klickhouse::RowRef)]`.
+/// Columns are matched as for [`Row`].
+pub trait RowRef<'a>: Sized {
+    fn deserialize_row_ref(row: &[(&'a str, &'a Type, &'a Value)]) -> Result<Self>;
+}
+
 /// A row that can be deserialized and serialized from a raw Clickhouse SQL value.
 /// Generally this is not implemented manually, but using `klickhouse_derive::Row`.
 /// I.e. `#[derive(klickhouse::Row)]`.
+/// Rows are matched to columns by field name, except for tuples and tuple structs, which are matched by position.
+/// Positional rows serialize every field with an empty name.
+///
+/// Field attributes of the derive:
+/// * `default` / `default = "path"`: value of the field if its column is absent from a query result, from `Default::default()` or `path()`.
+/// * `skip_serializing_if = "path"`: omits the column from the inserted row if `path(&field)` is true.
+///   A column omitted by every row of a block is left to the server, so that a `DEFAULT` expression of the table applies,
+///   otherwise rows omitting it are filled with the default value of the column type.
+/// * `serialize_with = "path"`: serializes the field with `fn(T) -> Result<Value>` instead of [`ToSql`].
+/// * `deserialize_with = "path"`: deserializes the field with `fn(&Type, Value) -> Result<T>` instead of [`FromSql`].
+/// * `with = "module"`: both of the above, with `module::to_sql` and `module::from_sql`.
 pub trait Row: Sized {
     fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self>;
 
     fn serialize_row(self) -> Result<Vec<(&'static str, Value)>>;
+
+    /// Names of the columns read by [`Row::deserialize_row`], in order, or `None` for positional rows.
+    /// Used by [`crate::Client::select`] to request exactly the columns of the row.
+    fn column_names() -> Option<Vec<&'static str>> {
+        None
+    }
+
+    /// Columns expected by [`Row::deserialize_row`], checked once against the first block of a query result,
+    /// so that mismatches fail before any row is deserialized. `None` skips the check.
+    fn expected_columns() -> Option<ExpectedColumns> {
+        None
+    }
 }
@@ -0,0 +1,139 @@
+//! Round-trip property tests for the std and third-party collection and wrapper conversions.
+//! Each value goes through `ToSql`, is checked against its `SqlType`, and comes back through `FromSql`.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashSet, VecDeque},
+    fmt::Debug,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    num::{NonZeroI128, NonZeroI32, NonZeroI8, NonZeroU16, NonZeroU64},
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use indexmap::IndexSet;
+use proptest::prelude::*;
+
+use super::*;
+
+fn roundtrip<T: ToSql + FromSql + SqlType + Clone + PartialEq + Debug>(
+    value: T,
+) -> std::result::Result<(), TestCaseError> {
+    roundtrip_as(&T::sql_type(), value)
+}
+
+fn roundtrip_as<T: ToSql + FromSql + Clone + PartialEq + Debug>(
+    type_: &Type,
+    value: T,
+) -> std::result::Result<(), TestCaseError> {
+    let sql = value.clone().to_sql().unwrap();
+    prop_assert!(
+        type_.validate_value(&sql).is_ok(),
+        "{:?} is not a {}",
+        sql,
+        type_
+    );
+    prop_assert_eq!(T::from_sql(type_, sql).unwrap(), value);
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn sets_roundtrip(values in prop::collection::vec(any::<u32>(), 0..16)) {
+        roundtrip(values.iter().copied().collect::<VecDeque<_>>())?;
+        roundtrip(values.iter().copied().collect::<HashSet<_>>())?;
+        roundtrip(values.iter().copied().collect::<BTreeSet<_>>())?;
+        roundtrip(values.iter().copied().collect::<IndexSet<_>>())?;
+    }
+
+    #[test]
+    fn shared_roundtrip(value in any::<i64>(), text in ".{0,16}") {
+        roundtrip(Arc::new(value))?;
+        roundtrip(Rc::new(value))?;
+        roundtrip(Box::<str>::from(&*text))?;
+        roundtrip(Arc::<str>::from(&*text))?;
+        roundtrip(Rc::<str>::from(&*text))?;
+        // `Cow` has no `FromSql`, it is read back through `FromSqlRef`
+        let value = Cow::<str>::Borrowed(&text).to_sql().unwrap();
+        prop_assert_eq!(Cow::<str>::from_sql_ref(&Type::String, &value).unwrap(), Cow::<str>::Borrowed(&text));
+    }
+
+    #[test]
+    fn char_roundtrip(value in any::<char>()) {
+        roundtrip(value)?;
+    }
+
+    #[test]
+    fn non_zero_roundtrip(
+        a in any::<NonZeroU16>(),
+        b in any::<NonZeroU64>(),
+        c in any::<NonZeroI8>(),
+        d in any::<NonZeroI32>(),
+        e in any::<NonZeroI128>(),
+    ) {
+        roundtrip(a)?;
+        roundtrip(b)?;
+        roundtrip(c)?;
+        roundtrip(d)?;
+        roundtrip(e)?;
+    }
+
+    #[test]
+    fn ip_roundtrip(v4 in any::<[u8; 4]>(), v6 in any::<[u8; 16]>()) {
+        roundtrip(Ipv4Addr::from(v4))?;
+        roundtrip(Ipv6Addr::from(v6))?;
+        roundtrip_as(&Type::Ipv4, IpAddr::from(v4))?;
+        roundtrip_as(&Type::Ipv6, IpAddr::from(v6))?;
+    }
+
+    #[test]
+    fn system_time_roundtrip(secs in 0u64..(i64::MAX as u64 / 1_000_000_000), nanos in 0u32..1_000_000_000) {
+        roundtrip(UNIX_EPOCH + Duration::new(secs, nanos))?;
+    }
+}
+
+#[cfg(feature = "smallvec")]
+proptest! {
+    #[test]
+    fn small_vec_roundtrip(values in prop::collection::vec(any::<u8>(), 0..16)) {
+        roundtrip(values.into_iter().collect::<smallvec::SmallVec<[u8; 4]>>())?;
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+proptest! {
+    #[test]
+    fn array_vec_roundtrip(values in prop::collection::vec(any::<u8>(), 0..8)) {
+        roundtrip(values.into_iter().collect::<arrayvec::ArrayVec<u8, 8>>())?;
+    }
+}
+
+#[test]
+fn test_rejects_out_of_range() {
+    assert!(NonZeroU64::from_sql(&Type::UInt64, Value::UInt64(0)).is_err());
+    assert!(char::from_sql(&Type::String, Value::String("ab".to_string())).is_err());
+    assert!(char::from_sql(&Type::String, Value::String(String::new())).is_err());
+    assert!((UNIX_EPOCH - Duration::from_secs(1)).to_sql().is_err());
+    // DateTime64 ticks are signed, ending at 2262-04-11
+    let last = UNIX_EPOCH + Duration::from_nanos(i64::MAX as u64);
+    assert_eq!(
+        last.to_sql().unwrap(),
+        Value::DateTime64(chrono_tz::UTC, 9, i64::MAX as u64)
+    );
+    assert!((last + Duration::from_nanos(1)).to_sql().is_err());
+    assert_eq!(
+        SystemTime::from_sql(
+            &Type::DateTime64(3, chrono_tz::UTC),
+            Value::DateTime64(chrono_tz::UTC, 3, -1500i64 as u64)
+        )
+        .unwrap(),
+        UNIX_EPOCH - Duration::from_millis(1500)
+    );
+    #[cfg(feature = "arrayvec")]
+    assert!(arrayvec::ArrayVec::<u8, 1>::from_sql(
+        &Type::Array(Box::new(Type::UInt8)),
+        Value::Array(vec![Value::UInt8(1), Value::UInt8(2)])
+    )
+    .is_err());
+}
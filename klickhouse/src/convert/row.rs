@@ -0,0 +1,53 @@
+use crate::{
+    convert::{FromSql, Row, ToSql},
+    types::Type,
+    Value,
+};
+use anyhow::*;
+
+macro_rules! row_tuple_impls {
+    ($($len:expr => ($($n:tt $name:ident)+))+) => {
+        $(
+            impl<$($name: FromSql + ToSql),+> Row for ($($name,)+) {
+                fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> Result<Self> {
+                    if map.len() != $len {
+                        return Err(anyhow!("unexpected column count, got {} expecting {}", map.len(), $len));
+                    }
+                    let mut map = map.into_iter();
+                    Ok((
+                        $({
+                            let (name, type_, value) = map.next().unwrap();
+                            $name::from_sql(type_, value)
+                                .with_context(|| format!("failed to deserialize column `{}`", name))?
+                        },)+
+                    ))
+                }
+
+                fn serialize_row(self) -> Result<Vec<(&'static str, Value)>> {
+                    Ok(vec![
+                        $(("", self.$n.to_sql()?),)+
+                    ])
+                }
+            }
+        )+
+    }
+}
+
+row_tuple_impls! {
+    1 => (0 T0)
+    2 => (0 T0 1 T1)
+    3 => (0 T0 1 T1 2 T2)
+    4 => (0 T0 1 T1 2 T2 3 T3)
+    5 => (0 T0 1 T1 2 T2 3 T3 4 T4)
+    6 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5)
+    7 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6)
+    8 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7)
+    9 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8)
+    10 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9)
+    11 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10)
+    12 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11)
+    13 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12)
+    14 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13)
+    15 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14)
+    16 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15)
+}
@@ -0,0 +1,117 @@
+use std::borrow::Cow;
+
+use crate::{
+    convert::{unexpected_type, unexpected_value, FromSql, FromSqlRef, RowRef},
+    types::Type,
+    Value,
+};
+use anyhow::*;
+
+impl<'a, T: FromSql> FromSqlRef<'a> for T {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+        T::from_sql(type_, value.clone())
+    }
+}
+
+impl<'a> FromSqlRef<'a> for &'a str {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+        match (type_, value) {
+            (Type::String | Type::FixedString(_), Value::String(x)) => Ok(x),
+            (Type::String | Type::FixedString(_), value) => Err(unexpected_value(type_, value)),
+            _ => Err(unexpected_type(type_)),
+        }
+    }
+}
+
+impl<'a> FromSqlRef<'a> for &'a [u8] {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+        Ok(<&str>::from_sql_ref(type_, value)?.as_bytes())
+    }
+}
+
+impl<'a> FromSqlRef<'a> for Cow<'a, str> {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+        Ok(Cow::Borrowed(<&str>::from_sql_ref(type_, value)?))
+    }
+}
+
+impl<'a> FromSqlRef<'a> for Cow<'a, [u8]> {
+    fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+        Ok(Cow::Borrowed(<&[u8]>::from_sql_ref(type_, value)?))
+    }
+}
+
+impl<'a> FromSqlRef<'a> for &'a Value {
+    fn from_sql_ref(_type_: &'a Type, value: &'a Value) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+/// Borrowed conversion of a `Nullable` value, as [`FromSql`] for `Option<T>`.
+fn from_sql_ref_option<'a, T: FromSqlRef<'a>>(
+    type_: &'a Type,
+    value: &'a Value,
+) -> Result<Option<T>> {
+    let subtype = match type_ {
+        Type::Nullable(x) => x.strip_low_cardinality(),
+        x => return Err(unexpected_type(x)),
+    };
+    match value {
+        Value::Null => Ok(None),
+        x => Ok(Some(T::from_sql_ref(subtype, x)?)),
+    }
+}
+
+macro_rules! option_impls {
+    ($($t:ty),+) => {
+        $(
+            impl<'a> FromSqlRef<'a> for Option<$t> {
+                fn from_sql_ref(type_: &'a Type, value: &'a Value) -> Result<Self> {
+                    from_sql_ref_option(type_, value)
+                }
+            }
+        )+
+    };
+}
+
+option_impls!(&'a str, &'a [u8], Cow<'a, str>, Cow<'a, [u8]>);
+
+macro_rules! row_ref_tuple_impls {
+    ($($len:expr => ($($n:tt $name:ident)+))+) => {
+        $(
+            impl<'a, $($name: FromSqlRef<'a>),+> RowRef<'a> for ($($name,)+) {
+                fn deserialize_row_ref(row: &[(&'a str, &'a Type, &'a Value)]) -> Result<Self> {
+                    if row.len() != $len {
+                        return Err(anyhow!("unexpected column count, got {} expecting {}", row.len(), $len));
+                    }
+                    Ok((
+                        $({
+                            let (name, type_, value) = row[$n];
+                            $name::from_sql_ref(type_, value)
+                                .with_context(|| format!("failed to deserialize column `{}`", name))?
+                        },)+
+                    ))
+                }
+            }
+        )+
+    }
+}
+
+row_ref_tuple_impls! {
+    1 => (0 T0)
+    2 => (0 T0 1 T1)
+    3 => (0 T0 1 T1 2 T2)
+    4 => (0 T0 1 T1 2 T2 3 T3)
+    5 => (0 T0 1 T1 2 T2 3 T3 4 T4)
+    6 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5)
+    7 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6)
+    8 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7)
+    9 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8)
+    10 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9)
+    11 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10)
+    12 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11)
+    13 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12)
+    14 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13)
+    15 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14)
+    16 => (0 T0 1 T1 2 T2 3 T3 4 T4 5 T5 6 T6 7 T7 8 T8 9 T9 10 T10 11 T11 12 T12 13 T13 14 T14 15 T15)
+}
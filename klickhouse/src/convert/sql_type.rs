@@ -0,0 +1,228 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    net::{Ipv4Addr, Ipv6Addr},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+        NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+    rc::Rc,
+    sync::Arc,
+    time::SystemTime,
+};
+
+use chrono_tz::UTC;
+use indexmap::{IndexMap, IndexSet};
+
+use super::SqlType;
+use crate::{
+    i256, types::Type, u256, Date, DateTime, DateTime64, FixedPoint128, FixedPoint256,
+    FixedPoint32, FixedPoint64, Ipv4, Ipv6, Uuid,
+};
+
+macro_rules! sql_type_impls {
+    ($($t:ty => $type_:expr,)+) => {
+        $(
+            impl SqlType for $t {
+                fn sql_type() -> Type {
+                    $type_
+                }
+            }
+        )+
+    }
+}
+
+sql_type_impls! {
+    u8 => Type::UInt8,
+    u16 => Type::UInt16,
+    u32 => Type::UInt32,
+    u64 => Type::UInt64,
+    u128 => Type::UInt128,
+    u256 => Type::UInt256,
+    i8 => Type::Int8,
+    i16 => Type::Int16,
+    i32 => Type::Int32,
+    i64 => Type::Int64,
+    i128 => Type::Int128,
+    i256 => Type::Int256,
+    f32 => Type::Float32,
+    f64 => Type::Float64,
+    String => Type::String,
+    Uuid => Type::Uuid,
+    Date => Type::Date,
+    DateTime => Type::DateTime(UTC),
+    Ipv4 => Type::Ipv4,
+    Ipv6 => Type::Ipv6,
+    Ipv4Addr => Type::Ipv4,
+    Ipv6Addr => Type::Ipv6,
+    NonZeroU8 => Type::UInt8,
+    NonZeroU16 => Type::UInt16,
+    NonZeroU32 => Type::UInt32,
+    NonZeroU64 => Type::UInt64,
+    NonZeroU128 => Type::UInt128,
+    NonZeroI8 => Type::Int8,
+    NonZeroI16 => Type::Int16,
+    NonZeroI32 => Type::Int32,
+    NonZeroI64 => Type::Int64,
+    NonZeroI128 => Type::Int128,
+    char => Type::String,
+    Box<str> => Type::String,
+    Arc<str> => Type::String,
+    Rc<str> => Type::String,
+    SystemTime => Type::DateTime64(9, UTC),
+}
+
+impl<const PRECISION: usize> SqlType for DateTime64<PRECISION> {
+    fn sql_type() -> Type {
+        Type::DateTime64(PRECISION, UTC)
+    }
+}
+
+impl<const PRECISION: u64> SqlType for FixedPoint32<PRECISION> {
+    fn sql_type() -> Type {
+        Type::Decimal32(PRECISION as usize)
+    }
+}
+
+impl<const PRECISION: u64> SqlType for FixedPoint64<PRECISION> {
+    fn sql_type() -> Type {
+        Type::Decimal64(PRECISION as usize)
+    }
+}
+
+impl<const PRECISION: u64> SqlType for FixedPoint128<PRECISION> {
+    fn sql_type() -> Type {
+        Type::Decimal128(PRECISION as usize)
+    }
+}
+
+impl<const PRECISION: u64> SqlType for FixedPoint256<PRECISION> {
+    fn sql_type() -> Type {
+        Type::Decimal256(PRECISION as usize)
+    }
+}
+
+impl<T: SqlType> SqlType for Option<T> {
+    fn sql_type() -> Type {
+        Type::Nullable(Box::new(T::sql_type()))
+    }
+}
+
+impl<T: SqlType> SqlType for Vec<T> {
+    fn sql_type() -> Type {
+        Type::Array(Box::new(T::sql_type()))
+    }
+}
+
+impl<T: SqlType, const N: usize> SqlType for [T; N] {
+    fn sql_type() -> Type {
+        Type::Array(Box::new(T::sql_type()))
+    }
+}
+
+impl<T: SqlType> SqlType for Box<T> {
+    fn sql_type() -> Type {
+        T::sql_type()
+    }
+}
+
+impl<T: SqlType> SqlType for Arc<T> {
+    fn sql_type() -> Type {
+        T::sql_type()
+    }
+}
+
+impl<T: SqlType> SqlType for Rc<T> {
+    fn sql_type() -> Type {
+        T::sql_type()
+    }
+}
+
+impl<T: ToOwned + ?Sized> SqlType for Cow<'_, T>
+where
+    T::Owned: SqlType,
+{
+    fn sql_type() -> Type {
+        T::Owned::sql_type()
+    }
+}
+
+macro_rules! array_sql_type_impls {
+    ($($t:ident),+) => {
+        $(
+            impl<T: SqlType> SqlType for $t<T> {
+                fn sql_type() -> Type {
+                    Type::Array(Box::new(T::sql_type()))
+                }
+            }
+        )+
+    }
+}
+
+array_sql_type_impls!(VecDeque, HashSet, BTreeSet, IndexSet);
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> SqlType for smallvec::SmallVec<A>
+where
+    A::Item: SqlType,
+{
+    fn sql_type() -> Type {
+        Type::Array(Box::new(A::Item::sql_type()))
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: SqlType, const CAP: usize> SqlType for arrayvec::ArrayVec<T, CAP> {
+    fn sql_type() -> Type {
+        Type::Array(Box::new(T::sql_type()))
+    }
+}
+
+impl<T: SqlType, Y: SqlType> SqlType for HashMap<T, Y> {
+    fn sql_type() -> Type {
+        Type::Map(Box::new(T::sql_type()), Box::new(Y::sql_type()))
+    }
+}
+
+impl<T: SqlType, Y: SqlType> SqlType for BTreeMap<T, Y> {
+    fn sql_type() -> Type {
+        Type::Map(Box::new(T::sql_type()), Box::new(Y::sql_type()))
+    }
+}
+
+impl<T: SqlType, Y: SqlType> SqlType for IndexMap<T, Y> {
+    fn sql_type() -> Type {
+        Type::Map(Box::new(T::sql_type()), Box::new(Y::sql_type()))
+    }
+}
+
+macro_rules! tuple_impls {
+    ($($len:expr => ($($name:ident)+))+) => {
+        $(
+            impl<$($name: SqlType),+> SqlType for ($($name,)+) {
+                fn sql_type() -> Type {
+                    Type::Tuple(vec![$($name::sql_type(),)+])
+                }
+            }
+        )+
+    }
+}
+
+tuple_impls! {
+    1 => (T0)
+    2 => (T0 T1)
+    3 => (T0 T1 T2)
+    4 => (T0 T1 T2 T3)
+    5 => (T0 T1 T2 T3 T4)
+    6 => (T0 T1 T2 T3 T4 T5)
+    7 => (T0 T1 T2 T3 T4 T5 T6)
+    8 => (T0 T1 T2 T3 T4 T5 T6 T7)
+    9 => (T0 T1 T2 T3 T4 T5 T6 T7 T8)
+    10 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9)
+    11 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10)
+    12 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11)
+    13 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12)
+    14 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13)
+    15 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14)
+    16 => (T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15)
+}
@@ -1,164 +1,201 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    convert::TryFrom,
     hash::Hash,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+        NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+    rc::Rc,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 
 use super::*;
+use crate::{types::IntValue, Ipv4, Ipv6};
 
-impl FromSql for u8 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt8) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt8(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+fn lenient_int<T>(type_: &Type, value: Value) -> Result<T>
+where
+    T: TryFrom<i128> + TryFrom<u128>,
+{
+    lenient_type(type_, &value)?;
+    let out = match IntValue::from_value(&value) {
+        Some(IntValue::Signed(x, _)) => T::try_from(x).ok(),
+        Some(IntValue::Unsigned(x, _)) => T::try_from(x).ok(),
+        None => return Err(unexpected_type(type_)),
+    };
+    out.ok_or_else(|| {
+        anyhow!(
+            "value {:?} out of range for {}",
+            value,
+            std::any::type_name::<T>()
+        )
+    })
 }
 
-impl FromSql for u16 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt16) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt16(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+/// Whether `type_` holds integers accepted by a lenient integer conversion, checked for range per value.
+fn is_lenient_int_type(type_: &Type) -> bool {
+    matches!(
+        strip_lenient_type(type_),
+        Type::Int8
+            | Type::Int16
+            | Type::Int32
+            | Type::Int64
+            | Type::Int128
+            | Type::UInt8
+            | Type::UInt16
+            | Type::UInt32
+            | Type::UInt64
+            | Type::UInt128
+    )
 }
 
-impl FromSql for u32 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt32(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
-}
+macro_rules! int_from_sql {
+    ($($t:ty => $variant:ident),+) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+                    match (type_, value) {
+                        (Type::$variant, Value::$variant(x)) => Ok(x),
+                        (_, value) if is_lenient_coercion() => lenient_int(type_, value),
+                        _ => Err(unexpected_type(type_)),
+                    }
+                }
 
-impl FromSql for u64 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt64(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+                fn accepts_type(type_: &Type) -> bool {
+                    match type_ {
+                        Type::$variant => true,
+                        _ => is_lenient_coercion() && is_lenient_int_type(type_),
+                    }
+                }
+            }
+        )+
+    };
 }
 
-impl FromSql for u128 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt128) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::UInt128(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+int_from_sql! {
+    u8 => UInt8,
+    u16 => UInt16,
+    u32 => UInt32,
+    u64 => UInt64,
+    u128 => UInt128,
+    i8 => Int8,
+    i16 => Int16,
+    i32 => Int32,
+    i64 => Int64,
+    i128 => Int128
 }
 
-impl FromSql for i8 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int8) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int8(x) => Ok(x),
-            _ => unimplemented!(),
-        }
+/// Converts an integer to a float, failing if it is not exactly representable.
+fn lenient_int_float(type_: &Type, value: &Value) -> Result<f64> {
+    let (out, exact) = match IntValue::from_value(value) {
+        Some(IntValue::Signed(x, _)) => (x as f64, (x as f64) as i128 == x),
+        Some(IntValue::Unsigned(x, _)) => (x as f64, (x as f64) as u128 == x),
+        None => return Err(unexpected_type(type_)),
+    };
+    if !exact {
+        return Err(anyhow!(
+            "value {:?} cannot be represented exactly as a float",
+            value
+        ));
     }
+    Ok(out)
 }
 
-impl FromSql for i16 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int16) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int16(x) => Ok(x),
-            _ => unimplemented!(),
-        }
-    }
+fn is_lenient_float_type(type_: &Type) -> bool {
+    matches!(strip_lenient_type(type_), Type::Float32 | Type::Float64) || is_lenient_int_type(type_)
 }
 
-impl FromSql for i32 {
+impl FromSql for f32 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int32(x) => Ok(x),
-            _ => unimplemented!(),
+        match (type_, value) {
+            (Type::Float32, Value::Float32(x)) => Ok(f32::from_bits(x)),
+            (_, value) if is_lenient_coercion() => {
+                lenient_type(type_, &value)?;
+                let out = match &value {
+                    Value::Float32(x) => return Ok(f32::from_bits(*x)),
+                    Value::Float64(x) => f64::from_bits(*x),
+                    value => lenient_int_float(type_, value)?,
+                };
+                if !out.is_nan() && (out as f32) as f64 != out {
+                    return Err(anyhow!(
+                        "value {} cannot be represented exactly as f32",
+                        out
+                    ));
+                }
+                Ok(out as f32)
+            }
+            _ => Err(unexpected_type(type_)),
         }
     }
-}
 
-impl FromSql for i64 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int64(x) => Ok(x),
-            _ => unimplemented!(),
+    fn accepts_type(type_: &Type) -> bool {
+        match type_ {
+            Type::Float32 => true,
+            _ => is_lenient_coercion() && is_lenient_float_type(type_),
         }
     }
 }
 
-impl FromSql for i128 {
+impl FromSql for f64 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int128) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Int128(x) => Ok(x),
-            _ => unimplemented!(),
+        match (type_, value) {
+            (Type::Float64, Value::Float64(x)) => Ok(f64::from_bits(x)),
+            (_, value) if is_lenient_coercion() => {
+                lenient_type(type_, &value)?;
+                match &value {
+                    Value::Float32(x) => Ok(f32::from_bits(*x) as f64),
+                    Value::Float64(x) => Ok(f64::from_bits(*x)),
+                    value => lenient_int_float(type_, value),
+                }
+            }
+            _ => Err(unexpected_type(type_)),
         }
     }
-}
 
-impl FromSql for f32 {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Float32) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Float32(x) => Ok(f32::from_bits(x)),
-            _ => unimplemented!(),
+    fn accepts_type(type_: &Type) -> bool {
+        match type_ {
+            Type::Float64 => true,
+            _ => is_lenient_coercion() && is_lenient_float_type(type_),
         }
     }
 }
 
-impl FromSql for f64 {
+impl FromSql for String {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Float64) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::Float64(x) => Ok(f64::from_bits(x)),
-            _ => unimplemented!(),
+        match (type_, value) {
+            (Type::String | Type::FixedString(_), Value::String(x)) => Ok(x),
+            (_, value) if is_lenient_coercion() => match (lenient_type(type_, &value)?, value) {
+                (Type::String | Type::FixedString(_), Value::String(x)) => Ok(x),
+                (Type::Enum8(entries), Value::Enum8(index)) => entries
+                    .iter()
+                    .find(|x| x.1 == index)
+                    .map(|x| x.0.clone())
+                    .ok_or_else(|| anyhow!("unknown enum value {}", index)),
+                (Type::Enum16(entries), Value::Enum16(index)) => entries
+                    .iter()
+                    .find(|x| x.1 == index)
+                    .map(|x| x.0.clone())
+                    .ok_or_else(|| anyhow!("unknown enum value {}", index)),
+                _ => Err(unexpected_type(type_)),
+            },
+            _ => Err(unexpected_type(type_)),
         }
     }
-}
 
-impl FromSql for String {
-    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::String | Type::FixedString(_)) {
-            return Err(unexpected_type(type_));
-        }
-        match value {
-            Value::String(x) => Ok(x),
-            _ => unimplemented!(),
+    fn accepts_type(type_: &Type) -> bool {
+        match type_ {
+            Type::String | Type::FixedString(_) => true,
+            _ => {
+                is_lenient_coercion()
+                    && matches!(
+                        strip_lenient_type(type_),
+                        Type::String | Type::FixedString(_) | Type::Enum8(_) | Type::Enum16(_)
+                    )
+            }
         }
     }
 }
@@ -166,7 +203,7 @@ impl FromSql for String {
 impl<T: FromSql> FromSql for Vec<T> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
         let subtype = match type_ {
-            Type::Array(x) => &**x,
+            Type::Array(x) => x,
             x => return Err(unexpected_type(x)),
         }
         .strip_low_cardinality();
@@ -175,9 +212,13 @@ impl<T: FromSql> FromSql for Vec<T> {
                 .into_iter()
                 .map(|x| T::from_sql(subtype, x))
                 .collect::<Result<Vec<_>>>()?),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
 }
 
 impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for HashMap<T, Y> {
@@ -192,14 +233,18 @@ impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for HashMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = HashMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        map_accepts_type::<T, Y>(type_)
+    }
 }
 
 impl<T: FromSql + Ord, Y: FromSql> FromSql for BTreeMap<T, Y> {
@@ -214,14 +259,18 @@ impl<T: FromSql + Ord, Y: FromSql> FromSql for BTreeMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = BTreeMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        map_accepts_type::<T, Y>(type_)
+    }
 }
 
 impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for IndexMap<T, Y> {
@@ -236,20 +285,25 @@ impl<T: FromSql + Hash + Eq, Y: FromSql> FromSql for IndexMap<T, Y> {
         match value {
             Value::Map(x, y) => {
                 let mut out = IndexMap::new();
-                for (x, y) in x.into_iter().zip(y.into_iter()) {
+                for (x, y) in x.into_iter().zip(y) {
                     out.insert(T::from_sql(x_type, x)?, Y::from_sql(y_type, y)?);
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        map_accepts_type::<T, Y>(type_)
+    }
 }
 
 impl<T: FromSql> FromSql for Option<T> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
         let subtype = match type_ {
             Type::Nullable(x) => x.strip_low_cardinality(),
+            x if is_lenient_coercion() => return Ok(Some(T::from_sql(x, value)?)),
             x => return Err(unexpected_type(x)),
         };
         match value {
@@ -257,6 +311,13 @@ impl<T: FromSql> FromSql for Option<T> {
             x => Ok(Some(T::from_sql(subtype, x)?)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        match type_ {
+            Type::Nullable(x) => T::accepts_type(x.strip_low_cardinality()),
+            x => is_lenient_coercion() && T::accepts_type(x),
+        }
+    }
 }
 
 #[cfg(const_generics)]
@@ -281,15 +342,282 @@ impl<T: FromSql + Default + Copy, const N: usize> FromSql for [T; N] {
                 }
                 Ok(out)
             }
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
 }
 
 impl<T: FromSql> FromSql for Box<T> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
         Ok(Box::new(T::from_sql(type_, value)?))
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        T::accepts_type(type_)
+    }
+}
+
+fn array_accepts_type<T: FromSql>(type_: &Type) -> bool {
+    matches!(type_, Type::Array(x) if T::accepts_type(x.strip_low_cardinality()))
+}
+
+fn map_accepts_type<T: FromSql, Y: FromSql>(type_: &Type) -> bool {
+    matches!(type_, Type::Map(x, y)
+        if T::accepts_type(x.strip_low_cardinality()) && Y::accepts_type(y.strip_low_cardinality()))
+}
+
+/// Converts the values of an `Array` with [`FromSql`], for collections other than `Vec`.
+fn array_from_sql<T: FromSql>(
+    type_: &Type,
+    value: Value,
+) -> Result<impl Iterator<Item = Result<T>> + '_> {
+    let subtype = match type_ {
+        Type::Array(x) => x.strip_low_cardinality(),
+        x => return Err(unexpected_type(x)),
+    };
+    match value {
+        Value::Array(x) => Ok(x.into_iter().map(move |x| T::from_sql(subtype, x))),
+        value => Err(unexpected_value(type_, &value)),
+    }
+}
+
+impl<T: FromSql> FromSql for VecDeque<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        array_from_sql(type_, value)?.collect()
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
+}
+
+impl<T: FromSql + Hash + Eq> FromSql for HashSet<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        array_from_sql(type_, value)?.collect()
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
+}
+
+impl<T: FromSql + Ord> FromSql for BTreeSet<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        array_from_sql(type_, value)?.collect()
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
+}
+
+impl<T: FromSql + Hash + Eq> FromSql for IndexSet<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        array_from_sql(type_, value)?.collect()
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> FromSql for smallvec::SmallVec<A>
+where
+    A::Item: FromSql,
+{
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        array_from_sql(type_, value)?.collect()
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<A::Item>(type_)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: FromSql, const CAP: usize> FromSql for arrayvec::ArrayVec<T, CAP> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        let mut out = arrayvec::ArrayVec::new();
+        for value in array_from_sql(type_, value)? {
+            out.try_push(value?)
+                .map_err(|_| anyhow!("array too long for ArrayVec of capacity {}", CAP))?;
+        }
+        Ok(out)
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        array_accepts_type::<T>(type_)
+    }
+}
+
+impl<T: FromSql> FromSql for Arc<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Arc::new(T::from_sql(type_, value)?))
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        T::accepts_type(type_)
+    }
+}
+
+impl<T: FromSql> FromSql for Rc<T> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Rc::new(T::from_sql(type_, value)?))
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        T::accepts_type(type_)
+    }
+}
+
+impl FromSql for Box<str> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(String::from_sql(type_, value)?.into())
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        String::accepts_type(type_)
+    }
+}
+
+impl FromSql for Arc<str> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(String::from_sql(type_, value)?.into())
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        String::accepts_type(type_)
+    }
+}
+
+impl FromSql for Rc<str> {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(String::from_sql(type_, value)?.into())
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        String::accepts_type(type_)
+    }
+}
+
+/// Strings of exactly one character.
+impl FromSql for char {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        let text = String::from_sql(type_, value)?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(x), None) => Ok(x),
+            _ => Err(anyhow!("expected a single character, got {:?}", text)),
+        }
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        String::accepts_type(type_)
+    }
+}
+
+macro_rules! non_zero_from_sql {
+    ($($t:ty => $inner:ty),+) => {
+        $(
+            impl FromSql for $t {
+                fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+                    <$t>::new(<$inner>::from_sql(type_, value)?)
+                        .ok_or_else(|| anyhow!("unexpected zero for {}", stringify!($t)))
+                }
+
+                fn accepts_type(type_: &Type) -> bool {
+                    <$inner>::accepts_type(type_)
+                }
+            }
+        )+
+    };
+}
+
+non_zero_from_sql! {
+    NonZeroU8 => u8,
+    NonZeroU16 => u16,
+    NonZeroU32 => u32,
+    NonZeroU64 => u64,
+    NonZeroU128 => u128,
+    NonZeroI8 => i8,
+    NonZeroI16 => i16,
+    NonZeroI32 => i32,
+    NonZeroI64 => i64,
+    NonZeroI128 => i128
+}
+
+impl FromSql for Ipv4Addr {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Ipv4::from_sql(type_, value)?.0)
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        Ipv4::accepts_type(type_)
+    }
+}
+
+impl FromSql for Ipv6Addr {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        Ok(Ipv6::from_sql(type_, value)?.0)
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        Ipv6::accepts_type(type_)
+    }
+}
+
+/// From either an `IPv4` or an `IPv6` column.
+impl FromSql for IpAddr {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        match type_ {
+            Type::Ipv4 => Ok(IpAddr::V4(Ipv4Addr::from_sql(type_, value)?)),
+            _ => Ok(IpAddr::V6(Ipv6Addr::from_sql(type_, value)?)),
+        }
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Ipv4 | Type::Ipv6)
+    }
+}
+
+/// From a `DateTime`, or a `DateTime64` of precision up to 9. `DateTime64` ticks are signed, so they may be before the epoch.
+impl FromSql for SystemTime {
+    fn from_sql(type_: &Type, value: Value) -> Result<Self> {
+        let (before_epoch, since_epoch) = match (type_, value) {
+            (Type::DateTime(_), Value::DateTime(x)) => (false, Duration::from_secs(x.1 as u64)),
+            (Type::DateTime64(precision, _), Value::DateTime64(_, _, ticks)) if *precision <= 9 => {
+                let ticks = ticks as i64;
+                let modulus = 10u64.pow(*precision as u32);
+                let magnitude = ticks.unsigned_abs();
+                (
+                    ticks < 0,
+                    Duration::new(
+                        magnitude / modulus,
+                        ((magnitude % modulus) * 10u64.pow(9 - *precision as u32)) as u32,
+                    ),
+                )
+            }
+            (Type::DateTime(_) | Type::DateTime64(..), value) => {
+                return Err(unexpected_value(type_, &value))
+            }
+            _ => return Err(unexpected_type(type_)),
+        };
+        let time = if before_epoch {
+            UNIX_EPOCH.checked_sub(since_epoch)
+        } else {
+            UNIX_EPOCH.checked_add(since_epoch)
+        };
+        time.ok_or_else(|| anyhow!("time out of range for SystemTime"))
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::DateTime(_) | Type::DateTime64(0..=9, _))
+    }
 }
 
 macro_rules! tuple_impls {
@@ -303,7 +631,7 @@ macro_rules! tuple_impls {
                     };
                     let values = match value {
                         Value::Tuple(n) => n,
-                        _ => unimplemented!(),
+                        value => return Err(unexpected_value(type_, &value)),
                     };
                     if values.len() != subtype.len() {
                         return Err(anyhow!("mismatch tuple length {} vs {}", values.len(), subtype.len()));
@@ -318,6 +646,15 @@ macro_rules! tuple_impls {
                         )+
                     ))
                 }
+
+                fn accepts_type(type_: &Type) -> bool {
+                    match type_ {
+                        Type::Tuple(x) if x.len() == $len => {
+                            $($name::accepts_type(x[$n].strip_low_cardinality()))&&+
+                        }
+                        _ => false,
+                    }
+                }
             }
         )+
     }
@@ -1,6 +1,19 @@
-use std::collections::{BTreeMap, HashMap};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    convert::TryFrom,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+        NonZeroU32, NonZeroU64, NonZeroU8,
+    },
+    rc::Rc,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use indexmap::IndexMap;
+use chrono_tz::UTC;
+use indexmap::{IndexMap, IndexSet};
 
 use super::*;
 
@@ -82,6 +95,12 @@ impl ToSql for String {
     }
 }
 
+impl ToSql for &str {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
 impl<T: ToSql> ToSql for Vec<T> {
     fn to_sql(self) -> Result<Value> {
         Ok(Value::Array(
@@ -141,20 +160,20 @@ impl<T: ToSql> ToSql for Option<T> {
 impl<T: ToSql, const N: usize> ToSql for [T; N] {
     fn to_sql(self) -> Result<Value> {
         Ok(Value::Array(
-            std::array::IntoIter::new(self)
+            IntoIterator::into_iter(self)
                 .map(|x| x.to_sql())
                 .collect::<Result<Vec<_>>>()?,
         ))
     }
 }
 
-impl<'a, T: ToSql + Copy> ToSql for &'a T {
+impl<T: ToSql + Copy> ToSql for &T {
     fn to_sql(self) -> Result<Value> {
         (*self).to_sql()
     }
 }
 
-impl<'a, T: ToSql + Copy> ToSql for &'a mut T {
+impl<T: ToSql + Copy> ToSql for &mut T {
     fn to_sql(self) -> Result<Value> {
         (*self).to_sql()
     }
@@ -166,6 +185,167 @@ impl<T: ToSql> ToSql for Box<T> {
     }
 }
 
+fn array_to_sql<T: ToSql>(values: impl IntoIterator<Item = T>) -> Result<Value> {
+    Ok(Value::Array(
+        values
+            .into_iter()
+            .map(|x| x.to_sql())
+            .collect::<Result<Vec<_>>>()?,
+    ))
+}
+
+impl<T: ToSql> ToSql for VecDeque<T> {
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+impl<T: ToSql> ToSql for HashSet<T> {
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+impl<T: ToSql> ToSql for BTreeSet<T> {
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+impl<T: ToSql> ToSql for IndexSet<T> {
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> ToSql for smallvec::SmallVec<A>
+where
+    A::Item: ToSql,
+{
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: ToSql, const CAP: usize> ToSql for arrayvec::ArrayVec<T, CAP> {
+    fn to_sql(self) -> Result<Value> {
+        array_to_sql(self)
+    }
+}
+
+/// Shared values are cloned unless this is the last reference.
+impl<T: ToSql + Clone> ToSql for Arc<T> {
+    fn to_sql(self) -> Result<Value> {
+        Arc::try_unwrap(self)
+            .unwrap_or_else(|x| (*x).clone())
+            .to_sql()
+    }
+}
+
+/// Shared values are cloned unless this is the last reference.
+impl<T: ToSql + Clone> ToSql for Rc<T> {
+    fn to_sql(self) -> Result<Value> {
+        Rc::try_unwrap(self)
+            .unwrap_or_else(|x| (*x).clone())
+            .to_sql()
+    }
+}
+
+/// `Cow` is only serialized: there is no [`crate::FromSql`] impl, as it would overlap with the borrowing
+/// [`crate::FromSqlRef`] impls for `Cow<str>` and `Cow<[u8]>`. Read `Cow` fields with [`crate::RowRef`], or use the owned type in a [`crate::Row`].
+impl<T: ToOwned + ?Sized> ToSql for Cow<'_, T>
+where
+    T::Owned: ToSql,
+{
+    fn to_sql(self) -> Result<Value> {
+        self.into_owned().to_sql()
+    }
+}
+
+impl ToSql for Box<str> {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::String(self.into()))
+    }
+}
+
+impl ToSql for Arc<str> {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+impl ToSql for Rc<str> {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+impl ToSql for char {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+macro_rules! non_zero_to_sql {
+    ($($t:ty),+) => {
+        $(
+            impl ToSql for $t {
+                fn to_sql(self) -> Result<Value> {
+                    self.get().to_sql()
+                }
+            }
+        )+
+    };
+}
+
+non_zero_to_sql!(
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128
+);
+
+impl ToSql for Ipv4Addr {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::Ipv4(self.into()))
+    }
+}
+
+impl ToSql for Ipv6Addr {
+    fn to_sql(self) -> Result<Value> {
+        Ok(Value::Ipv6(self.into()))
+    }
+}
+
+impl ToSql for IpAddr {
+    fn to_sql(self) -> Result<Value> {
+        match self {
+            IpAddr::V4(x) => x.to_sql(),
+            IpAddr::V6(x) => x.to_sql(),
+        }
+    }
+}
+
+/// Serialized as a `DateTime64(9, 'UTC')`, whose ticks are an `Int64`, covering times from the epoch until 2262-04-11.
+impl ToSql for SystemTime {
+    fn to_sql(self) -> Result<Value> {
+        let since_epoch = self
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| anyhow!("time {:?} is before the unix epoch", self))?;
+        let nanos = i64::try_from(since_epoch.as_nanos())
+            .map_err(|_| anyhow!("time {:?} is out of range for DateTime64(9)", self))?;
+        Ok(Value::DateTime64(UTC, 9, nanos as u64))
+    }
+}
+
 macro_rules! tuple_impls {
     ($($len:expr => ($($n:tt $name:ident)+))+) => {
         $(
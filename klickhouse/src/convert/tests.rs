@@ -0,0 +1,121 @@
+use crate::{Date, DateTime};
+
+use super::*;
+
+#[test]
+fn test_strict_by_default() {
+    assert!(!is_lenient_coercion());
+    assert!(u64::from_sql(&Type::UInt32, Value::UInt32(1)).is_err());
+    assert!(f64::from_sql(&Type::Float32, Value::Float32(1.0_f32.to_bits())).is_err());
+    assert_eq!(u32::from_sql(&Type::UInt32, Value::UInt32(1)).unwrap(), 1);
+}
+
+#[test]
+fn test_lenient_widening() {
+    with_lenient_coercion(|| {
+        assert!(is_lenient_coercion());
+        assert_eq!(u64::from_sql(&Type::UInt32, Value::UInt32(7)).unwrap(), 7);
+        assert_eq!(i64::from_sql(&Type::UInt32, Value::UInt32(7)).unwrap(), 7);
+        assert_eq!(i128::from_sql(&Type::Int8, Value::Int8(-7)).unwrap(), -7);
+        assert_eq!(
+            f64::from_sql(&Type::Float32, Value::Float32(1.5_f32.to_bits())).unwrap(),
+            1.5
+        );
+        assert_eq!(f64::from_sql(&Type::Int32, Value::Int32(-2)).unwrap(), -2.0);
+        assert_eq!(
+            DateTime::from_sql(&Type::Date, Value::Date(Date(2))).unwrap(),
+            DateTime(chrono_tz::UTC, 2 * 86400)
+        );
+        assert_eq!(
+            String::from_sql(
+                &Type::Enum8(vec![("a".to_string(), 1), ("b".to_string(), 2)]),
+                Value::Enum8(2)
+            )
+            .unwrap(),
+            "b"
+        );
+        assert_eq!(
+            String::from_sql(
+                &Type::LowCardinality(Box::new(Type::FixedString(3))),
+                Value::String("abc".to_string())
+            )
+            .unwrap(),
+            "abc"
+        );
+        assert_eq!(
+            Option::<u64>::from_sql(&Type::UInt8, Value::UInt8(3)).unwrap(),
+            Some(3)
+        );
+    });
+    assert!(!is_lenient_coercion());
+}
+
+#[test]
+fn test_lenient_narrowing() {
+    with_lenient_coercion(|| {
+        assert_eq!(
+            u8::from_sql(&Type::UInt64, Value::UInt64(255)).unwrap(),
+            255
+        );
+        assert!(u8::from_sql(&Type::UInt64, Value::UInt64(256)).is_err());
+        assert!(u32::from_sql(&Type::Int32, Value::Int32(-1)).is_err());
+        assert_eq!(
+            i8::from_sql(&Type::Int64, Value::Int64(-128)).unwrap(),
+            -128
+        );
+        assert_eq!(
+            f32::from_sql(&Type::Float64, Value::Float64(0.5_f64.to_bits())).unwrap(),
+            0.5
+        );
+        assert!(f32::from_sql(&Type::Float64, Value::Float64(0.1_f64.to_bits())).is_err());
+        assert!(f64::from_sql(&Type::UInt64, Value::UInt64(u64::MAX - 1)).is_err());
+        assert!(u32::from_sql(&Type::Nullable(Box::new(Type::UInt32)), Value::Null).is_err());
+        assert!(u32::from_sql(&Type::String, Value::String("1".to_string())).is_err());
+    });
+}
+
+#[test]
+fn test_lenient_nested() {
+    with_lenient_coercion(|| {
+        with_lenient_coercion(|| ());
+        // still enabled after an inner scope ends
+        assert!(is_lenient_coercion());
+        assert_eq!(
+            Vec::<u64>::from_sql(
+                &Type::Array(Box::new(Type::UInt16)),
+                Value::Array(vec![Value::UInt16(1), Value::UInt16(2)])
+            )
+            .unwrap(),
+            vec![1, 2]
+        );
+    });
+}
+
+#[test]
+fn test_accepts_type() {
+    let nullable = Type::Nullable(Box::new(Type::UInt32));
+    assert!(u32::accepts_type(&Type::UInt32));
+    assert!(!u64::accepts_type(&Type::UInt32));
+    assert!(!u32::accepts_type(&nullable));
+    assert!(Option::<u32>::accepts_type(&nullable));
+    assert!(Vec::<String>::accepts_type(&Type::Array(Box::new(
+        Type::LowCardinality(Box::new(Type::String))
+    ))));
+    assert!(!<(u8, u8)>::accepts_type(&Type::Tuple(vec![Type::UInt8])));
+    with_lenient_coercion(|| {
+        assert!(u64::accepts_type(&Type::UInt32));
+        assert!(u8::accepts_type(&nullable));
+        assert!(f64::accepts_type(&Type::Int8));
+        assert!(String::accepts_type(&Type::Enum8(vec![])));
+        assert!(!u32::accepts_type(&Type::String));
+    });
+}
+
+#[test]
+fn test_prefixed_column_names() {
+    static PREFIXED: PrefixedColumnNames = PrefixedColumnNames::new("geo_");
+    let lat = PREFIXED.get("lat");
+    assert_eq!(lat, "geo_lat");
+    assert!(std::ptr::eq(lat, PREFIXED.get("lat")));
+    assert_eq!(PREFIXED.get("lon"), "geo_lon");
+}
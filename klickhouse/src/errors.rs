@@ -1,3 +1,4 @@
+use crate::{types::Type, Value};
 use anyhow::*;
 
 pub fn missing_field(name: &'static str) -> Error {
@@ -7,3 +8,27 @@ pub fn missing_field(name: &'static str) -> Error {
 pub fn duplicate_field(name: &'static str) -> Error {
     anyhow!("duplicate field '{}' in struct", name)
 }
+
+pub fn unexpected_column(index: usize, name: &str) -> Error {
+    anyhow!("unexpected column {} '{}' for tuple struct", index, name)
+}
+
+pub fn unknown_field(name: &str) -> Error {
+    anyhow!("unknown column '{}' for struct", name)
+}
+
+pub fn unexpected_type(type_: &Type) -> Error {
+    crate::convert::unexpected_type(type_)
+}
+
+pub fn unexpected_value(type_: &Type, value: &Value) -> Error {
+    crate::convert::unexpected_value(type_, value)
+}
+
+pub fn tuple_length(got: usize, expected: usize) -> Error {
+    anyhow!(
+        "unexpected tuple length, got {} expecting {}",
+        got,
+        expected
+    )
+}
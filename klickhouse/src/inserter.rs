@@ -0,0 +1,475 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::*;
+use tokio::{
+    select,
+    sync::{mpsc, oneshot},
+    time::{sleep_until, Instant},
+};
+
+use crate::{
+    client::{
+        build_block, take_insert_error, Client, InsertSchema, QueryResponse, ResponseReceiver,
+        SerializedRow,
+    },
+    convert::Row,
+    values::Value,
+    Block,
+};
+
+/// Callback receiving rows rejected under [`InsertErrorPolicy::DeadLetter`].
+pub type DeadLetterCallback = Arc<dyn Fn(InsertRowError) + Send + Sync>;
+
+/// What to do with rows that fail to serialize or do not match the columns of the table during an insert.
+#[derive(Clone, Default)]
+pub enum InsertErrorPolicy {
+    /// Fail the whole insert on the first bad row.
+    #[default]
+    Fail,
+    /// Skip bad rows, reporting them in the returned [`InsertReport`].
+    Skip,
+    /// Skip bad rows, passing each one to a callback.
+    DeadLetter(DeadLetterCallback),
+}
+
+impl InsertErrorPolicy {
+    /// Applies the policy to a bad row, returning an error if the insert should fail.
+    pub(crate) fn handle(
+        &self,
+        error: InsertRowError,
+        skipped: &mut Vec<InsertRowError>,
+    ) -> Result<()> {
+        match self {
+            InsertErrorPolicy::Fail => Err(error
+                .error
+                .context(format!("failed to insert row {}", error.index))),
+            InsertErrorPolicy::Skip => {
+                skipped.push(error);
+                Ok(())
+            }
+            InsertErrorPolicy::DeadLetter(callback) => {
+                callback(error);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// A row rejected during an insert.
+#[derive(Debug)]
+pub struct InsertRowError {
+    /// Index of the row within the insert, counting from 0
+    pub index: u64,
+    /// Serialized row, if it was serialized successfully
+    pub row: Option<Vec<(&'static str, Value)>>,
+    /// Why the row was rejected
+    pub error: Error,
+}
+
+/// Outcome of an insert with an [`InsertErrorPolicy`].
+#[derive(Debug, Default)]
+pub struct InsertReport {
+    /// Rows sent
+    pub rows: u64,
+    /// Rows skipped under [`InsertErrorPolicy::Skip`]
+    pub skipped: Vec<InsertRowError>,
+}
+
+/// Statistics for blocks sent by an [`Inserter`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InsertStats {
+    /// Rows sent
+    pub rows: u64,
+    /// Estimated bytes sent, before compression
+    pub bytes: u64,
+    /// Blocks sent
+    pub blocks: u64,
+    /// Rows skipped under [`InsertErrorPolicy::Skip`] or [`InsertErrorPolicy::DeadLetter`]
+    pub skipped: u64,
+    /// Time spent sending blocks
+    pub elapsed: Duration,
+}
+
+impl InsertStats {
+    fn add(&mut self, other: &InsertStats) {
+        self.rows += other.rows;
+        self.bytes += other.bytes;
+        self.blocks += other.blocks;
+        self.skipped += other.skipped;
+        self.elapsed += other.elapsed;
+    }
+}
+
+/// Callback invoked by an [`Inserter`] after each block is sent.
+pub type FlushCallback = Arc<dyn Fn(&InsertStats) + Send + Sync>;
+
+/// Options for an [`Inserter`].
+#[derive(Clone)]
+pub struct InserterOptions {
+    /// Rows buffered before a block is sent.
+    pub max_rows: usize,
+    /// Estimated bytes buffered before a block is sent.
+    pub max_bytes: usize,
+    /// Longest time a row is buffered before a block is sent.
+    /// An INSERT query is also ended once no rows were sent for this long, and a new one is started for the next block.
+    pub max_latency: Duration,
+    /// If set, the INSERT query is ended once it has been open this long, and a new one is started for the next block.
+    /// Otherwise, an INSERT query is kept open as long as rows keep arriving within [`InserterOptions::max_latency`].
+    /// Other queries sent on the same connection wait while an INSERT is open.
+    pub rotate_period: Option<Duration>,
+    /// Rows queued while a block is being sent before [`Inserter::write`] waits.
+    pub queue_size: usize,
+    /// Called with the statistics of each block sent.
+    pub on_flush: Option<FlushCallback>,
+    /// Handling of rows that fail to serialize or do not match the table.
+    /// Under [`InsertErrorPolicy::Fail`], serialization errors are returned by [`Inserter::write`], and any other bad row fails the inserter.
+    /// Skipped rows are only counted in [`InsertStats::skipped`], use [`InsertErrorPolicy::DeadLetter`] to inspect them.
+    pub error_policy: InsertErrorPolicy,
+}
+
+impl Default for InserterOptions {
+    fn default() -> Self {
+        InserterOptions {
+            max_rows: 100_000,
+            max_bytes: 16 * 1024 * 1024,
+            max_latency: Duration::from_secs(1),
+            rotate_period: None,
+            queue_size: 10_000,
+            on_flush: None,
+            error_policy: InsertErrorPolicy::Fail,
+        }
+    }
+}
+
+enum InserterMessage {
+    Row(Result<SerializedRow>),
+    Flush(oneshot::Sender<()>),
+    Close(oneshot::Sender<Result<InsertStats>>),
+}
+
+/// Buffered inserter, accepting rows one at a time and sending them to Clickhouse in blocks.
+/// Handles can be freely cloned and sent across tasks. Rows are sent by a background task, and writes wait when it falls behind.
+/// Call [`Inserter::close`] to send any buffered rows and end the INSERT query.
+pub struct Inserter<T: Row> {
+    sender: mpsc::Sender<InserterMessage>,
+    fail_fast: bool,
+    error: Arc<Mutex<Option<String>>>,
+    _row: PhantomData<fn(T)>,
+}
+
+impl<T: Row> Clone for Inserter<T> {
+    fn clone(&self) -> Self {
+        Inserter {
+            sender: self.sender.clone(),
+            fail_fast: self.fail_fast,
+            error: self.error.clone(),
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<T: Row> Inserter<T> {
+    pub(crate) fn new(client: Client, query: &str, options: InserterOptions) -> Self {
+        let (sender, receiver) = mpsc::channel(options.queue_size.max(1));
+        let error = Arc::new(Mutex::new(None));
+        let fail_fast = matches!(options.error_policy, InsertErrorPolicy::Fail);
+        let task = InserterTask {
+            client,
+            query: query.to_string(),
+            options,
+            receiver,
+            insert: None,
+            rows: vec![],
+            index: 0,
+            bytes: 0,
+            deadline: None,
+            last_flush: Instant::now(),
+            totals: InsertStats::default(),
+        };
+        tokio::spawn(task.run(error.clone()));
+        Inserter {
+            sender,
+            fail_fast,
+            error,
+            _row: PhantomData,
+        }
+    }
+
+    fn failed(&self) -> Error {
+        match &*self.error.lock().unwrap() {
+            Some(e) => anyhow!("inserter failed: {}", e),
+            None => anyhow!("inserter closed"),
+        }
+    }
+
+    /// Queues a row to be inserted.
+    /// Serialization errors are returned directly under [`InsertErrorPolicy::Fail`], otherwise they are handled by the background task.
+    pub async fn write(&self, row: T) -> Result<()> {
+        let row = match row.serialize_row() {
+            Err(e) if self.fail_fast => return Err(e),
+            row => row,
+        };
+        self.sender
+            .send(InserterMessage::Row(row))
+            .await
+            .map_err(|_| self.failed())
+    }
+
+    /// Sends any buffered rows now, waiting until they are sent.
+    pub async fn flush(&self) -> Result<()> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(InserterMessage::Flush(sender))
+            .await
+            .map_err(|_| self.failed())?;
+        receiver.await.map_err(|_| self.failed())
+    }
+
+    /// Sends any buffered rows, ends the INSERT query and stops the inserter, returning statistics for all blocks sent.
+    /// Any other handles to this inserter will fail on further writes.
+    pub async fn close(self) -> Result<InsertStats> {
+        let (sender, receiver) = oneshot::channel();
+        self.sender
+            .send(InserterMessage::Close(sender))
+            .await
+            .map_err(|_| self.failed())?;
+        receiver.await.map_err(|_| self.failed())?
+    }
+}
+
+struct InserterTask {
+    client: Client,
+    query: String,
+    options: InserterOptions,
+    receiver: mpsc::Receiver<InserterMessage>,
+    insert: Option<(ResponseReceiver, InsertSchema, Instant)>,
+    rows: Vec<(u64, Result<SerializedRow>)>,
+    // index of the next row received
+    index: u64,
+    bytes: usize,
+    // flush deadline for the oldest buffered row
+    deadline: Option<Instant>,
+    // when the last block was sent, to end an idle insert
+    last_flush: Instant,
+    totals: InsertStats,
+}
+
+impl InserterTask {
+    async fn run(mut self, error: Arc<Mutex<Option<String>>>) {
+        let close = match self.run_inner().await {
+            Ok(close) => close,
+            Err(e) => {
+                let e = self.abort(e).await;
+                log::error!("inserter failed: {:?}", e);
+                *error.lock().unwrap() = Some(format!("{:?}", e));
+                return;
+            }
+        };
+        let result = self.finish().await;
+        if let Err(e) = &result {
+            *error.lock().unwrap() = Some(format!("{:?}", e));
+        }
+        match close {
+            Some(close) => {
+                close.send(result).ok();
+            }
+            None => {
+                if let Err(e) = result {
+                    log::error!("inserter failed: {:?}", e);
+                }
+            }
+        }
+    }
+
+    /// Receives rows until closed, returning the close response channel if closed explicitly.
+    async fn run_inner(&mut self) -> Result<Option<oneshot::Sender<Result<InsertStats>>>> {
+        loop {
+            let deadline = self.deadline;
+            let insert_deadline = self.insert_deadline();
+            let message = select! {
+                message = self.receiver.recv() => message,
+                // drain progress of the open insert, so it does not block the connection
+                response = next_response(&mut self.insert) => {
+                    self.handle_response(response)?;
+                    continue;
+                }
+                _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    self.flush().await?;
+                    continue;
+                }
+                // only while no rows are buffered, otherwise the insert is rotated after the next block
+                _ = sleep_until(insert_deadline.unwrap_or_else(Instant::now)), if insert_deadline.is_some() && deadline.is_none() => {
+                    self.end_insert().await?;
+                    continue;
+                }
+            };
+            match message {
+                Some(InserterMessage::Row(row)) => {
+                    self.push_row(row);
+                    if self.deadline.is_none() {
+                        self.deadline = Some(Instant::now() + self.options.max_latency);
+                    }
+                    if self.rows.len() >= self.options.max_rows
+                        || self.bytes >= self.options.max_bytes
+                    {
+                        self.flush().await?;
+                    }
+                }
+                Some(InserterMessage::Flush(response)) => {
+                    self.flush().await?;
+                    response.send(()).ok();
+                }
+                Some(InserterMessage::Close(response)) => return Ok(Some(response)),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// When the open insert is ended if no rows arrive: after [`InserterOptions::max_latency`] without rows,
+    /// or once [`InserterOptions::rotate_period`] elapsed.
+    fn insert_deadline(&self) -> Option<Instant> {
+        let (_, _, opened_at) = self.insert.as_ref()?;
+        let idle = self.last_flush + self.options.max_latency;
+        Some(match self.options.rotate_period {
+            Some(period) => idle.min(*opened_at + period),
+            None => idle,
+        })
+    }
+
+    fn push_row(&mut self, row: Result<SerializedRow>) {
+        if let Ok(row) = &row {
+            self.bytes += row.iter().map(|(_, x)| x.estimated_size()).sum::<usize>();
+        }
+        self.rows.push((self.index, row));
+        self.index += 1;
+    }
+
+    async fn flush(&mut self) -> Result<()> {
+        self.deadline = None;
+        if self.rows.is_empty() {
+            return Ok(());
+        }
+        let start = Instant::now();
+        if let Some((receiver, _, _)) = &mut self.insert {
+            if let Some(e) = take_insert_error(receiver) {
+                self.insert = None;
+                return Err(e);
+            }
+        }
+        if self.insert.is_none() {
+            let (receiver, schema) = self.client.begin_insert(&self.query).await?;
+            self.insert = Some((receiver, schema, Instant::now()));
+        }
+        let (_, schema, opened_at) = self.insert.as_ref().unwrap();
+        let opened_at = *opened_at;
+        let rows = std::mem::take(&mut self.rows);
+        let bytes = std::mem::replace(&mut self.bytes, 0);
+        let mut skipped = vec![];
+        let block = match build_block(schema, rows, &self.options.error_policy, &mut skipped) {
+            Ok(block) => block,
+            Err(e) => return Err(self.abort(e).await),
+        };
+        let stats = InsertStats {
+            rows: block.rows,
+            bytes: bytes as u64,
+            blocks: (block.rows > 0) as u64,
+            skipped: skipped.len() as u64,
+            elapsed: Duration::default(),
+        };
+        if block.rows > 0 {
+            self.send_block(block).await?;
+        }
+        self.last_flush = Instant::now();
+        if matches!(self.options.rotate_period, Some(period) if opened_at.elapsed() >= period) {
+            self.end_insert().await?;
+        }
+        let stats = InsertStats {
+            elapsed: start.elapsed(),
+            ..stats
+        };
+        self.totals.add(&stats);
+        if let Some(on_flush) = &self.options.on_flush {
+            on_flush(&stats);
+        }
+        Ok(())
+    }
+
+    /// Sends a block of the open insert, receiving its responses meanwhile so the connection is not blocked on them.
+    async fn send_block(&mut self, block: Block) -> Result<()> {
+        let client = self.client.clone();
+        let send = client.send_data(block);
+        tokio::pin!(send);
+        loop {
+            let response = select! {
+                result = &mut send => return result,
+                response = next_response(&mut self.insert) => response,
+            };
+            self.handle_response(response)?;
+        }
+    }
+
+    fn handle_response(&mut self, response: Option<QueryResponse>) -> Result<()> {
+        match response {
+            Some(QueryResponse::Error(e)) => {
+                // the server already ended the insert
+                self.insert = None;
+                Err(e)
+            }
+            Some(_) => Ok(()),
+            None => {
+                self.insert = None;
+                Err(anyhow!("insert ended unexpectedly"))
+            }
+        }
+    }
+
+    /// Ends the open insert after `error`, returning the error.
+    async fn abort(&mut self, error: Error) -> Error {
+        match self.insert.take() {
+            Some((receiver, _, _)) => self.client.abort_insert(receiver, error).await,
+            None => error,
+        }
+    }
+
+    async fn end_insert(&mut self) -> Result<()> {
+        if let Some((receiver, _, _)) = self.insert.take() {
+            self.client.end_insert(receiver).await?;
+        }
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<InsertStats> {
+        self.receiver.close();
+        // rows already queued by other handles are still sent
+        while let Some(message) = self.receiver.recv().await {
+            match message {
+                InserterMessage::Row(row) => {
+                    self.push_row(row);
+                }
+                InserterMessage::Flush(response) => {
+                    response.send(()).ok();
+                }
+                InserterMessage::Close(response) => {
+                    response.send(Err(anyhow!("inserter closed"))).ok();
+                }
+            }
+        }
+        self.flush().await?;
+        self.end_insert().await?;
+        Ok(self.totals)
+    }
+}
+
+/// Receives the next response of the open insert, if any.
+async fn next_response(
+    insert: &mut Option<(ResponseReceiver, InsertSchema, Instant)>,
+) -> Option<QueryResponse> {
+    match insert {
+        Some((receiver, _, _)) => receiver.recv().await,
+        None => futures::future::pending().await,
+    }
+}
@@ -11,7 +11,6 @@ use crate::{
     },
 };
 use anyhow::*;
-use cityhash_rs::cityhash_102_128;
 use indexmap::IndexMap;
 use protocol::ServerPacketId;
 use tokio::io::AsyncReadExt;
@@ -20,6 +19,7 @@ use uuid::Uuid;
 pub struct InternalClientIn<R: ClickhouseRead> {
     reader: R,
     pub server_hello: ServerHello,
+    pub compression: CompressionMethod,
 }
 
 impl<R: ClickhouseRead> InternalClientIn<R> {
@@ -27,11 +27,12 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
         InternalClientIn {
             reader,
             server_hello: ServerHello::default(),
+            compression: CompressionMethod::None,
         }
     }
 
     async fn read_exception(&mut self) -> Result<ServerException> {
-        let code = self.reader.read_i32().await?;
+        let code = self.reader.read_i32_le().await?;
         let name = self.reader.read_string().await?;
         let message = self.reader.read_string().await?;
         let stack_trace = self.reader.read_string().await?;
@@ -46,55 +47,6 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
         })
     }
 
-    #[cfg(feature = "compression")]
-    async fn decompress_data(&mut self, compression: CompressionMethod) -> Result<Block> {
-        let checksum = (self.reader.read_u64_le().await? as u128) << 64u128
-            | (self.reader.read_u64_le().await? as u128);
-        let type_byte = self.reader.read_u8().await?;
-        if type_byte != compression.byte() {
-            return Err(anyhow!(
-                "unexpected compression algorithm identifier: '{:02X}', expected {:02X} ({:?})",
-                type_byte,
-                compression.byte(),
-                compression
-            ));
-        }
-        let compressed_size = self.reader.read_u32_le().await?;
-        if compressed_size > 0x40000000 {
-            // 1 GB
-            return Err(anyhow!("compressed payload too large!"));
-        } else if compressed_size < 9 {
-            return Err(anyhow!("compressed payload too small!"));
-        }
-        let decompressed_size = self.reader.read_u32_le().await?;
-        let mut compressed = vec![0u8; compressed_size as usize];
-        self.reader.read_exact(&mut compressed[9..]).await?;
-        compressed[0] = type_byte;
-        (&mut compressed[1..5]).copy_from_slice(&compressed_size.to_le_bytes()[..]);
-        (&mut compressed[5..9]).copy_from_slice(&decompressed_size.to_le_bytes()[..]);
-        let calc_checksum = cityhash_102_128(&compressed[..]);
-        if calc_checksum != checksum {
-            return Err(anyhow!(
-                "corrupt checksum from clickhouse '{:032X}' vs '{:032X}'",
-                calc_checksum,
-                checksum
-            ));
-        }
-        let block = crate::compression::decompress_block(
-            &compressed[9..],
-            decompressed_size,
-            self.server_hello.revision_version,
-        )
-        .await?;
-
-        Ok(block)
-    }
-
-    #[cfg(not(feature = "compression"))]
-    async fn decompress_data(&mut self, compression: CompressionMethod) -> Result<Block> {
-        panic!("attempted to use compression when not compiled with `compression` feature in klickhouse");
-    }
-
     async fn receive_data(&mut self, compression: CompressionMethod) -> Result<ServerData> {
         let table_name = self.reader.read_string().await?;
 
@@ -102,14 +54,21 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
             CompressionMethod::None => {
                 Block::read(&mut self.reader, self.server_hello.revision_version).await?
             }
-            _ => self.decompress_data(compression).await?,
+            _ => {
+                crate::compression::read_compressed_data(
+                    &mut self.reader,
+                    self.server_hello.revision_version,
+                )
+                .await?
+            }
         };
 
         Ok(ServerData { table_name, block })
     }
 
     async fn receive_log_data(&mut self) -> Result<ServerData> {
-        unimplemented!()
+        // logs and profile events are never compressed
+        self.receive_data(CompressionMethod::None).await
     }
 
     pub async fn receive_packet(&mut self) -> Result<ServerPacket> {
@@ -120,18 +79,18 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
                 let major_version = self.reader.read_var_uint().await?;
                 let minor_version = self.reader.read_var_uint().await?;
                 let revision_version = self.reader.read_var_uint().await?;
-                let timezone = if revision_version > DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE {
+                let timezone = if revision_version >= DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE {
                     Some(self.reader.read_string().await?)
                 } else {
                     None
                 };
-                let display_name = if revision_version > DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME
+                let display_name = if revision_version >= DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME
                 {
                     Some(self.reader.read_string().await?)
                 } else {
                     None
                 };
-                let patch_version = if revision_version > DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+                let patch_version = if revision_version >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
                     self.reader.read_var_uint().await?
                 } else {
                     revision_version
@@ -147,7 +106,7 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
                 }))
             }
             ServerPacketId::Data => Ok(ServerPacket::Data(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::Exception => Ok(ServerPacket::Exception(self.read_exception().await?)),
             ServerPacketId::Progress => {
@@ -195,10 +154,10 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
                 }))
             }
             ServerPacketId::Totals => Ok(ServerPacket::Totals(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::Extremes => Ok(ServerPacket::Extremes(
-                self.receive_data(CompressionMethod::default()).await?,
+                self.receive_data(self.compression).await?,
             )),
             ServerPacketId::TablesStatusResponse => {
                 let mut response = TablesStatusResponse {
@@ -220,7 +179,7 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
                     response
                         .database_tables
                         .entry(database_name)
-                        .or_insert_with(IndexMap::new)
+                        .or_default()
                         .insert(
                             table_name,
                             TableStatus {
@@ -252,6 +211,9 @@ impl<R: ClickhouseRead> InternalClientIn<R> {
                 Ok(ServerPacket::PartUUIDs(out))
             }
             ServerPacketId::ReadTaskRequest => Ok(ServerPacket::ReadTaskRequest),
+            ServerPacketId::ProfileEvents => {
+                Ok(ServerPacket::ProfileEvents(self.receive_log_data().await?))
+            }
         }
     }
 
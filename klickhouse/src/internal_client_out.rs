@@ -2,14 +2,17 @@ use crate::{
     block::Block,
     io::ClickhouseWrite,
     protocol::{
-        self, CompressionMethod, ServerHello, DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
-        DBMS_MIN_REVISION_WITH_CLIENT_INFO, DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET,
-        DBMS_MIN_REVISION_WITH_OPENTELEMETRY, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+        self, CompressionMethod, QueryKind, ServerHello, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS, DBMS_MIN_REVISION_WITH_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET, DBMS_MIN_REVISION_WITH_OPENTELEMETRY,
+        DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS,
         DBMS_MIN_REVISION_WITH_VERSION_PATCH,
     },
 };
 use anyhow::*;
-use cityhash_rs::cityhash_102_128;
 use tokio::io::AsyncWriteExt;
 use uuid::Uuid;
 
@@ -24,20 +27,13 @@ pub struct ClientHello<'a> {
     pub password: &'a str,
 }
 
-#[repr(u8)]
-#[derive(PartialEq, Clone, Copy)]
-#[allow(unused, clippy::enum_variant_names)]
-pub enum QueryKind {
-    NoQuery,
-    InitialQuery,
-    SecondaryQuery,
-}
-
 pub struct ClientInfo<'a> {
     pub kind: QueryKind,
     pub initial_user: &'a str,
     pub initial_query_id: &'a str,
     pub initial_address: &'a str,
+    // if DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME
+    pub initial_query_start_time_microseconds: u64,
     // interface = TCP = 1
     pub os_user: &'a str,
     pub client_hostname: &'a str,
@@ -54,6 +50,10 @@ pub struct ClientInfo<'a> {
     pub client_version_patch: u64,
     // if DBMS_MIN_REVISION_WITH_OPENTELEMETRY
     pub open_telemetry: Option<OpenTelemetry<'a>>,
+    // if DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS
+    pub collaborate_with_initiator: u64,
+    pub count_participating_replicas: u64,
+    pub number_of_current_replica: u64,
 }
 
 impl<'a> ClientInfo<'a> {
@@ -65,6 +65,10 @@ impl<'a> ClientInfo<'a> {
         to.write_string(self.initial_user).await?;
         to.write_string(self.initial_query_id).await?;
         to.write_string(self.initial_address).await?;
+        if revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME {
+            to.write_u64_le(self.initial_query_start_time_microseconds)
+                .await?;
+        }
         to.write_u8(1).await?;
         to.write_string(self.os_user).await?;
         to.write_string(self.client_hostname).await?;
@@ -92,6 +96,11 @@ impl<'a> ClientInfo<'a> {
                 to.write_u8(0u8).await?;
             }
         }
+        if revision >= DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS {
+            to.write_var_uint(self.collaborate_with_initiator).await?;
+            to.write_var_uint(self.count_participating_replicas).await?;
+            to.write_var_uint(self.number_of_current_replica).await?;
+        }
 
         Ok(())
     }
@@ -117,11 +126,13 @@ pub enum QueryProcessingStage {
 pub struct Query<'a> {
     pub id: &'a str,
     pub info: ClientInfo<'a>,
-    // pub settings: (), //TODO
+    pub settings: &'a [(&'a str, &'a str)],
     //todo: interserver secret
     pub stage: QueryProcessingStage,
     pub compression: CompressionMethod,
     pub query: &'a str,
+    /// Query parameters, with values already quoted
+    pub parameters: &'a [(String, String)],
     //todo: data
 }
 
@@ -145,7 +156,16 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
                 .write(&mut self.writer, self.server_hello.revision_version)
                 .await?;
         }
-        //todo: settings
+        for (name, value) in params.settings {
+            self.writer.write_string(name).await?;
+            if self.server_hello.revision_version
+                >= DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS
+            {
+                // flags, not important
+                self.writer.write_var_uint(0).await?;
+            }
+            self.writer.write_string(value).await?;
+        }
         self.writer.write_string("").await?;
         if self.server_hello.revision_version >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET {
             //todo interserver secret
@@ -160,35 +180,22 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
             })
             .await?;
         self.writer.write_string(params.query).await?;
+        if self.server_hello.revision_version >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS {
+            for (name, value) in params.parameters {
+                self.writer.write_string(name).await?;
+                // flags, custom
+                self.writer.write_var_uint(2).await?;
+                self.writer.write_string(value).await?;
+            }
+            self.writer.write_string("").await?;
+        } else if !params.parameters.is_empty() {
+            return Err(anyhow!("server does not support query parameters"));
+        }
 
         self.writer.flush().await?;
         Ok(())
     }
 
-    #[cfg(feature = "compression")]
-    async fn compress_data(&mut self, byte: u8, block: &Block) -> Result<()> {
-        let (out, decompressed_size) =
-            crate::compression::compress_block(block, self.server_hello.revision_version).await?;
-        let mut new_out = Vec::with_capacity(out.len() + 5);
-        new_out.push(byte);
-        new_out.extend_from_slice(&(out.len() as u32 + 9).to_le_bytes()[..]);
-        new_out.extend_from_slice(&(decompressed_size as u32).to_le_bytes()[..]);
-        new_out.extend(out);
-
-        let hash = cityhash_102_128(&new_out[..]);
-        self.writer.write_u64_le((hash >> 64) as u64).await?;
-        self.writer.write_u64_le(hash as u64).await?;
-        // self.writer.write_u8(byte).await?;
-        // self.writer.write_u32_le(out.len() as u32).await?;
-        self.writer.write_all(&new_out[..]).await?;
-        Ok(())
-    }
-
-    #[cfg(not(feature = "compression"))]
-    async fn compress_data(&mut self, byte: u8, block: &Block) -> Result<()> {
-        panic!("attempted to use compression when not compiled with `compression` feature in klickhouse");
-    }
-
     pub async fn send_data(
         &mut self,
         block: &Block,
@@ -212,9 +219,14 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
                     .write(&mut self.writer, self.server_hello.revision_version)
                     .await?;
             }
-            CompressionMethod::LZ4 => {
-                self.compress_data(CompressionMethod::LZ4.byte(), block)
-                    .await?;
+            CompressionMethod::LZ4 | CompressionMethod::ZSTD => {
+                crate::compression::write_compressed_data(
+                    &mut self.writer,
+                    compression,
+                    block,
+                    self.server_hello.revision_version,
+                )
+                .await?;
             }
         }
 
@@ -229,7 +241,7 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
             .write_var_uint(protocol::ClientPacketId::Hello as u64)
             .await?;
         self.writer
-            .write_string(&*format!(
+            .write_string(&format!(
                 "ClickHouse Rust-Klickhouse {}",
                 env!("CARGO_PKG_VERSION")
             ))
@@ -246,6 +258,15 @@ impl<W: ClickhouseWrite> InternalClientOut<W> {
         Ok(())
     }
 
+    /// Sends the addendum following the handshake, once the server hello is received.
+    pub async fn send_addendum(&mut self, quota_key: &str) -> Result<()> {
+        if self.server_hello.revision_version >= DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
+            self.writer.write_string(quota_key).await?;
+            self.writer.flush().await?;
+        }
+        Ok(())
+    }
+
     // pub async fn send_ping(&mut self) -> Result<()> {
     //     self.writer.write_var_uint(protocol::ClientPacketId::Ping as u64).await?;
     //     Ok(())
@@ -0,0 +1,244 @@
+use crate::{
+    block::Block,
+    io::ClickhouseRead,
+    protocol::{
+        ClientData, ClientHelloPacket, ClientInfo, ClientPacket, ClientPacketId, ClientQuery,
+        CompressionMethod, OpenTelemetry, QueryKind, DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME,
+        DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS, DBMS_MIN_REVISION_WITH_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET, DBMS_MIN_REVISION_WITH_OPENTELEMETRY,
+        DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS, DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO,
+        DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS,
+        DBMS_MIN_REVISION_WITH_VERSION_PATCH,
+    },
+};
+use anyhow::*;
+use tokio::io::AsyncReadExt;
+use uuid::Uuid;
+
+pub struct InternalServerIn<R: ClickhouseRead> {
+    reader: R,
+    pub revision: u64,
+    pub compression: CompressionMethod,
+}
+
+impl<R: ClickhouseRead> InternalServerIn<R> {
+    pub fn new(reader: R) -> Self {
+        InternalServerIn {
+            reader,
+            revision: 0,
+            compression: CompressionMethod::None,
+        }
+    }
+
+    async fn read_client_info(&mut self) -> Result<ClientInfo> {
+        let kind = QueryKind::from_u8(self.reader.read_u8().await?)?;
+        if kind == QueryKind::NoQuery {
+            return Ok(ClientInfo::default());
+        }
+        let initial_user = self.reader.read_string().await?;
+        let initial_query_id = self.reader.read_string().await?;
+        let initial_address = self.reader.read_string().await?;
+        let initial_query_start_time_microseconds =
+            if self.revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME {
+                self.reader.read_u64_le().await?
+            } else {
+                0
+            };
+        let interface = self.reader.read_u8().await?;
+        if interface != 1 {
+            return Err(anyhow!("unsupported client interface: {}", interface));
+        }
+        let os_user = self.reader.read_string().await?;
+        let client_hostname = self.reader.read_string().await?;
+        let client_name = self.reader.read_string().await?;
+        let client_version_major = self.reader.read_var_uint().await?;
+        let client_version_minor = self.reader.read_var_uint().await?;
+        let client_tcp_protocol_version = self.reader.read_var_uint().await?;
+        let quota_key = if self.revision >= DBMS_MIN_REVISION_WITH_QUOTA_KEY_IN_CLIENT_INFO {
+            self.reader.read_string().await?
+        } else {
+            String::new()
+        };
+        let distributed_depth = if self.revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH
+        {
+            self.reader.read_var_uint().await?
+        } else {
+            0
+        };
+        let client_version_patch = if self.revision >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+            self.reader.read_var_uint().await?
+        } else {
+            client_tcp_protocol_version
+        };
+        let open_telemetry = if self.revision >= DBMS_MIN_REVISION_WITH_OPENTELEMETRY
+            && self.reader.read_u8().await? != 0
+        {
+            let mut trace_id = [0u8; 16];
+            self.reader.read_exact(&mut trace_id[..]).await?;
+            Some(OpenTelemetry {
+                trace_id: Uuid::from_bytes(trace_id),
+                span_id: self.reader.read_u64().await?,
+                tracestate: self.reader.read_string().await?,
+                trace_flags: self.reader.read_u8().await?,
+            })
+        } else {
+            None
+        };
+        let (collaborate_with_initiator, count_participating_replicas, number_of_current_replica) =
+            if self.revision >= DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS {
+                (
+                    self.reader.read_var_uint().await?,
+                    self.reader.read_var_uint().await?,
+                    self.reader.read_var_uint().await?,
+                )
+            } else {
+                (0, 0, 0)
+            };
+        Ok(ClientInfo {
+            kind,
+            initial_user,
+            initial_query_id,
+            initial_address,
+            os_user,
+            client_hostname,
+            client_name,
+            client_version_major,
+            client_version_minor,
+            client_tcp_protocol_version,
+            quota_key,
+            distributed_depth,
+            client_version_patch,
+            open_telemetry,
+            initial_query_start_time_microseconds,
+            collaborate_with_initiator,
+            count_participating_replicas,
+            number_of_current_replica,
+        })
+    }
+
+    async fn read_settings(&mut self) -> Result<Vec<(String, String)>> {
+        let mut settings = vec![];
+        loop {
+            let name = self.reader.read_string().await?;
+            if name.is_empty() {
+                break;
+            }
+            if self.revision < DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS {
+                return Err(anyhow!("binary serialized settings are not supported"));
+            }
+            let _flags = self.reader.read_var_uint().await?;
+            let value = self.reader.read_string().await?;
+            settings.push((name, value));
+        }
+        Ok(settings)
+    }
+
+    async fn receive_query(&mut self) -> Result<ClientQuery> {
+        let id = self.reader.read_string().await?;
+        let info = if self.revision >= DBMS_MIN_REVISION_WITH_CLIENT_INFO {
+            self.read_client_info().await?
+        } else {
+            ClientInfo::default()
+        };
+        let settings = self.read_settings().await?;
+        if self.revision >= DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET {
+            let _interserver_secret = self.reader.read_string().await?;
+        }
+        let stage = self.reader.read_var_uint().await?;
+        let compression = match self.reader.read_var_uint().await? {
+            0 => CompressionMethod::None,
+            // the client picks the method for server responses through settings
+            _ => match settings
+                .iter()
+                .find(|(name, _)| name == "network_compression_method")
+                .map(|(_, value)| value.to_ascii_uppercase())
+                .as_deref()
+            {
+                Some("ZSTD") => CompressionMethod::ZSTD,
+                _ => CompressionMethod::LZ4,
+            },
+        };
+        let query = self.reader.read_string().await?;
+        let parameters = if self.revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS {
+            self.read_settings().await?
+        } else {
+            vec![]
+        };
+        Ok(ClientQuery {
+            id,
+            info,
+            settings,
+            stage,
+            compression,
+            query,
+            parameters,
+        })
+    }
+
+    async fn receive_data(&mut self) -> Result<ClientData> {
+        let table_name = self.reader.read_string().await?;
+
+        let block = match self.compression {
+            CompressionMethod::None => Block::read(&mut self.reader, self.revision).await?,
+            _ => crate::compression::read_compressed_data(&mut self.reader, self.revision).await?,
+        };
+
+        Ok(ClientData { table_name, block })
+    }
+
+    pub async fn receive_packet(&mut self) -> Result<ClientPacket> {
+        let packet_id = ClientPacketId::from_u64(self.reader.read_var_uint().await?)?;
+        match packet_id {
+            ClientPacketId::Hello => {
+                let client_name = self.reader.read_string().await?;
+                let major_version = self.reader.read_var_uint().await?;
+                let minor_version = self.reader.read_var_uint().await?;
+                let protocol_version = self.reader.read_var_uint().await?;
+                let default_database = self.reader.read_string().await?;
+                let username = self.reader.read_string().await?;
+                let password = self.reader.read_string().await?;
+                Ok(ClientPacket::Hello(ClientHelloPacket {
+                    client_name,
+                    major_version,
+                    minor_version,
+                    protocol_version,
+                    default_database,
+                    username,
+                    password,
+                    quota_key: String::new(),
+                }))
+            }
+            ClientPacketId::Query => {
+                let query = self.receive_query().await?;
+                self.compression = query.compression;
+                Ok(ClientPacket::Query(query))
+            }
+            ClientPacketId::Data => Ok(ClientPacket::Data(self.receive_data().await?)),
+            ClientPacketId::Scalar => Ok(ClientPacket::Scalar(self.receive_data().await?)),
+            ClientPacketId::Cancel => Ok(ClientPacket::Cancel),
+            ClientPacketId::Ping => Ok(ClientPacket::Ping),
+            packet_id => Err(anyhow!("unsupported packet from client: {:?}", packet_id)),
+        }
+    }
+
+    /// Receives the addendum following the handshake, once the negotiated revision is set.
+    pub async fn receive_addendum(&mut self) -> Result<String> {
+        if self.revision >= DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM {
+            Ok(self.reader.read_string().await?)
+        } else {
+            Ok(String::new())
+        }
+    }
+
+    pub async fn receive_hello(&mut self) -> Result<ClientHelloPacket> {
+        match self.receive_packet().await? {
+            ClientPacket::Hello(hello) => Ok(hello),
+            packet => Err(anyhow!(
+                "unexpected packet {:?}, expected client hello",
+                packet
+            )),
+        }
+    }
+}
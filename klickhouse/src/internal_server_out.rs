@@ -0,0 +1,195 @@
+use crate::{
+    io::ClickhouseWrite,
+    progress::Progress,
+    protocol::{
+        BlockStreamProfileInfo, CompressionMethod, ServerData, ServerException, ServerHello,
+        ServerPacket, ServerPacketId, TableColumns, TablesStatusResponse,
+        DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO, DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME,
+        DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE, DBMS_MIN_REVISION_WITH_VERSION_PATCH,
+    },
+};
+use anyhow::*;
+use tokio::io::AsyncWriteExt;
+
+pub struct InternalServerOut<W: ClickhouseWrite> {
+    writer: W,
+    pub revision: u64,
+    pub compression: CompressionMethod,
+}
+
+impl<W: ClickhouseWrite> InternalServerOut<W> {
+    pub fn new(writer: W) -> Self {
+        InternalServerOut {
+            writer,
+            revision: 0,
+            compression: CompressionMethod::None,
+        }
+    }
+
+    async fn write_hello(&mut self, hello: &ServerHello) -> Result<()> {
+        self.writer.write_string(&hello.server_name).await?;
+        self.writer.write_var_uint(hello.major_version).await?;
+        self.writer.write_var_uint(hello.minor_version).await?;
+        self.writer.write_var_uint(hello.revision_version).await?;
+        if hello.revision_version >= DBMS_MIN_REVISION_WITH_SERVER_TIMEZONE {
+            self.writer
+                .write_string(hello.timezone.as_deref().unwrap_or("UTC"))
+                .await?;
+        }
+        if hello.revision_version >= DBMS_MIN_REVISION_WITH_SERVER_DISPLAY_NAME {
+            self.writer
+                .write_string(hello.display_name.as_deref().unwrap_or(""))
+                .await?;
+        }
+        if hello.revision_version >= DBMS_MIN_REVISION_WITH_VERSION_PATCH {
+            self.writer.write_var_uint(hello.patch_version).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_data(
+        &mut self,
+        data: &ServerData,
+        compression: CompressionMethod,
+    ) -> Result<()> {
+        self.writer.write_string(&data.table_name).await?;
+        match compression {
+            CompressionMethod::None => {
+                data.block.write(&mut self.writer, self.revision).await?;
+            }
+            compression => {
+                crate::compression::write_compressed_data(
+                    &mut self.writer,
+                    compression,
+                    &data.block,
+                    self.revision,
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_exception(&mut self, exception: &ServerException) -> Result<()> {
+        self.writer.write_i32_le(exception.code).await?;
+        self.writer.write_string(&exception.name).await?;
+        self.writer.write_string(&exception.message).await?;
+        self.writer.write_string(&exception.stack_trace).await?;
+        self.writer
+            .write_u8(if exception.has_nested { 1 } else { 0 })
+            .await?;
+        Ok(())
+    }
+
+    async fn write_progress(&mut self, progress: &Progress) -> Result<()> {
+        self.writer.write_var_uint(progress.read_rows).await?;
+        self.writer.write_var_uint(progress.read_bytes).await?;
+        self.writer
+            .write_var_uint(progress.new_total_rows_to_read)
+            .await?;
+        if self.revision >= DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO {
+            self.writer
+                .write_var_uint(progress.new_written_rows.unwrap_or(0))
+                .await?;
+            self.writer
+                .write_var_uint(progress.new_written_bytes.unwrap_or(0))
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn write_profile_info(&mut self, info: &BlockStreamProfileInfo) -> Result<()> {
+        self.writer.write_var_uint(info.rows).await?;
+        self.writer.write_var_uint(info.blocks).await?;
+        self.writer.write_var_uint(info.bytes).await?;
+        self.writer
+            .write_u8(if info.applied_limit { 1 } else { 0 })
+            .await?;
+        self.writer.write_var_uint(info.rows_before_limit).await?;
+        self.writer
+            .write_u8(if info.calculated_rows_before_limit {
+                1
+            } else {
+                0
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn write_tables_status(&mut self, response: &TablesStatusResponse) -> Result<()> {
+        let size = response
+            .database_tables
+            .values()
+            .map(|tables| tables.len())
+            .sum::<usize>();
+        self.writer.write_var_uint(size as u64).await?;
+        for (database_name, tables) in &response.database_tables {
+            for (table_name, status) in tables {
+                self.writer.write_string(database_name).await?;
+                self.writer.write_string(table_name).await?;
+                self.writer
+                    .write_u8(if status.is_replicated { 1 } else { 0 })
+                    .await?;
+                if status.is_replicated {
+                    self.writer
+                        .write_var_uint(status.absolute_delay as u64)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_table_columns(&mut self, columns: &TableColumns) -> Result<()> {
+        self.writer.write_string(&columns.name).await?;
+        self.writer.write_string(&columns.description).await?;
+        Ok(())
+    }
+
+    pub async fn send_packet(&mut self, packet: &ServerPacket) -> Result<()> {
+        let packet_id = match packet {
+            ServerPacket::Hello(_) => ServerPacketId::Hello,
+            ServerPacket::Data(_) => ServerPacketId::Data,
+            ServerPacket::Exception(_) => ServerPacketId::Exception,
+            ServerPacket::Progress(_) => ServerPacketId::Progress,
+            ServerPacket::Pong => ServerPacketId::Pong,
+            ServerPacket::EndOfStream => ServerPacketId::EndOfStream,
+            ServerPacket::ProfileInfo(_) => ServerPacketId::ProfileInfo,
+            ServerPacket::Totals(_) => ServerPacketId::Totals,
+            ServerPacket::Extremes(_) => ServerPacketId::Extremes,
+            ServerPacket::TablesStatusResponse(_) => ServerPacketId::TablesStatusResponse,
+            ServerPacket::Log(_) => ServerPacketId::Log,
+            ServerPacket::TableColumns(_) => ServerPacketId::TableColumns,
+            ServerPacket::PartUUIDs(_) => ServerPacketId::PartUUIDs,
+            ServerPacket::ReadTaskRequest => ServerPacketId::ReadTaskRequest,
+            ServerPacket::ProfileEvents(_) => ServerPacketId::ProfileEvents,
+        };
+        self.writer.write_var_uint(packet_id as u64).await?;
+        match packet {
+            ServerPacket::Hello(hello) => self.write_hello(hello).await?,
+            ServerPacket::Data(data)
+            | ServerPacket::Totals(data)
+            | ServerPacket::Extremes(data) => self.write_data(data, self.compression).await?,
+            // logs and profile events are never compressed
+            ServerPacket::Log(data) | ServerPacket::ProfileEvents(data) => {
+                self.write_data(data, CompressionMethod::None).await?
+            }
+            ServerPacket::Exception(exception) => self.write_exception(exception).await?,
+            ServerPacket::Progress(progress) => self.write_progress(progress).await?,
+            ServerPacket::ProfileInfo(info) => self.write_profile_info(info).await?,
+            ServerPacket::TablesStatusResponse(response) => {
+                self.write_tables_status(response).await?
+            }
+            ServerPacket::TableColumns(columns) => self.write_table_columns(columns).await?,
+            ServerPacket::PartUUIDs(uuids) => {
+                self.writer.write_var_uint(uuids.len() as u64).await?;
+                for uuid in uuids {
+                    self.writer.write_all(&uuid.as_bytes()[..]).await?;
+                }
+            }
+            ServerPacket::Pong | ServerPacket::EndOfStream | ServerPacket::ReadTaskRequest => (),
+        }
+        self.writer.flush().await?;
+        Ok(())
+    }
+}
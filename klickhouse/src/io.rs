@@ -59,14 +59,14 @@ impl<T: AsyncRead + Unpin + Send + Sync> ClickhouseRead for T {
 }
 
 #[async_trait::async_trait]
-pub trait ClickhouseWrite: AsyncWrite + Unpin + Send + Sync + 'static {
+pub trait ClickhouseWrite: AsyncWrite + Unpin + Send + Sync {
     async fn write_var_uint(&mut self, value: u64) -> Result<()>;
 
-    async fn write_string(&mut self, mut value: &str) -> Result<()>;
+    async fn write_string(&mut self, value: &str) -> Result<()>;
 }
 
 #[async_trait::async_trait]
-impl<T: AsyncWrite + Unpin + Send + Sync + 'static> ClickhouseWrite for T {
+impl<T: AsyncWrite + Unpin + Send + Sync> ClickhouseWrite for T {
     async fn write_var_uint(&mut self, mut value: u64) -> Result<()> {
         for _ in 0..9u64 {
             let mut byte = value & 0x7F;
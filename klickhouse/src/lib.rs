@@ -5,16 +5,27 @@ pub const VERSION_MINOR: u64 = 6;
 
 mod block;
 mod client;
-#[cfg(feature = "compression")]
 mod compression;
 mod convert;
 /// Error generator functions used by `klickhouse_derive`
 pub mod errors;
+mod inserter;
 mod internal_client_in;
 mod internal_client_out;
+mod internal_server_in;
+mod internal_server_out;
 mod io;
+mod params;
 mod progress;
-mod protocol;
+/// Native protocol packet definitions, shared by [`Client`] and [`ServerConnection`]
+pub mod protocol;
+mod schema;
+mod select;
+#[cfg(feature = "serde")]
+pub mod serde;
+mod server;
+/// Mock Clickhouse server for testing
+pub mod testing;
 mod types;
 mod values;
 
@@ -26,10 +37,24 @@ pub mod uuid;
 pub use uuid::Uuid;
 
 #[cfg(feature = "derive")]
-pub use klickhouse_derive::Row;
+pub use klickhouse_derive::{FromSql, Row, RowRef, ToSql};
 
+#[cfg(feature = "serde")]
+pub use crate::serde::SerdeRow;
+pub use block::{Block, BlockInfo};
 pub use client::*;
-pub use convert::{FromSql, Row, ToSql};
+pub use convert::{
+    is_lenient_coercion, with_lenient_coercion, ExpectedColumn, ExpectedColumns, ExpectedSqlType,
+    ExpectedType, ExpectedTypeFallback, FromSql, FromSqlRef, PrefixedColumnNames, Row, RowRef,
+    SqlType, ToSql,
+};
+pub use inserter::*;
+pub use params::QueryParams;
+pub use progress::Progress;
+pub use protocol::CompressionMethod;
+pub use schema::*;
+pub use select::Select;
+pub use server::*;
 pub use types::Type;
 pub use values::*;
 
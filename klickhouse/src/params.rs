@@ -0,0 +1,248 @@
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+
+use crate::{convert::ToSql, types::Type, values::write_quoted_str, Value};
+use anyhow::*;
+
+/// Typed values bound to the `{name:Type}` placeholders of a query, sent to Clickhouse separately from the query text.
+/// Usually built with [`crate::params!`].
+#[derive(Debug, Default)]
+pub struct QueryParams {
+    values: IndexMap<String, Value>,
+    error: Option<Error>,
+}
+
+/// Builds [`QueryParams`] from `name => value` pairs, where each value implements [`crate::ToSql`].
+/// ```ignore
+/// client.query_with::<Row>("select * from t where id = {id:UInt64}", params! { "id" => 5u64 })
+/// ```
+#[macro_export]
+macro_rules! params {
+    () => {
+        $crate::QueryParams::new()
+    };
+    ($($name:expr => $value:expr),+ $(,)?) => {{
+        let mut params = $crate::QueryParams::new();
+        $(params.add($name, $value);)+
+        params
+    }};
+}
+
+impl QueryParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `value` to the placeholder `name`, replacing any previous value.
+    /// Conversion errors are returned when the query is sent.
+    pub fn add(&mut self, name: impl Into<String>, value: impl ToSql) -> &mut Self {
+        let name = name.into();
+        match value.to_sql() {
+            Ok(value) => {
+                self.values.insert(name, value);
+            }
+            Err(e) => {
+                if self.error.is_none() {
+                    self.error = Some(e.context(format!("invalid value for parameter `{}`", name)));
+                }
+            }
+        }
+        self
+    }
+
+    /// Checks the parameters against the placeholders of `query`, and encodes them as sent over the native protocol.
+    pub(crate) fn encode(self, query: &str) -> Result<Vec<(String, String)>> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let placeholders = placeholders(query);
+        if let Some(name) = self
+            .values
+            .keys()
+            .find(|name| !placeholders.contains_key(&name[..]))
+        {
+            return Err(anyhow!("parameter `{}` is not used in query", name));
+        }
+        let mut values = self.values;
+        let mut out = Vec::with_capacity(placeholders.len());
+        for (name, type_name) in placeholders {
+            let value = values
+                .shift_remove(name)
+                .ok_or_else(|| anyhow!("missing parameter `{}`", name))?;
+            let text = encode_value(type_name, value)
+                .with_context(|| format!("invalid value for parameter `{}`", name))?;
+            let mut quoted = String::new();
+            write_quoted_str(&text, &mut quoted);
+            out.push((name.to_string(), quoted));
+        }
+        Ok(out)
+    }
+}
+
+/// Encodes `value` in escaped text format, as parsed by Clickhouse for a placeholder of type `type_name`.
+fn encode_value(type_name: &str, value: Value) -> Result<String> {
+    let mut out = String::new();
+    if type_name == "Identifier" {
+        return match value {
+            Value::String(identifier) => Ok(identifier),
+            value => Err(anyhow!("expected string identifier, got {:?}", value)),
+        };
+    }
+    let type_ = Type::from_str(type_name)?;
+    let value = type_.coerce_value(value);
+    type_.validate_value(&value)?;
+    value.write_escaped(&type_, &mut out)?;
+    Ok(out)
+}
+
+/// Finds `{name:Type}` placeholders in `query`, skipping over quoted strings, identifiers and comments.
+/// The first type given for a name is used.
+fn placeholders(query: &str) -> IndexMap<&str, &str> {
+    let bytes = query.as_bytes();
+    let mut out = IndexMap::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !bytes[i..].starts_with(b"*/") {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'{' => {
+                let placeholder = query[i + 1..]
+                    .find('}')
+                    .and_then(|end| query[i + 1..i + 1 + end].split_once(':'))
+                    .map(|(name, type_)| (name.trim(), type_.trim()))
+                    .filter(|(name, type_)| {
+                        !name.is_empty()
+                            && !type_.is_empty()
+                            && name.bytes().all(|x| x.is_ascii_alphanumeric() || x == b'_')
+                    });
+                if let Some((name, type_)) = placeholder {
+                    out.entry(name).or_insert(type_);
+                    i += query[i..].find('}').unwrap();
+                }
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        let found = placeholders(
+            "select {a:UInt64}, '{b:String}', `{c:String}` -- {d:String}\n, {e: Array(String) }, {'f':1}, /* {g:String} */ {a:String}",
+        );
+        assert_eq!(
+            found.into_iter().collect::<Vec<_>>(),
+            vec![("a", "UInt64"), ("e", "Array(String)")]
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let query =
+            "select {id:UInt64}, {name:String}, {tags:Array(Nullable(String))}, {col:Identifier}";
+        let mut params = QueryParams::new();
+        params
+            .add("id", 5u32)
+            .add("name", "it's\na \\ test")
+            .add("tags", vec![Some("a'b".to_string()), None])
+            .add("col", "name");
+        assert_eq!(
+            params.encode(query).unwrap(),
+            vec![
+                ("id".to_string(), "'5'".to_string()),
+                ("name".to_string(), r"'it\\\'s\\na \\\\ test'".to_string()),
+                ("tags".to_string(), r"'[\'a\\\'b\',NULL]'".to_string()),
+                ("col".to_string(), "'name'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_encode_text() {
+        let encode = |type_name, value: Value| encode_value(type_name, value).unwrap();
+        assert_eq!(encode("Decimal64(3)", Value::Decimal64(3, -1234)), "-1.234");
+        assert_eq!(encode("Decimal32(4)", Value::Decimal32(4, 5)), "0.0005");
+        assert_eq!(encode("Float64", Value::Float64(1.5f64.to_bits())), "1.5");
+        assert_eq!(
+            encode("Date", Value::Date(crate::Date(19000))),
+            "2022-01-08"
+        );
+        assert_eq!(
+            encode(
+                "DateTime64(3, 'UTC')",
+                Value::DateTime64(chrono_tz::UTC, 3, 1_600_000_000_123)
+            ),
+            "2020-09-13 12:26:40.123"
+        );
+        assert_eq!(
+            encode(
+                "Tuple(String, Map(String, Array(UInt8)))",
+                Value::Tuple(vec![
+                    Value::String("t".to_string()),
+                    Value::Map(
+                        vec![Value::String("k".to_string())],
+                        vec![Value::Array(vec![Value::UInt8(1), Value::UInt8(2)])]
+                    )
+                ])
+            ),
+            "('t',{'k':[1,2]})"
+        );
+        assert_eq!(
+            encode("Int256", Value::Int256((u128::MAX, u128::MAX - 41).into())),
+            "-42"
+        );
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let mut params = QueryParams::new();
+        params.add("id", 5u64);
+        assert_eq!(
+            params
+                .encode("select {id:UInt64}, {name:String}")
+                .unwrap_err()
+                .to_string(),
+            "missing parameter `name`"
+        );
+
+        let mut params = QueryParams::new();
+        params.add("id", 5u64).add("name", "x");
+        assert_eq!(
+            params.encode("select {id:UInt64}").unwrap_err().to_string(),
+            "parameter `name` is not used in query"
+        );
+
+        let mut params = QueryParams::new();
+        params.add("id", "x");
+        assert_eq!(
+            params.encode("select {id:UInt64}").unwrap_err().to_string(),
+            "invalid value for parameter `id`"
+        );
+    }
+}
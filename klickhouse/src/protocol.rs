@@ -17,20 +17,27 @@ pub const DBMS_MIN_REVISION_WITH_VERSION_PATCH: u64 = 54401;
 // pub const DBMS_MIN_REVISION_WITH_COLUMN_DEFAULTS_METADATA: u64 = 54410;
 // pub const DBMS_MIN_REVISION_WITH_LOW_CARDINALITY_TYPE: u64 = 54405;
 pub const DBMS_MIN_REVISION_WITH_CLIENT_WRITE_INFO: u64 = 54420;
-// pub const DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS: u64 = 54429;
+pub const DBMS_MIN_REVISION_WITH_SETTINGS_SERIALIZED_AS_STRINGS: u64 = 54429;
 pub const DBMS_MIN_REVISION_WITH_OPENTELEMETRY: u64 = 54442;
 pub const DBMS_MIN_REVISION_WITH_INTERSERVER_SECRET: u64 = 54441;
 // pub const DBMS_MIN_REVISION_WITH_X_FORWARDED_FOR_IN_CLIENT_INFO: u64 = 54443;
 // pub const DBMS_MIN_REVISION_WITH_REFERER_IN_CLIENT_INFO: u64 = 54447;
 pub const DBMS_MIN_PROTOCOL_VERSION_WITH_DISTRIBUTED_DEPTH: u64 = 54448;
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_INITIAL_QUERY_START_TIME: u64 = 54449;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_INCREMENTAL_PROFILE_EVENTS: u64 = 54451;
+pub const DBMS_MIN_REVISION_WITH_PARALLEL_REPLICAS: u64 = 54453;
+pub const DBMS_MIN_REVISION_WITH_CUSTOM_SERIALIZATION: u64 = 54454;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_PROFILE_EVENTS_IN_INSERT: u64 = 54456;
+// pub const DBMS_MIN_PROTOCOL_VERSION_WITH_VIEW_IF_PERMITTED: u64 = 54457;
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM: u64 = 54458;
+pub const DBMS_MIN_PROTOCOL_VERSION_WITH_PARAMETERS: u64 = 54459;
 
-pub const DBMS_TCP_PROTOCOL_VERSION: u64 = 54448;
+pub const DBMS_TCP_PROTOCOL_VERSION: u64 = 54459;
 
 pub const MAX_STRING_SIZE: usize = 1 << 30;
 
 #[repr(u64)]
 #[derive(Clone, Copy, Debug)]
-#[allow(unused)]
 pub enum ClientPacketId {
     Hello,
     Query,
@@ -44,6 +51,24 @@ pub enum ClientPacketId {
     ReadTaskResponse,
 }
 
+impl ClientPacketId {
+    pub fn from_u64(i: u64) -> Result<Self> {
+        Ok(match i {
+            0 => ClientPacketId::Hello,
+            1 => ClientPacketId::Query,
+            2 => ClientPacketId::Data,
+            3 => ClientPacketId::Cancel,
+            4 => ClientPacketId::Ping,
+            5 => ClientPacketId::TablesStatusRequest,
+            6 => ClientPacketId::KeepAlive,
+            7 => ClientPacketId::Scalar,
+            8 => ClientPacketId::IgnoredPartUUIDs,
+            9 => ClientPacketId::ReadTaskResponse,
+            x => return Err(anyhow!("invalid packet id from client: {}", x)),
+        })
+    }
+}
+
 #[repr(u64)]
 #[derive(Clone, Copy, Debug)]
 pub enum ServerPacketId {
@@ -61,6 +86,7 @@ pub enum ServerPacketId {
     TableColumns,
     PartUUIDs,
     ReadTaskRequest,
+    ProfileEvents,
 }
 
 impl ServerPacketId {
@@ -80,6 +106,7 @@ impl ServerPacketId {
             11 => ServerPacketId::TableColumns,
             12 => ServerPacketId::PartUUIDs,
             13 => ServerPacketId::ReadTaskRequest,
+            14 => ServerPacketId::ProfileEvents,
             x => return Err(anyhow!("invalid packet id from server: {}", x)),
         })
     }
@@ -139,6 +166,97 @@ pub struct TableColumns {
     pub description: String,
 }
 
+/// A column of a table, as described in [`TableColumns::description`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDescription {
+    pub name: String,
+    /// Type name, as printed by Clickhouse
+    pub type_name: String,
+    /// One of `DEFAULT`, `MATERIALIZED`, `ALIAS` or `EPHEMERAL`, if the column has a default expression
+    pub default_kind: Option<String>,
+    pub default_expression: Option<String>,
+}
+
+impl TableColumns {
+    /// Parses the column list out of [`TableColumns::description`].
+    pub fn columns(&self) -> Result<Vec<ColumnDescription>> {
+        let mut lines = self.description.lines();
+        match lines.next() {
+            Some("columns format version: 1") => (),
+            _ => return Err(anyhow!("unsupported columns description format")),
+        }
+        let count = lines
+            .next()
+            .and_then(|x| x.strip_suffix(" columns:"))
+            .and_then(|x| x.parse::<usize>().ok())
+            .ok_or_else(|| anyhow!("malformed columns description"))?;
+        let mut out = Vec::with_capacity(count);
+        for line in lines.take(count) {
+            out.push(parse_column_description(line)?);
+        }
+        if out.len() != count {
+            return Err(anyhow!("truncated columns description"));
+        }
+        Ok(out)
+    }
+}
+
+pub(crate) fn unescape(input: &str, quote: Option<char>) -> Result<(String, &str)> {
+    let mut out = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let (_, c) = chars
+                    .next()
+                    .ok_or_else(|| anyhow!("malformed escape sequence"))?;
+                out.push(match c {
+                    'n' => '\n',
+                    't' => '\t',
+                    'r' => '\r',
+                    '0' => '\0',
+                    'b' => '\x08',
+                    'f' => '\x0c',
+                    c => c,
+                });
+            }
+            c if Some(c) == quote => return Ok((out, &input[i + c.len_utf8()..])),
+            c => out.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(anyhow!("unterminated quoted identifier"));
+    }
+    Ok((out, ""))
+}
+
+fn parse_column_description(line: &str) -> Result<ColumnDescription> {
+    let line = line
+        .strip_prefix('`')
+        .ok_or_else(|| anyhow!("malformed column description: {}", line))?;
+    let (name, rest) = unescape(line, Some('`'))?;
+    let rest = rest
+        .strip_prefix(' ')
+        .ok_or_else(|| anyhow!("malformed column description for {}", name))?;
+    let mut parts = rest.split('\t');
+    let type_name = parts.next().unwrap_or_default().to_string();
+    let mut default_kind = None;
+    let mut default_expression = None;
+    // default, then optional comment, codec and TTL
+    if let Some(part) = parts.next() {
+        if matches!(part, "DEFAULT" | "MATERIALIZED" | "ALIAS" | "EPHEMERAL") {
+            default_kind = Some(part.to_string());
+            default_expression = Some(unescape(parts.next().unwrap_or_default(), None)?.0);
+        }
+    }
+    Ok(ColumnDescription {
+        name,
+        type_name,
+        default_kind,
+        default_expression,
+    })
+}
+
 #[derive(Debug, Clone)]
 pub struct TableStatus {
     pub is_replicated: bool,
@@ -166,23 +284,120 @@ pub enum ServerPacket {
     TableColumns(TableColumns),
     PartUUIDs(Vec<Uuid>),
     ReadTaskRequest,
+    ProfileEvents(ServerData),
 }
 
-#[derive(Clone, Copy, Debug)]
-#[allow(unused)]
+#[derive(Debug, Clone, Default)]
+pub struct ClientHelloPacket {
+    pub client_name: String,
+    pub major_version: u64,
+    pub minor_version: u64,
+    pub protocol_version: u64,
+    pub default_database: String,
+    pub username: String,
+    pub password: String,
+    /// Sent after the handshake if DBMS_MIN_PROTOCOL_VERSION_WITH_ADDENDUM
+    pub quota_key: String,
+}
+
+#[repr(u8)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+#[allow(clippy::enum_variant_names)]
+pub enum QueryKind {
+    #[default]
+    NoQuery,
+    InitialQuery,
+    SecondaryQuery,
+}
+
+impl QueryKind {
+    pub fn from_u8(i: u8) -> Result<Self> {
+        Ok(match i {
+            0 => QueryKind::NoQuery,
+            1 => QueryKind::InitialQuery,
+            2 => QueryKind::SecondaryQuery,
+            x => return Err(anyhow!("invalid query kind from client: {}", x)),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenTelemetry {
+    pub trace_id: Uuid,
+    pub span_id: u64,
+    pub tracestate: String,
+    pub trace_flags: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientInfo {
+    pub kind: QueryKind,
+    pub initial_user: String,
+    pub initial_query_id: String,
+    pub initial_address: String,
+    pub os_user: String,
+    pub client_hostname: String,
+    pub client_name: String,
+    pub client_version_major: u64,
+    pub client_version_minor: u64,
+    pub client_tcp_protocol_version: u64,
+    pub quota_key: String,
+    pub distributed_depth: u64,
+    pub client_version_patch: u64,
+    pub open_telemetry: Option<OpenTelemetry>,
+    pub initial_query_start_time_microseconds: u64,
+    pub collaborate_with_initiator: u64,
+    pub count_participating_replicas: u64,
+    pub number_of_current_replica: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientQuery {
+    pub id: String,
+    pub info: ClientInfo,
+    pub settings: Vec<(String, String)>,
+    pub stage: u64,
+    pub compression: CompressionMethod,
+    pub query: String,
+    /// Query parameters, with values quoted as sent by the client, i.e. `'value'`
+    pub parameters: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientData {
+    pub table_name: String,
+    pub block: Block,
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum ClientPacket {
+    Hello(ClientHelloPacket),
+    Query(ClientQuery),
+    Data(ClientData),
+    Cancel,
+    Ping,
+    Scalar(ClientData),
+}
+
+/// Compression used for data blocks sent over the native protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum CompressionMethod {
     None,
     LZ4,
+    ZSTD,
 }
 
-#[cfg(feature = "compression")]
+#[cfg(any(feature = "compression", feature = "lz4-pure"))]
+#[allow(clippy::derivable_impls)]
 impl Default for CompressionMethod {
     fn default() -> Self {
         CompressionMethod::LZ4
     }
 }
 
-#[cfg(not(feature = "compression"))]
+#[cfg(not(any(feature = "compression", feature = "lz4-pure")))]
+#[allow(clippy::derivable_impls)]
 impl Default for CompressionMethod {
     fn default() -> Self {
         CompressionMethod::None
@@ -194,6 +409,80 @@ impl CompressionMethod {
         match self {
             CompressionMethod::None => 0x02,
             CompressionMethod::LZ4 => 0x82,
+            CompressionMethod::ZSTD => 0x90,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Result<Self> {
+        Ok(match byte {
+            0x02 => CompressionMethod::None,
+            0x82 => CompressionMethod::LZ4,
+            0x90 => CompressionMethod::ZSTD,
+            x => {
+                return Err(anyhow!(
+                    "unknown compression algorithm identifier: '{:02X}'",
+                    x
+                ))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_columns() {
+        let columns = TableColumns {
+            name: "test".to_string(),
+            description: "columns format version: 1\n4 columns:\n`id` UInt32\n`name` String\tDEFAULT\t\\'none\\'\n`we\\`ird` Nullable(String)\tCOMMENT \\'x\\'\n`total` UInt64\tMATERIALIZED\tid * 2\tCODEC(ZSTD(1))\n".to_string(),
+        };
+        assert_eq!(
+            columns.columns().unwrap(),
+            vec![
+                ColumnDescription {
+                    name: "id".to_string(),
+                    type_name: "UInt32".to_string(),
+                    default_kind: None,
+                    default_expression: None,
+                },
+                ColumnDescription {
+                    name: "name".to_string(),
+                    type_name: "String".to_string(),
+                    default_kind: Some("DEFAULT".to_string()),
+                    default_expression: Some("'none'".to_string()),
+                },
+                ColumnDescription {
+                    name: "we`ird".to_string(),
+                    type_name: "Nullable(String)".to_string(),
+                    default_kind: None,
+                    default_expression: None,
+                },
+                ColumnDescription {
+                    name: "total".to_string(),
+                    type_name: "UInt64".to_string(),
+                    default_kind: Some("MATERIALIZED".to_string()),
+                    default_expression: Some("id * 2".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_table_columns_malformed() {
+        for description in [
+            "",
+            "columns format version: 2\n0 columns:\n",
+            "columns format version: 1\n2 columns:\n`id` UInt32\n",
+            "columns format version: 1\n1 columns:\nid UInt32\n",
+            "columns format version: 1\n1 columns:\n`id UInt32\n",
+        ] {
+            let columns = TableColumns {
+                name: "test".to_string(),
+                description: description.to_string(),
+            };
+            assert!(columns.columns().is_err(), "{:?}", description);
         }
     }
 }
@@ -0,0 +1,210 @@
+use std::{fmt::Write, str::FromStr};
+
+use crate::{types::Type, values::write_quoted_identifier};
+use anyhow::*;
+
+/// How the value of a column is computed when not given, from `system.columns`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultKind {
+    Default,
+    Materialized,
+    Alias,
+    Ephemeral,
+}
+
+impl FromStr for DefaultKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "DEFAULT" => DefaultKind::Default,
+            "MATERIALIZED" => DefaultKind::Materialized,
+            "ALIAS" => DefaultKind::Alias,
+            "EPHEMERAL" => DefaultKind::Ephemeral,
+            _ => return Err(anyhow!("unknown default kind: {}", s)),
+        })
+    }
+}
+
+/// Metadata of a table column, as returned by [`crate::Client::describe_table`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnInfo {
+    pub name: String,
+    /// Type as given by Clickhouse, i.e. `Nullable(String)`.
+    pub type_name: String,
+    /// Parsed type of the column, `None` if it is not supported, i.e. `AggregateFunction(sum, UInt64)`.
+    pub type_: Option<Type>,
+    /// `None` if the column has no default expression.
+    pub default_kind: Option<DefaultKind>,
+    pub default_expression: String,
+    pub comment: String,
+    /// Compression codec, i.e. `CODEC(ZSTD(1))`, or empty for the table default.
+    pub codec: String,
+    pub is_in_partition_key: bool,
+    pub is_in_sorting_key: bool,
+    pub is_in_primary_key: bool,
+    pub is_in_sampling_key: bool,
+}
+
+pub(crate) type ColumnRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    u8,
+    u8,
+    u8,
+    u8,
+);
+
+impl ColumnInfo {
+    pub(crate) fn from_row(row: ColumnRow) -> Result<Self> {
+        let (
+            name,
+            type_name,
+            default_kind,
+            default_expression,
+            comment,
+            codec,
+            is_in_partition_key,
+            is_in_sorting_key,
+            is_in_primary_key,
+            is_in_sampling_key,
+        ) = row;
+        let type_ = Type::from_str(&type_name).ok();
+        let default_kind = if default_kind.is_empty() {
+            None
+        } else {
+            Some(default_kind.parse()?)
+        };
+        Ok(ColumnInfo {
+            name,
+            type_name,
+            type_,
+            default_kind,
+            default_expression,
+            comment,
+            codec,
+            is_in_partition_key: is_in_partition_key != 0,
+            is_in_sorting_key: is_in_sorting_key != 0,
+            is_in_primary_key: is_in_primary_key != 0,
+            is_in_sampling_key: is_in_sampling_key != 0,
+        })
+    }
+}
+
+/// Metadata of a table, as returned by [`crate::Client::table_info`]. Keys are given as expressions, empty if not set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableInfo {
+    pub database: String,
+    pub name: String,
+    /// Engine name, i.e. `MergeTree`.
+    pub engine: String,
+    /// Engine with its parameters and settings, i.e. `MergeTree ORDER BY id SETTINGS index_granularity = 8192`.
+    pub engine_full: String,
+    pub partition_key: String,
+    pub sorting_key: String,
+    pub primary_key: String,
+    pub sampling_key: String,
+}
+
+pub(crate) type TableRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+    String,
+);
+
+impl From<TableRow> for TableInfo {
+    fn from(row: TableRow) -> Self {
+        let (
+            database,
+            name,
+            engine,
+            engine_full,
+            partition_key,
+            sorting_key,
+            primary_key,
+            sampling_key,
+        ) = row;
+        TableInfo {
+            database,
+            name,
+            engine,
+            engine_full,
+            partition_key,
+            sorting_key,
+            primary_key,
+            sampling_key,
+        }
+    }
+}
+
+/// Column definition of a [`RowSchema`], used to generate `CREATE TABLE` statements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: &'static str,
+    pub type_: Type,
+    /// Expression of the `DEFAULT` clause, i.e. `now()`.
+    pub default: Option<&'static str>,
+    /// Codecs of the `CODEC` clause, i.e. `ZSTD(3)`.
+    pub codec: Option<&'static str>,
+}
+
+/// Table schema of a [`crate::Row`].
+/// Implemented by `#[derive(Row)]` for structs with named fields, whenever each field type implements [`crate::SqlType`] or has a `#[klickhouse(type = "...")]` attribute.
+/// The derive also adds inherent `clickhouse_schema()` and `create_table_sql(table, engine)` functions.
+pub trait RowSchema {
+    /// Serialized columns of the row, in order.
+    fn columns() -> Vec<ColumnDef>;
+
+    /// `ORDER BY` expression of the table, from `#[klickhouse(order_by = "...")]`.
+    fn order_by() -> Option<&'static str> {
+        None
+    }
+
+    /// `PARTITION BY` expression of the table, from `#[klickhouse(partition_by = "...")]`.
+    fn partition_by() -> Option<&'static str> {
+        None
+    }
+}
+
+/// Parses the type of a `#[klickhouse(type = "...")]` attribute, which the derive already validated at compile time.
+#[doc(hidden)]
+pub fn parse_column_type(column: &str, type_: &str) -> Type {
+    match Type::from_str(type_) {
+        Ok(type_) => type_,
+        Err(e) => panic!("invalid type for column `{}`: {:#}", column, e),
+    }
+}
+
+/// Generates a `CREATE TABLE` statement for the columns of `T`.
+/// `table` and `engine` are inserted as is, so the table name may be qualified with a database, and the engine may have parameters.
+pub fn create_table_sql<T: RowSchema>(table: &str, engine: &str) -> String {
+    let mut out = format!("CREATE TABLE {} (", table);
+    for (i, column) in T::columns().into_iter().enumerate() {
+        out.push_str(if i == 0 { "\n    " } else { ",\n    " });
+        write_quoted_identifier(column.name, &mut out);
+        write!(out, " {}", column.type_).unwrap();
+        if let Some(default) = column.default {
+            write!(out, " DEFAULT {}", default).unwrap();
+        }
+        if let Some(codec) = column.codec {
+            write!(out, " CODEC({})", codec).unwrap();
+        }
+    }
+    write!(out, "\n) ENGINE = {}", engine).unwrap();
+    if let Some(partition_by) = T::partition_by() {
+        write!(out, "\nPARTITION BY {}", partition_by).unwrap();
+    }
+    if let Some(order_by) = T::order_by() {
+        write!(out, "\nORDER BY {}", order_by).unwrap();
+    }
+    out
+}
@@ -0,0 +1,133 @@
+use std::{fmt::Write, marker::PhantomData};
+
+use futures::{Stream, StreamExt};
+
+use crate::{
+    client::first_row, convert::Row, values::write_quoted_identifier, Client, QueryParams,
+};
+use anyhow::*;
+
+/// A `SELECT` query for exactly the columns of a [`Row`], built with [`Client::select`].
+/// ```ignore
+/// let rows = client
+///     .select::<Event>("analytics.events")
+///     .filter("id > {id:UInt64}")
+///     .param("id", 5u64)
+///     .limit(10)
+///     .fetch_all()
+///     .await?;
+/// ```
+pub struct Select<T: Row> {
+    client: Client,
+    table: String,
+    filters: Vec<String>,
+    order_by: Option<String>,
+    limit: Option<u64>,
+    params: QueryParams,
+    _row: PhantomData<T>,
+}
+
+impl<T: Row> Select<T> {
+    pub(crate) fn new(client: Client, table: &str) -> Self {
+        Select {
+            client,
+            table: table.to_string(),
+            filters: vec![],
+            order_by: None,
+            limit: None,
+            params: QueryParams::new(),
+            _row: PhantomData,
+        }
+    }
+
+    /// Adds a `WHERE` condition, inserted as is. Multiple filters are combined with `AND`.
+    pub fn filter(mut self, condition: impl Into<String>) -> Self {
+        self.filters.push(condition.into());
+        self
+    }
+
+    /// Sets the `ORDER BY` expression, inserted as is.
+    pub fn order_by(mut self, order_by: impl Into<String>) -> Self {
+        self.order_by = Some(order_by.into());
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Binds `value` to a `{name:Type}` placeholder of a filter, see [`QueryParams::add`].
+    pub fn param(mut self, name: impl Into<String>, value: impl crate::ToSql) -> Self {
+        self.params.add(name, value);
+        self
+    }
+
+    /// Renders the query. The table name is quoted as an identifier, qualified with a database if it contains a `.`.
+    /// Fails for positional rows, which have no column names.
+    pub fn sql(&self) -> Result<String> {
+        let columns = T::column_names()
+            .ok_or_else(|| anyhow!("cannot select positional rows by column name"))?;
+        if columns.is_empty() {
+            return Err(anyhow!("cannot select a row without columns"));
+        }
+        let mut out = "SELECT ".to_string();
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                out.push_str(", ");
+            }
+            write_quoted_identifier(column, &mut out);
+        }
+        out.push_str(" FROM ");
+        match self.table.split_once('.') {
+            Some((database, table)) => {
+                write_quoted_identifier(database, &mut out);
+                out.push('.');
+                write_quoted_identifier(table, &mut out);
+            }
+            None => write_quoted_identifier(&self.table, &mut out),
+        }
+        for (i, filter) in self.filters.iter().enumerate() {
+            out.push_str(if i == 0 { " WHERE (" } else { " AND (" });
+            out.push_str(filter);
+            out.push(')');
+        }
+        if let Some(order_by) = &self.order_by {
+            write!(out, " ORDER BY {}", order_by)?;
+        }
+        if let Some(limit) = self.limit {
+            write!(out, " LIMIT {}", limit)?;
+        }
+        Ok(out)
+    }
+
+    /// Runs the query, returning a stream of deserialized rows, as with [`Client::query`].
+    pub async fn fetch(self) -> Result<impl Stream<Item = Result<T>>> {
+        let query = self.sql()?;
+        self.client.query_with(&query, self.params).await
+    }
+
+    /// Runs the query, collecting all rows.
+    pub async fn fetch_all(self) -> Result<Vec<T>> {
+        self.fetch()
+            .await?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    /// Runs the query, returning its only row, or `None` if there are no rows.
+    /// Fails if more than one row is returned.
+    pub async fn fetch_opt(self) -> Result<Option<T>> {
+        first_row(self.fetch().await?).await
+    }
+
+    /// Runs the query, returning its only row.
+    /// Fails unless exactly one row is returned.
+    pub async fn fetch_one(self) -> Result<T> {
+        self.fetch_opt()
+            .await?
+            .ok_or_else(|| anyhow!("query returned no rows"))
+    }
+}
@@ -0,0 +1,293 @@
+use chrono::TimeZone;
+use serde::{
+    de::{
+        self,
+        value::{MapDeserializer, SeqDeserializer},
+        IntoDeserializer, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+
+use super::Error;
+use crate::{convert::unexpected_value, types::Type, Value};
+
+/// Deserializer of a [`Value`] of a given [`Type`], see [`super::from_value`].
+pub struct Deserializer<'a> {
+    type_: &'a Type,
+    value: Value,
+}
+
+impl<'a> Deserializer<'a> {
+    pub fn new(type_: &'a Type, value: Value) -> Self {
+        Deserializer {
+            type_: type_.strip_low_cardinality(),
+            value,
+        }
+    }
+
+    /// Text form of values without a serde equivalent.
+    fn into_text(self) -> Result<String, Error> {
+        let text = match (self.type_, &self.value) {
+            (Type::Enum8(entries), Value::Enum8(index)) => enum_name(entries, *index),
+            (Type::Enum16(entries), Value::Enum16(index)) => enum_name(entries, *index),
+            (_, Value::DateTime(x)) => Some(x.0.timestamp(x.1 as i64, 0).to_rfc3339()),
+            (_, Value::DateTime64(tz, precision, ticks)) if *precision <= 9 => {
+                let modulus = 10u64.pow(*precision as u32);
+                let nanos = (ticks % modulus) * 10u64.pow(9 - *precision as u32);
+                Some(
+                    tz.timestamp((ticks / modulus) as i64, nanos as u32)
+                        .to_rfc3339(),
+                )
+            }
+            (_, Value::String(x)) => Some(x.clone()),
+            (_, Value::Int256(x)) => Some(x.to_string()),
+            (_, Value::UInt256(x)) => Some(x.to_string()),
+            (_, Value::Decimal32(..))
+            | (_, Value::Decimal64(..))
+            | (_, Value::Decimal128(..))
+            | (_, Value::Decimal256(..))
+            | (_, Value::Uuid(_))
+            | (_, Value::Date(_))
+            | (_, Value::Ipv4(_))
+            | (_, Value::Ipv6(_)) => {
+                let mut out = String::new();
+                self.value.write_escaped(self.type_, &mut out)?;
+                Some(out)
+            }
+            _ => None,
+        };
+        text.ok_or_else(|| unexpected_value(self.type_, &self.value).into())
+    }
+}
+
+fn enum_name<T: PartialEq>(entries: &[(String, T)], index: T) -> Option<String> {
+    entries
+        .iter()
+        .find(|(_, x)| *x == index)
+        .map(|(name, _)| name.clone())
+}
+
+impl<'de, 'a> IntoDeserializer<'de, Error> for Deserializer<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match (self.type_, self.value) {
+            (Type::Nullable(_), Value::Null) => visitor.visit_none(),
+            (Type::Nullable(inner), value) => {
+                Deserializer::new(inner, value).deserialize_any(visitor)
+            }
+            (_, Value::Int8(x)) => visitor.visit_i8(x),
+            (_, Value::Int16(x)) => visitor.visit_i16(x),
+            (_, Value::Int32(x)) => visitor.visit_i32(x),
+            (_, Value::Int64(x)) => visitor.visit_i64(x),
+            (_, Value::Int128(x)) => visitor.visit_i128(x),
+            (_, Value::UInt8(x)) => visitor.visit_u8(x),
+            (_, Value::UInt16(x)) => visitor.visit_u16(x),
+            (_, Value::UInt32(x)) => visitor.visit_u32(x),
+            (_, Value::UInt64(x)) => visitor.visit_u64(x),
+            (_, Value::UInt128(x)) => visitor.visit_u128(x),
+            (_, Value::Float32(x)) => visitor.visit_f32(f32::from_bits(x)),
+            (_, Value::Float64(x)) => visitor.visit_f64(f64::from_bits(x)),
+            (_, Value::String(x)) => visitor.visit_string(x),
+            (Type::Array(inner), Value::Array(values)) => {
+                let mut seq =
+                    SeqDeserializer::new(values.into_iter().map(|x| Deserializer::new(inner, x)));
+                let out = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(out)
+            }
+            (Type::Tuple(types), Value::Tuple(values)) if types.len() == values.len() => {
+                let mut seq = SeqDeserializer::new(
+                    types
+                        .iter()
+                        .zip(values)
+                        .map(|(type_, x)| Deserializer::new(type_, x)),
+                );
+                let out = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(out)
+            }
+            (Type::Map(key_type, value_type), Value::Map(keys, values))
+                if keys.len() == values.len() =>
+            {
+                let mut map =
+                    MapDeserializer::new(keys.into_iter().zip(values).map(|(key, value)| {
+                        (
+                            Deserializer::new(key_type, key),
+                            Deserializer::new(value_type, value),
+                        )
+                    }));
+                let out = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(out)
+            }
+            (type_, value) => visitor.visit_string(Deserializer::new(type_, value).into_text()?),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::UInt8(x) => visitor.visit_bool(x != 0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_f64(visitor)
+    }
+
+    /// Decimals deserialize into floats, with the precision of a float.
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::Decimal32(..)
+            | Value::Decimal64(..)
+            | Value::Decimal128(..)
+            | Value::Decimal256(..) => {
+                let text = self.into_text()?;
+                visitor.visit_f64(
+                    text.parse().map_err(|_| {
+                        Error(format!("could not convert decimal {} to a float", text))
+                    })?,
+                )
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.value {
+            Value::String(x) => visitor.visit_byte_buf(x.into_bytes()),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match (self.type_, self.value) {
+            (_, Value::Null) => visitor.visit_none(),
+            (Type::Nullable(inner), value) => visitor.visit_some(Deserializer::new(inner, value)),
+            (type_, value) => visitor.visit_some(Deserializer::new(type_, value)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Enums deserialize from the name of a unit variant, i.e. the entry of an `Enum8` or `Enum16` column.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let variant: String = match self.type_ {
+            Type::Nullable(inner) => Deserializer::new(inner, self.value).into_text()?,
+            _ => self.into_text()?,
+        };
+        visitor.visit_enum(variant.into_deserializer())
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        seq tuple tuple_struct map struct identifier
+    }
+}
+
+/// Deserializer of a row, as a map of column names to values, or a sequence of values for tuples.
+pub struct RowDeserializer<'a> {
+    row: Vec<(&'a str, &'a Type, Value)>,
+}
+
+impl<'a> RowDeserializer<'a> {
+    pub fn new(row: Vec<(&'a str, &'a Type, Value)>) -> Self {
+        RowDeserializer { row }
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for RowDeserializer<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut map = MapDeserializer::new(
+            self.row
+                .into_iter()
+                .map(|(name, type_, value)| (name, Deserializer::new(type_, value))),
+        );
+        let out = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(out)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut seq = SeqDeserializer::new(
+            self.row
+                .into_iter()
+                .map(|(_, type_, value)| Deserializer::new(type_, value)),
+        );
+        let out = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(out)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct map struct enum identifier ignored_any
+    }
+}
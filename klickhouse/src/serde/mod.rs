@@ -0,0 +1,90 @@
+//! Conversions between serde types and Clickhouse values, enabled by the `serde` feature.
+//!
+//! [`to_value`] serializes any [`Serialize`] type into a [`Value`], and [`from_value`] deserializes a [`Value`] of a given [`Type`].
+//! [`SerdeRow`] uses these to query rows into any [`DeserializeOwned`] type, with struct fields matched to columns by name.
+//!
+//! Values without a serde equivalent are deserialized from text: `Enum8`/`Enum16` as the entry name, `Uuid` and IP addresses as usual,
+//! `Date` as `YYYY-MM-DD`, `DateTime` and `DateTime64` as RFC 3339, and `Decimal` and 256 bit integers as decimal strings
+//! (`Decimal` also deserializes into floats). [`to_typed_value`] converts serialized text back for a column of one of these types.
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{convert::Row, types::Type, Value};
+
+mod de;
+mod ser;
+#[cfg(test)]
+mod tests;
+
+pub use de::{Deserializer, RowDeserializer};
+pub use ser::Serializer;
+
+/// Error of a serde conversion, converted into an [`anyhow::Error`] by [`to_value`], [`from_value`] and [`SerdeRow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        Error(e.to_string())
+    }
+}
+
+/// Serializes `value` into a [`Value`]. Structs and tuples become `Tuple`s, sequences `Array`s, and maps `Map`s.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> anyhow::Result<Value> {
+    Ok(value.serialize(Serializer)?)
+}
+
+/// Serializes `value` into a [`Value`] of type `type_`, converting text to enum, UUID, IP, date and date time values as described above,
+/// and widening numbers where lossless. The result is not validated against `type_`.
+pub fn to_typed_value<T: Serialize + ?Sized>(type_: &Type, value: &T) -> anyhow::Result<Value> {
+    let value = ser::parse_text_value(type_, to_value(value)?);
+    Ok(type_.coerce_value(value))
+}
+
+/// Deserializes a [`Value`] of type `type_` into `T`.
+pub fn from_value<T: DeserializeOwned>(type_: &Type, value: Value) -> anyhow::Result<T> {
+    Ok(T::deserialize(Deserializer::new(type_, value))?)
+}
+
+/// A row deserialized with serde, i.e. `client.query::<SerdeRow<MyType>>(...)` for any `MyType: DeserializeOwned`.
+/// Struct fields are matched to columns by name, and tuples by position.
+/// Rows cannot be serialized for inserts, as `T` need not implement [`Serialize`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SerdeRow<T>(pub T);
+
+impl<T> SerdeRow<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned> Row for SerdeRow<T> {
+    fn deserialize_row(map: Vec<(&str, &Type, Value)>) -> anyhow::Result<Self> {
+        Ok(SerdeRow(T::deserialize(RowDeserializer::new(map))?))
+    }
+
+    fn serialize_row(self) -> anyhow::Result<Vec<(&'static str, Value)>> {
+        Err(anyhow::anyhow!("serde rows cannot be serialized"))
+    }
+}
@@ -0,0 +1,376 @@
+use std::convert::TryFrom;
+
+use chrono::NaiveDate;
+use serde::ser::{self, Serialize};
+
+use super::Error;
+use crate::{types::Type, Date, DateTime, Ipv4, Ipv6, Value};
+
+/// Serializer of any [`Serialize`] type into a [`Value`], see [`super::to_value`].
+/// Values are typed after the Rust type (i.e. `u32` is `UInt32`), and converted to the column type when inserted.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeVec;
+    type SerializeTuple = SerializeVec;
+    type SerializeTupleStruct = SerializeVec;
+    type SerializeTupleVariant = ser::Impossible<Value, Error>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeVec;
+    type SerializeStructVariant = ser::Impossible<Value, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::UInt8(v as u8))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(Value::Int8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(Value::Int16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(Value::Int32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(Value::Int64(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        Ok(Value::Int128(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(Value::UInt8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(Value::UInt16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(Value::UInt32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(Value::UInt64(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        Ok(Value::UInt128(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(Value::Float32(v.to_bits()))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(Value::Float64(v.to_bits()))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        String::from_utf8(v.to_vec())
+            .map(Value::String)
+            .map_err(|_| Error("bytes must be valid UTF-8 to serialize as a String".to_string()))
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Err(Error(
+            "unit values have no Clickhouse equivalent".to_string(),
+        ))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, Error> {
+        Err(Error(format!(
+            "unit struct `{}` has no Clickhouse equivalent",
+            name
+        )))
+    }
+
+    /// Unit variants are serialized by name, for `Enum8` and `Enum16` columns.
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, Error> {
+        Err(variant_error(name, variant))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec::new(len, false))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec::new(Some(len), true))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec::new(Some(len), true))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(variant_error(name, variant))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeMap, Error> {
+        Ok(SerializeMap {
+            keys: Vec::with_capacity(len.unwrap_or_default()),
+            values: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    /// Structs are serialized as `Tuple`s of their fields, in order.
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeVec, Error> {
+        Ok(SerializeVec::new(Some(len), true))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(variant_error(name, variant))
+    }
+}
+
+fn variant_error(name: &'static str, variant: &'static str) -> Error {
+    Error(format!(
+        "enum variant `{}::{}` has fields, only unit variants can be serialized",
+        name, variant
+    ))
+}
+
+/// Serializes sequences into an `Array`, and tuples and structs into a `Tuple`.
+pub struct SerializeVec {
+    values: Vec<Value>,
+    tuple: bool,
+}
+
+impl SerializeVec {
+    fn new(len: Option<usize>, tuple: bool) -> Self {
+        SerializeVec {
+            values: Vec::with_capacity(len.unwrap_or_default()),
+            tuple,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.values.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        if self.tuple {
+            Value::Tuple(self.values)
+        } else {
+            Value::Array(self.values)
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStruct for SerializeVec {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Serializes maps into a `Map`.
+pub struct SerializeMap {
+    keys: Vec<Value>,
+    values: Vec<Value>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.keys.push(key.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.values.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        Ok(Value::Map(self.keys, self.values))
+    }
+}
+
+/// Converts serialized text in `value` to the type `type_`, i.e. unit variants into `Enum8` values, see [`super::to_typed_value`].
+/// Text which does not convert exactly is returned unchanged.
+pub(super) fn parse_text_value(type_: &Type, value: Value) -> Value {
+    match (type_, value) {
+        (Type::LowCardinality(inner), value) | (Type::Nullable(inner), value) => {
+            parse_text_value(inner, value)
+        }
+        (Type::Array(inner), Value::Array(values)) => Value::Array(
+            values
+                .into_iter()
+                .map(|x| parse_text_value(inner, x))
+                .collect(),
+        ),
+        (Type::Tuple(types), Value::Tuple(values)) if types.len() == values.len() => Value::Tuple(
+            types
+                .iter()
+                .zip(values)
+                .map(|(type_, value)| parse_text_value(type_, value))
+                .collect(),
+        ),
+        (Type::Map(key, value), Value::Map(keys, values)) => Value::Map(
+            keys.into_iter().map(|x| parse_text_value(key, x)).collect(),
+            values
+                .into_iter()
+                .map(|x| parse_text_value(value, x))
+                .collect(),
+        ),
+        (type_, Value::String(text)) => match parse_text(type_, &text) {
+            Some(value) => value,
+            None => Value::String(text),
+        },
+        (_, value) => value,
+    }
+}
+
+/// Parses the text form of an enum name, UUID, IP address, `YYYY-MM-DD` date or RFC 3339 date time,
+/// i.e. as serialized by serde for types without a native Clickhouse value. Returns `None` unless the text converts exactly.
+fn parse_text(type_: &Type, text: &str) -> Option<Value> {
+    Some(match type_ {
+        Type::Enum8(entries) => Value::Enum8(entries.iter().find(|x| x.0 == text)?.1),
+        Type::Enum16(entries) => Value::Enum16(entries.iter().find(|x| x.0 == text)?.1),
+        Type::Uuid => Value::Uuid(text.parse().ok()?),
+        Type::Ipv4 => Value::Ipv4(Ipv4(text.parse().ok()?)),
+        Type::Ipv6 => Value::Ipv6(Ipv6(text.parse().ok()?)),
+        Type::Date => {
+            let days = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()?
+                .signed_duration_since(NaiveDate::from_ymd(1970, 1, 1))
+                .num_days();
+            Value::Date(Date(u16::try_from(days).ok()?))
+        }
+        Type::DateTime(tz) => {
+            let seconds = chrono::DateTime::parse_from_rfc3339(text).ok()?.timestamp();
+            Value::DateTime(DateTime(*tz, u32::try_from(seconds).ok()?))
+        }
+        Type::DateTime64(precision, tz) if *precision <= 9 => {
+            let datetime = chrono::DateTime::parse_from_rfc3339(text).ok()?;
+            let nanos = datetime.timestamp_subsec_nanos() as u64;
+            let divisor = 10u64.pow(9 - *precision as u32);
+            if !nanos.is_multiple_of(divisor) {
+                return None;
+            }
+            let ticks = u64::try_from(datetime.timestamp())
+                .ok()?
+                .checked_mul(10u64.pow(*precision as u32))?
+                + nanos / divisor;
+            Value::DateTime64(*tz, *precision, ticks)
+        }
+        _ => return None,
+    })
+}
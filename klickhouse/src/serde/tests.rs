@@ -0,0 +1,270 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::*;
+use crate::{Date, DateTime, Uuid};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Active,
+    Disabled,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+struct Event {
+    id: u64,
+    name: String,
+    parent: Option<u64>,
+    tags: Vec<String>,
+    attributes: BTreeMap<String, u32>,
+    point: Point,
+    pair: (u8, String),
+    status: Status,
+    uuid: String,
+    day: String,
+    price: f64,
+    #[serde(default)]
+    missing: u32,
+}
+
+fn status_type() -> Type {
+    Type::Enum8(vec![("active".to_string(), 1), ("disabled".to_string(), 2)])
+}
+
+#[test]
+fn test_serde_row() {
+    let types = vec![
+        Type::UInt64,
+        Type::LowCardinality(Box::new(Type::String)),
+        Type::Nullable(Box::new(Type::UInt64)),
+        Type::Array(Box::new(Type::String)),
+        Type::Map(Box::new(Type::String), Box::new(Type::UInt32)),
+        Type::Tuple(vec![Type::Int32, Type::Int32]),
+        Type::Tuple(vec![Type::UInt8, Type::String]),
+        status_type(),
+        Type::Uuid,
+        Type::Date,
+        Type::Decimal64(2),
+    ];
+    let values = vec![
+        Value::UInt64(1),
+        Value::String("a".to_string()),
+        Value::Null,
+        Value::Array(vec![Value::String("x".to_string())]),
+        Value::Map(vec![Value::String("k".to_string())], vec![Value::UInt32(3)]),
+        Value::Tuple(vec![Value::Int32(-1), Value::Int32(2)]),
+        Value::Tuple(vec![Value::UInt8(4), Value::String("b".to_string())]),
+        Value::Enum8(2),
+        Value::Uuid(Uuid::nil()),
+        Value::Date(Date(1)),
+        Value::Decimal64(2, 1250),
+    ];
+    let names = [
+        "id",
+        "name",
+        "parent",
+        "tags",
+        "attributes",
+        "point",
+        "pair",
+        "status",
+        "uuid",
+        "day",
+        "price",
+    ];
+    let row = names
+        .iter()
+        .zip(types.iter())
+        .zip(values)
+        .map(|((name, type_), value)| (*name, type_, value))
+        .collect();
+    let SerdeRow(event) = SerdeRow::<Event>::deserialize_row(row).unwrap();
+    assert_eq!(
+        event,
+        Event {
+            id: 1,
+            name: "a".to_string(),
+            parent: None,
+            tags: vec!["x".to_string()],
+            attributes: vec![("k".to_string(), 3)].into_iter().collect(),
+            point: Point { x: -1, y: 2 },
+            pair: (4, "b".to_string()),
+            status: Status::Disabled,
+            uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+            day: "1970-01-02".to_string(),
+            price: 12.5,
+            missing: 0,
+        }
+    );
+
+    let error = SerdeRow::<Event>::deserialize_row(vec![("id", &Type::UInt64, Value::UInt64(1))])
+        .unwrap_err();
+    assert_eq!(error.to_string(), "missing field `name`");
+}
+
+#[test]
+fn test_serde_tuple_row() {
+    let row = vec![
+        ("a", &Type::UInt8, Value::UInt8(1)),
+        ("b", &Type::String, Value::String("x".to_string())),
+    ];
+    let SerdeRow(row) = SerdeRow::<(u8, String)>::deserialize_row(row).unwrap();
+    assert_eq!(row, (1, "x".to_string()));
+}
+
+#[test]
+fn test_from_value() {
+    let nullable = Type::Nullable(Box::new(Type::Int32));
+    assert_eq!(
+        from_value::<Option<i64>>(&nullable, Value::Int32(3)).unwrap(),
+        Some(3)
+    );
+    assert_eq!(
+        from_value::<Option<i64>>(&nullable, Value::Null).unwrap(),
+        None
+    );
+    assert!(from_value::<u8>(&Type::Int32, Value::Int32(-1)).is_err());
+    assert!(from_value::<bool>(&Type::UInt8, Value::UInt8(1)).unwrap());
+    assert_eq!(
+        from_value::<String>(
+            &Type::DateTime(chrono_tz::UTC),
+            Value::DateTime(DateTime(chrono_tz::UTC, 86400))
+        )
+        .unwrap(),
+        "1970-01-02T00:00:00+00:00"
+    );
+    assert_eq!(
+        from_value::<String>(
+            &Type::DateTime64(3, chrono_tz::UTC),
+            Value::DateTime64(chrono_tz::UTC, 3, 1500)
+        )
+        .unwrap(),
+        "1970-01-01T00:00:01.500+00:00"
+    );
+    assert_eq!(
+        from_value::<String>(&Type::Decimal128(3), Value::Decimal128(3, -1005)).unwrap(),
+        "-1.005"
+    );
+    assert_eq!(
+        from_value::<Status>(&status_type(), Value::Enum8(1)).unwrap(),
+        Status::Active
+    );
+    assert!(from_value::<Status>(&status_type(), Value::Enum8(3)).is_err());
+}
+
+#[test]
+fn test_from_nullable_value() {
+    let nullable = |type_| Type::Nullable(Box::new(type_));
+    assert_eq!(
+        from_value::<String>(&nullable(Type::String), Value::String("a".to_string())).unwrap(),
+        "a"
+    );
+    assert_eq!(
+        from_value::<u64>(&nullable(Type::UInt64), Value::UInt64(7)).unwrap(),
+        7
+    );
+    assert_eq!(
+        from_value::<f64>(&nullable(Type::Decimal64(2)), Value::Decimal64(2, 1250)).unwrap(),
+        12.5
+    );
+    assert_eq!(
+        from_value::<Status>(&nullable(status_type()), Value::Enum8(2)).unwrap(),
+        Status::Disabled
+    );
+    assert_eq!(
+        from_value::<Vec<u8>>(
+            &Type::Array(Box::new(nullable(Type::UInt8))),
+            Value::Array(vec![Value::UInt8(1)])
+        )
+        .unwrap(),
+        vec![1]
+    );
+    assert!(from_value::<u64>(&nullable(Type::UInt64), Value::Null).is_err());
+}
+
+#[test]
+fn test_to_value() {
+    assert_eq!(to_value(&7u32).unwrap(), Value::UInt32(7));
+    assert_eq!(
+        to_value(&Some("a")).unwrap(),
+        Value::String("a".to_string())
+    );
+    assert_eq!(to_value(&None::<u8>).unwrap(), Value::Null);
+    assert_eq!(
+        to_value(&Point { x: 1, y: 2 }).unwrap(),
+        Value::Tuple(vec![Value::Int32(1), Value::Int32(2)])
+    );
+    assert_eq!(
+        to_value(&vec![1u8, 2]).unwrap(),
+        Value::Array(vec![Value::UInt8(1), Value::UInt8(2)])
+    );
+    let map: BTreeMap<_, _> = vec![("k", 1i64)].into_iter().collect();
+    assert_eq!(
+        to_value(&map).unwrap(),
+        Value::Map(vec![Value::String("k".to_string())], vec![Value::Int64(1)])
+    );
+    assert!(to_value(&()).is_err());
+
+    // unit variants convert by name into enum values
+    let value = to_value(&Status::Disabled).unwrap();
+    assert_eq!(value, Value::String("disabled".to_string()));
+    assert_eq!(
+        to_typed_value(&status_type(), &Status::Disabled).unwrap(),
+        Value::Enum8(2)
+    );
+    assert_eq!(
+        to_typed_value(
+            &Type::Array(Box::new(Type::Nullable(Box::new(status_type())))),
+            &vec![Some(Status::Active), None]
+        )
+        .unwrap(),
+        Value::Array(vec![Value::Enum8(1), Value::Null])
+    );
+    // text which does not convert is left to validation
+    assert_eq!(
+        to_typed_value(&Type::Uuid, "x").unwrap(),
+        Value::String("x".to_string())
+    );
+    assert_eq!(
+        to_typed_value(
+            &Type::DateTime64(0, chrono_tz::UTC),
+            "1970-01-01T00:00:01.5Z"
+        )
+        .unwrap(),
+        Value::String("1970-01-01T00:00:01.5Z".to_string())
+    );
+}
+
+#[test]
+fn test_text_roundtrip() {
+    let values = [
+        (Type::Uuid, Value::Uuid(Uuid::nil())),
+        (
+            Type::Ipv4,
+            Value::Ipv4(crate::Ipv4("10.0.0.1".parse().unwrap())),
+        ),
+        (Type::Ipv6, Value::Ipv6(crate::Ipv6("::1".parse().unwrap()))),
+        (Type::Date, Value::Date(Date(19000))),
+        (
+            Type::DateTime(chrono_tz::UTC),
+            Value::DateTime(DateTime(chrono_tz::UTC, 1_600_000_000)),
+        ),
+        (
+            Type::DateTime64(6, chrono_tz::UTC),
+            Value::DateTime64(chrono_tz::UTC, 6, 1_600_000_000_123_456),
+        ),
+        (status_type(), Value::Enum8(1)),
+    ];
+    for (type_, value) in values {
+        let text = from_value::<String>(&type_, value.clone()).unwrap();
+        assert_eq!(to_typed_value(&type_, &text).unwrap(), value, "{}", text);
+    }
+}
@@ -0,0 +1,79 @@
+use anyhow::*;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, BufReader, BufWriter},
+    net::TcpStream,
+};
+
+use crate::{
+    internal_server_in::InternalServerIn,
+    internal_server_out::InternalServerOut,
+    protocol::{ClientHelloPacket, ClientPacket, ServerHello, ServerPacket},
+};
+
+type BoxedRead = Box<dyn AsyncRead + Unpin + Send + Sync>;
+type BoxedWrite = Box<dyn AsyncWrite + Unpin + Send + Sync>;
+
+/// Server side of a Clickhouse native protocol connection, for building proxies or fake Clickhouse servers.
+/// Unlike [`crate::Client`], this is a thin packet-level interface: it is up to the caller to respond to each [`ClientPacket`].
+pub struct ServerConnection {
+    input: InternalServerIn<BufReader<BoxedRead>>,
+    output: InternalServerOut<BufWriter<BoxedWrite>>,
+    client_hello: ClientHelloPacket,
+}
+
+impl ServerConnection {
+    /// Performs the server side of the handshake over a reader and writer. To be used for exotic setups or TLS. Generally prefer [`ServerConnection::accept()`]
+    /// `hello` is sent to the client as-is, the negotiated revision is the lower of `hello.revision_version` and the client's protocol version.
+    pub async fn accept_stream(
+        read: impl AsyncRead + Unpin + Send + Sync + 'static,
+        writer: impl AsyncWrite + Unpin + Send + Sync + 'static,
+        hello: ServerHello,
+    ) -> Result<Self> {
+        let mut input = InternalServerIn::new(BufReader::new(Box::new(read) as BoxedRead));
+        let mut output = InternalServerOut::new(BufWriter::new(Box::new(writer) as BoxedWrite));
+
+        let mut client_hello = input.receive_hello().await?;
+        let revision = client_hello.protocol_version.min(hello.revision_version);
+        output.send_packet(&ServerPacket::Hello(hello)).await?;
+        input.revision = revision;
+        output.revision = revision;
+        client_hello.quota_key = input.receive_addendum().await?;
+
+        Ok(ServerConnection {
+            input,
+            output,
+            client_hello,
+        })
+    }
+
+    /// Performs the server side of the handshake over an accepted plaintext TCP connection.
+    pub async fn accept(stream: TcpStream, hello: ServerHello) -> Result<Self> {
+        let (read, writer) = stream.into_split();
+        Self::accept_stream(read, writer, hello).await
+    }
+
+    /// Hello packet sent by the client during the handshake.
+    pub fn client_hello(&self) -> &ClientHelloPacket {
+        &self.client_hello
+    }
+
+    /// Negotiated protocol revision for this connection.
+    pub fn revision(&self) -> u64 {
+        self.input.revision
+    }
+
+    /// Reads the next packet from the client.
+    /// Data blocks following a query are decompressed if the query requested compression, and later data sent back is compressed in kind.
+    pub async fn receive_packet(&mut self) -> Result<ClientPacket> {
+        let packet = self.input.receive_packet().await?;
+        if let ClientPacket::Query(query) = &packet {
+            self.output.compression = query.compression;
+        }
+        Ok(packet)
+    }
+
+    /// Writes a packet to the client.
+    pub async fn send_packet(&mut self, packet: &ServerPacket) -> Result<()> {
+        self.output.send_packet(packet).await
+    }
+}
@@ -0,0 +1,237 @@
+//! In-process mock of a Clickhouse server speaking the native protocol, for testing code built on [`Client`] without a real Clickhouse instance.
+
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::*;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+
+use crate::{
+    block::Block,
+    progress::Progress,
+    protocol::{
+        self, ClientPacket, CompressionMethod, ServerData, ServerException, ServerHello,
+        ServerPacket, TableColumns,
+    },
+    Client, ClientOptions, ServerConnection,
+};
+
+/// A scripted response sent by [`MockServer`] after receiving a query.
+#[derive(Debug, Clone)]
+pub enum MockResponse {
+    /// Sends a data block. For inserts, the first block sent is the header block describing the columns to send.
+    Data(Block),
+    /// Sends a progress report.
+    Progress(Progress),
+    /// Sends table metadata. For inserts, this is sent before the header block.
+    TableColumns(TableColumns),
+    /// Sends a server exception, usually terminating the response.
+    Exception {
+        code: i32,
+        name: String,
+        message: String,
+    },
+    /// Reads data blocks from the client until an empty block is received. They are recorded in [`ReceivedQuery::blocks`].
+    ReceiveData,
+    /// Ends the response to the current query.
+    EndOfStream,
+}
+
+/// A query received by [`MockServer`], along with any data blocks sent with it.
+#[derive(Debug, Clone)]
+pub struct ReceivedQuery {
+    pub query: String,
+    pub compression: CompressionMethod,
+    /// Quoted values of the query's `{name:Type}` placeholders.
+    pub parameters: Vec<(String, String)>,
+    pub blocks: Vec<Block>,
+}
+
+type Handler = dyn Fn(&str) -> Vec<MockResponse> + Send + Sync;
+
+/// Mock Clickhouse server listening on a random local port. Every query received is passed to a handler, which returns the responses to send back.
+/// The server is shut down when dropped.
+pub struct MockServer {
+    local_addr: SocketAddr,
+    queries: Arc<Mutex<Vec<ReceivedQuery>>>,
+    handle: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts a mock server on `127.0.0.1`, using `handler` to script responses to each query.
+    pub async fn start(
+        handler: impl Fn(&str) -> Vec<MockResponse> + Send + Sync + 'static,
+    ) -> Result<Self> {
+        Self::start_with_revision(protocol::DBMS_TCP_PROTOCOL_VERSION, handler).await
+    }
+
+    /// Like [`MockServer::start`], but announces protocol `revision` to clients, i.e. to mock older servers.
+    pub async fn start_with_revision(
+        revision: u64,
+        handler: impl Fn(&str) -> Vec<MockResponse> + Send + Sync + 'static,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_addr = listener.local_addr()?;
+        let queries = Arc::new(Mutex::new(vec![]));
+        let handler: Arc<Handler> = Arc::new(handler);
+        let handle = tokio::spawn({
+            let queries = queries.clone();
+            async move {
+                loop {
+                    let stream = match listener.accept().await {
+                        Ok((stream, _)) => stream,
+                        Err(e) => {
+                            log::error!("mock server failed to accept connection: {:?}", e);
+                            return;
+                        }
+                    };
+                    let connection = MockConnection {
+                        handler: handler.clone(),
+                        queries: queries.clone(),
+                        revision,
+                    };
+                    tokio::spawn(async move {
+                        if let Err(e) = connection.run(stream).await {
+                            log::error!("mock server connection failed: {:?}", e);
+                        }
+                    });
+                }
+            }
+        });
+        Ok(MockServer {
+            local_addr,
+            queries,
+            handle,
+        })
+    }
+
+    /// Address the mock server is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Connects a new [`Client`] to this mock server with default options.
+    pub async fn connect(&self) -> Result<Client> {
+        Ok(Client::connect(self.local_addr, ClientOptions::default()).await?)
+    }
+
+    /// All queries received so far, across all connections, in order of arrival.
+    pub fn queries(&self) -> Vec<ReceivedQuery> {
+        self.queries.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+fn is_empty_block(block: &Block) -> bool {
+    block.rows == 0 && block.column_types.is_empty()
+}
+
+struct MockConnection {
+    handler: Arc<Handler>,
+    queries: Arc<Mutex<Vec<ReceivedQuery>>>,
+    revision: u64,
+}
+
+impl MockConnection {
+    async fn run(self, stream: TcpStream) -> Result<()> {
+        let mut connection = ServerConnection::accept(
+            stream,
+            ServerHello {
+                server_name: "ClickHouse".to_string(),
+                major_version: crate::VERSION_MAJOR,
+                minor_version: crate::VERSION_MINOR,
+                revision_version: self.revision,
+                timezone: Some("UTC".to_string()),
+                display_name: Some("klickhouse-mock".to_string()),
+                patch_version: 0,
+            },
+        )
+        .await?;
+
+        loop {
+            let packet = match connection.receive_packet().await {
+                Ok(packet) => packet,
+                Err(e) => match e.downcast_ref::<std::io::Error>() {
+                    // client disconnected
+                    Some(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+                    _ => return Err(e),
+                },
+            };
+            match packet {
+                ClientPacket::Query(query) => {
+                    // external tables, terminated by an empty block
+                    receive_blocks(&mut connection).await?;
+                    let index = {
+                        let mut queries = self.queries.lock().unwrap();
+                        queries.push(ReceivedQuery {
+                            query: query.query.clone(),
+                            compression: query.compression,
+                            parameters: query.parameters.clone(),
+                            blocks: vec![],
+                        });
+                        queries.len() - 1
+                    };
+                    for response in (self.handler)(&query.query) {
+                        let packet = match response {
+                            MockResponse::Data(block) => ServerPacket::Data(ServerData {
+                                table_name: String::new(),
+                                block,
+                            }),
+                            MockResponse::Progress(progress) => ServerPacket::Progress(progress),
+                            MockResponse::TableColumns(columns) => {
+                                ServerPacket::TableColumns(columns)
+                            }
+                            MockResponse::Exception {
+                                code,
+                                name,
+                                message,
+                            } => ServerPacket::Exception(ServerException {
+                                code,
+                                name,
+                                message,
+                                stack_trace: String::new(),
+                                has_nested: false,
+                            }),
+                            MockResponse::ReceiveData => {
+                                let blocks = receive_blocks(&mut connection).await?;
+                                self.queries.lock().unwrap()[index].blocks.extend(blocks);
+                                continue;
+                            }
+                            MockResponse::EndOfStream => ServerPacket::EndOfStream,
+                        };
+                        connection.send_packet(&packet).await?;
+                    }
+                }
+                ClientPacket::Ping => connection.send_packet(&ServerPacket::Pong).await?,
+                ClientPacket::Cancel => (),
+                packet => return Err(anyhow!("unexpected packet from client: {:?}", packet)),
+            }
+        }
+    }
+}
+
+async fn receive_blocks(connection: &mut ServerConnection) -> Result<Vec<Block>> {
+    let mut blocks = vec![];
+    loop {
+        match connection.receive_packet().await? {
+            ClientPacket::Data(data) if is_empty_block(&data.block) => return Ok(blocks),
+            ClientPacket::Data(data) => blocks.push(data.block),
+            packet => {
+                return Err(anyhow!(
+                    "unexpected packet {:?}, expected client data",
+                    packet
+                ))
+            }
+        }
+    }
+}
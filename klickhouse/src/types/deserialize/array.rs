@@ -4,6 +4,7 @@ use tokio::io::AsyncReadExt;
 use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct ArrayDeserializer;
 
@@ -35,7 +36,7 @@ impl Array2Deserializer {
 
                 Value::Array(out)
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
@@ -51,7 +52,7 @@ impl Deserializer for ArrayDeserializer {
             Type::Array(inner) => {
                 inner.deserialize_prefix(reader, state).await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -65,14 +66,14 @@ impl Deserializer for ArrayDeserializer {
             Type::Array(inner) => {
                 let len = reader.read_u64_le().await?;
                 if let Type::Array(_) = &**inner {
-                    return Array2Deserializer::read(&**inner, reader, len, state).await;
+                    return Array2Deserializer::read(inner, reader, len, state).await;
                 }
                 let items = inner
                     .deserialize_column(reader, len as usize, state)
                     .await?;
                 Value::Array(items)
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
@@ -5,10 +5,12 @@ use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
 
+use crate::convert::unexpected_type;
 use crate::types::low_cardinality::*;
 
 pub struct LowCardinalityDeserializer;
 
+#[allow(clippy::diverging_sub_expression)]
 #[async_trait::async_trait]
 impl Deserializer for LowCardinalityDeserializer {
     async fn read_prefix<R: ClickhouseRead>(
@@ -103,7 +105,7 @@ impl Deserializer for LowCardinalityDeserializer {
                             .as_ref()
                             .ok_or_else(|| anyhow!("missing additional keys"))?;
                         for entry in entries {
-                            let entry = entry.index_value();
+                            let entry = entry.index_value()?;
                             let value = if is_nullable && entry == 0 {
                                 Value::Null
                             } else {
@@ -118,17 +120,10 @@ impl Deserializer for LowCardinalityDeserializer {
                             .as_ref()
                             .ok_or_else(|| anyhow!("missing global dictionary"))?;
                         for entry in entries {
-                            output.push(
-                                global_dictionary
-                                    .get(entry.index_value())
-                                    .cloned()
-                                    .ok_or_else(|| {
-                                        anyhow!(
-                                            "illegal index {} in global_dictionary",
-                                            entry.index_value()
-                                        )
-                                    })?,
-                            );
+                            let entry = entry.index_value()?;
+                            output.push(global_dictionary.get(entry).cloned().ok_or_else(
+                                || anyhow!("illegal index {} in global_dictionary", entry),
+                            )?);
                         }
                     } else if needs_global_dictionary && has_additional_keys {
                         let additional_keys = additional_keys
@@ -138,7 +133,7 @@ impl Deserializer for LowCardinalityDeserializer {
                             .as_ref()
                             .ok_or_else(|| anyhow!("missing global dictionary"))?;
                         for entry in entries {
-                            let entry = entry.index_value();
+                            let entry = entry.index_value()?;
                             let value = if is_nullable && entry == 0 {
                                 Value::Null
                             } else if entry < additional_keys.len() {
@@ -160,7 +155,7 @@ impl Deserializer for LowCardinalityDeserializer {
 
                 output
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 
@@ -169,6 +164,8 @@ impl Deserializer for LowCardinalityDeserializer {
         _reader: &mut R,
         _state: &mut DeserializerState,
     ) -> Result<Value> {
-        unimplemented!()
+        Err(anyhow!(
+            "LowCardinality values can only be read as part of a column"
+        ))
     }
 }
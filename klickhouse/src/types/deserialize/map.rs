@@ -4,6 +4,7 @@ use tokio::io::AsyncReadExt;
 use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct MapDeserializer;
 
@@ -17,12 +18,12 @@ impl Deserializer for MapDeserializer {
         match type_ {
             Type::Map(key, value) => {
                 let nested = Type::Array(Box::new(Type::Tuple(vec![
-                    (&**key).clone(),
-                    (&**value).clone(),
+                    (**key).clone(),
+                    (**value).clone(),
                 ])));
                 nested.deserialize_prefix(reader, state).await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -42,7 +43,7 @@ impl Deserializer for MapDeserializer {
 
                 Value::Map(keys, values)
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
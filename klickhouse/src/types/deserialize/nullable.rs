@@ -4,6 +4,7 @@ use tokio::io::AsyncReadExt;
 use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct NullableDeserializer;
 
@@ -18,7 +19,7 @@ impl Deserializer for NullableDeserializer {
             Type::Nullable(inner) => {
                 inner.deserialize_prefix(reader, state).await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -39,7 +40,7 @@ impl Deserializer for NullableDeserializer {
                     Value::Null
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 
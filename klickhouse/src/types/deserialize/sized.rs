@@ -8,6 +8,7 @@ use uuid::Uuid;
 use crate::{i256, io::ClickhouseRead, u256, values::Value, Date, DateTime};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct SizedDeserializer;
 
@@ -71,7 +72,7 @@ impl Deserializer for SizedDeserializer {
             }
             Type::Enum8(_) => Value::Enum8(reader.read_u8().await?),
             Type::Enum16(_) => Value::Enum16(reader.read_u16_le().await?),
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
@@ -4,6 +4,7 @@ use tokio::io::AsyncReadExt;
 use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct StringDeserializer;
 
@@ -18,14 +19,13 @@ impl Deserializer for StringDeserializer {
         Ok(match type_ {
             Type::String => Value::String(reader.read_string().await?),
             Type::FixedString(n) => {
-                let mut buf = Vec::with_capacity(*n);
-                unsafe { buf.set_len(*n) };
+                let mut buf = vec![0u8; *n];
                 reader.read_exact(&mut buf[..]).await?;
                 let first_null = buf.iter().position(|x| *x == 0).unwrap_or(buf.len());
                 buf.truncate(first_null);
                 Value::String(String::from_utf8(buf)?)
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
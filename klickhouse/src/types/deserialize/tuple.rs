@@ -3,6 +3,7 @@ use anyhow::*;
 use crate::{io::ClickhouseRead, values::Value};
 
 use super::{Deserializer, DeserializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct TupleDeserializer;
 
@@ -19,7 +20,7 @@ impl Deserializer for TupleDeserializer {
                     item.deserialize_prefix(reader, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -37,7 +38,7 @@ impl Deserializer for TupleDeserializer {
                 }
                 Value::Tuple(items)
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         })
     }
 }
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use anyhow::*;
 use chrono_tz::Tz;
@@ -6,6 +6,8 @@ use uuid::Uuid;
 
 mod deserialize;
 mod low_cardinality;
+#[cfg(test)]
+mod proptests;
 mod serialize;
 #[cfg(test)]
 mod tests;
@@ -13,8 +15,9 @@ mod tests;
 use crate::{
     i256,
     io::{ClickhouseRead, ClickhouseWrite},
+    protocol::unescape,
     u256,
-    values::Value,
+    values::{write_quoted_str, Value},
     Date, DateTime, Ipv4, Ipv6,
 };
 
@@ -74,30 +77,30 @@ pub enum Type {
 }
 
 impl Type {
-    pub fn unwrap_array(&self) -> &Type {
+    pub fn unwrap_array(&self) -> Result<&Type> {
         match self {
-            Type::Array(x) => &**x,
-            _ => unimplemented!(),
+            Type::Array(x) => Ok(x),
+            _ => Err(anyhow!("expected array type, got {}", self)),
         }
     }
 
-    pub fn unwrap_map(&self) -> (&Type, &Type) {
+    pub fn unwrap_map(&self) -> Result<(&Type, &Type)> {
         match self {
-            Type::Map(key, value) => (&**key, &**value),
-            _ => unimplemented!(),
+            Type::Map(key, value) => Ok((&**key, &**value)),
+            _ => Err(anyhow!("expected map type, got {}", self)),
         }
     }
 
-    pub fn unwrap_tuple(&self) -> &[Type] {
+    pub fn unwrap_tuple(&self) -> Result<&[Type]> {
         match self {
-            Type::Tuple(x) => &x[..],
-            _ => unimplemented!(),
+            Type::Tuple(x) => Ok(&x[..]),
+            _ => Err(anyhow!("expected tuple type, got {}", self)),
         }
     }
 
     pub fn strip_null(&self) -> &Type {
         match self {
-            Type::Nullable(x) => &**x,
+            Type::Nullable(x) => x,
             _ => self,
         }
     }
@@ -147,7 +150,7 @@ impl Type {
 
     pub fn strip_low_cardinality(&self) -> &Type {
         match self {
-            Type::LowCardinality(x) => &**x,
+            Type::LowCardinality(x) => x,
             _ => self,
         }
     }
@@ -165,6 +168,7 @@ fn eat_identifier(input: &str) -> (&str, &str) {
     (input, "")
 }
 
+#[allow(clippy::collapsible_match)]
 fn parse_args(input: &str) -> Result<Vec<&str>> {
     if !input.starts_with('(') || !input.ends_with(')') {
         return Err(anyhow!("malformed arguments to type"));
@@ -173,9 +177,24 @@ fn parse_args(input: &str) -> Result<Vec<&str>> {
     let mut out = vec![];
     let mut in_parens = 0usize;
     let mut last_start = 0;
-    // todo: handle parens in enum strings?
-    for (i, c) in input.char_indices() {
+    // commas and parens in quoted enum names are skipped
+    let mut in_quotes = false;
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if in_quotes {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' => in_quotes = false,
+                _ => (),
+            }
+            continue;
+        }
         match c {
+            '\'' => {
+                in_quotes = true;
+            }
             ',' => {
                 if in_parens == 0 {
                     out.push(input[last_start..i].trim());
@@ -194,12 +213,33 @@ fn parse_args(input: &str) -> Result<Vec<&str>> {
     if in_parens != 0 {
         return Err(anyhow!("mismatched parenthesis"));
     }
+    if in_quotes {
+        return Err(anyhow!("unterminated quoted string"));
+    }
     if last_start != input.len() {
         out.push(input[last_start..input.len()].trim());
     }
     Ok(out)
 }
 
+/// Parses the `'name' = value` arguments of an `Enum8` or `Enum16` type.
+fn parse_enum_entries<T: FromStr>(args: &[&str]) -> Result<Vec<(String, T)>> {
+    args.iter()
+        .map(|arg| {
+            let quoted = arg
+                .strip_prefix('\'')
+                .ok_or_else(|| anyhow!("malformed enum entry: {}", arg))?;
+            let (name, rest) = unescape(quoted, Some('\''))?;
+            let value = rest
+                .trim()
+                .strip_prefix('=')
+                .and_then(|x| x.trim().parse().ok())
+                .ok_or_else(|| anyhow!("malformed enum entry: {}", arg))?;
+            Ok((name, value))
+        })
+        .collect()
+}
+
 impl FromStr for Type {
     type Err = anyhow::Error;
 
@@ -290,12 +330,18 @@ impl FromStr for Type {
                         return Err(anyhow!("bad arg count for DateTime64"));
                     }
                 }
-                "Enum8" => {
-                    todo!()
-                }
-                "Enum16" => {
-                    todo!()
-                }
+                "Enum8" => Type::Enum8(
+                    parse_enum_entries::<i8>(&args)?
+                        .into_iter()
+                        .map(|(name, value)| (name, value as u8))
+                        .collect(),
+                ),
+                "Enum16" => Type::Enum16(
+                    parse_enum_entries::<i16>(&args)?
+                        .into_iter()
+                        .map(|(name, value)| (name, value as u16))
+                        .collect(),
+                ),
                 "LowCardinality" => {
                     if args.len() != 1 {
                         return Err(anyhow!("bad arg count for LowCardinality"));
@@ -309,7 +355,7 @@ impl FromStr for Type {
                     Type::Array(Box::new(Type::from_str(args[0])?))
                 }
                 "Nested" => {
-                    todo!()
+                    return Err(anyhow!("unsupported type: Nested"));
                 }
                 "Tuple" => {
                     let mut inner = vec![];
@@ -362,9 +408,15 @@ impl FromStr for Type {
     }
 }
 
-impl ToString for Type {
-    fn to_string(&self) -> String {
-        match self {
+fn enum_entry(name: &str, value: impl fmt::Display) -> String {
+    let mut out = String::new();
+    write_quoted_str(name, &mut out);
+    format!("{} = {}", out, value)
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let out = match self {
             Type::Int8 => "Int8".to_string(),
             Type::Int16 => "Int16".to_string(),
             Type::Int32 => "Int32".to_string(),
@@ -395,7 +447,7 @@ impl ToString for Type {
                 "Enum8({})",
                 items
                     .iter()
-                    .map(|(name, value)| format!("{}={}", name, value))
+                    .map(|(name, value)| enum_entry(name, *value as i8))
                     .collect::<Vec<_>>()
                     .join(",")
             ),
@@ -403,12 +455,12 @@ impl ToString for Type {
                 "Enum16({})",
                 items
                     .iter()
-                    .map(|(name, value)| format!("{}={}", name, value))
+                    .map(|(name, value)| enum_entry(name, *value as i16))
                     .collect::<Vec<_>>()
                     .join(",")
             ),
-            Type::LowCardinality(inner) => format!("LowCardinality({})", inner.to_string()),
-            Type::Array(inner) => format!("Array({})", inner.to_string()),
+            Type::LowCardinality(inner) => format!("LowCardinality({})", inner),
+            Type::Array(inner) => format!("Array({})", inner),
             // Type::Nested(items) => format!("Nested({})", items.iter().map(|(key, value)| format!("{} {}", key, value.to_string())).collect::<Vec<_>>().join(",")),
             Type::Tuple(items) => format!(
                 "Tuple({})",
@@ -418,9 +470,10 @@ impl ToString for Type {
                     .collect::<Vec<_>>()
                     .join(",")
             ),
-            Type::Nullable(inner) => format!("Nullable({})", inner.to_string()),
-            Type::Map(key, value) => format!("Map({},{})", key.to_string(), value.to_string()),
-        }
+            Type::Nullable(inner) => format!("Nullable({})", inner),
+            Type::Map(key, value) => format!("Map({},{})", key, value),
+        };
+        f.write_str(&out)
     }
 }
 
@@ -737,6 +790,7 @@ impl Type {
         Ok(())
     }
 
+    #[allow(clippy::collapsible_match)]
     pub(crate) fn validate(&self, dimensions: usize) -> Result<()> {
         match self {
             Type::Decimal32(precision) => {
@@ -889,6 +943,66 @@ impl Type {
         Ok(())
     }
 
+    /// Losslessly converts `value` to this type where they differ, i.e. an `UInt32` value into an `UInt64` column.
+    /// Values which cannot be converted are returned unchanged, and left for [`Type::validate_value`] to reject.
+    pub(crate) fn coerce_value(&self, value: Value) -> Value {
+        match (self, value) {
+            (Type::LowCardinality(inner), value) => inner.coerce_value(value),
+            (Type::Nullable(_), Value::Null) => Value::Null,
+            (Type::Nullable(inner), value) => inner.coerce_value(value),
+            (Type::Array(inner), Value::Array(values)) => {
+                Value::Array(values.into_iter().map(|x| inner.coerce_value(x)).collect())
+            }
+            (Type::Tuple(types), Value::Tuple(values)) if types.len() == values.len() => {
+                Value::Tuple(
+                    types
+                        .iter()
+                        .zip(values)
+                        .map(|(type_, value)| type_.coerce_value(value))
+                        .collect(),
+                )
+            }
+            (Type::Map(key, value), Value::Map(keys, values)) => Value::Map(
+                keys.into_iter().map(|x| key.coerce_value(x)).collect(),
+                values.into_iter().map(|x| value.coerce_value(x)).collect(),
+            ),
+            (Type::Float64, Value::Float32(x)) => {
+                Value::Float64((f32::from_bits(x) as f64).to_bits())
+            }
+            (type_, value) => match (type_, IntValue::from_value(&value)) {
+                (Type::Int16, Some(x)) if x.fits_signed(16) => Value::Int16(x.value() as i16),
+                (Type::Int32, Some(x)) if x.fits_signed(32) => Value::Int32(x.value() as i32),
+                (Type::Int64, Some(x)) if x.fits_signed(64) => Value::Int64(x.value() as i64),
+                (Type::Int128, Some(x)) if x.fits_signed(128) => Value::Int128(x.value()),
+                (Type::Int256, Some(IntValue::Unsigned(x, _))) => Value::Int256((0, x).into()),
+                (Type::Int256, Some(IntValue::Signed(x, _))) => {
+                    let high = if x < 0 { u128::MAX } else { 0 };
+                    Value::Int256((high, x as u128).into())
+                }
+                (Type::UInt16, Some(IntValue::Unsigned(x, bits))) if bits < 16 => {
+                    Value::UInt16(x as u16)
+                }
+                (Type::UInt32, Some(IntValue::Unsigned(x, bits))) if bits < 32 => {
+                    Value::UInt32(x as u32)
+                }
+                (Type::UInt64, Some(IntValue::Unsigned(x, bits))) if bits < 64 => {
+                    Value::UInt64(x as u64)
+                }
+                (Type::UInt128, Some(IntValue::Unsigned(x, bits))) if bits < 128 => {
+                    Value::UInt128(x)
+                }
+                (Type::UInt256, Some(IntValue::Unsigned(x, _))) => Value::UInt256((0, x).into()),
+                (Type::Float32, Some(x)) if x.bits() <= 16 => {
+                    Value::Float32((x.value() as f32).to_bits())
+                }
+                (Type::Float64, Some(x)) if x.bits() <= 32 => {
+                    Value::Float64((x.value() as f64).to_bits())
+                }
+                _ => value,
+            },
+        }
+    }
+
     fn inner_validate_value(&self, value: &Value) -> bool {
         match (self, value) {
             (Type::Int8, Value::Int8(_))
@@ -932,15 +1046,19 @@ impl Type {
             (Type::Array(inner_type), Value::Array(values)) => {
                 values.iter().all(|x| inner_type.inner_validate_value(x))
             }
-            (Type::Tuple(inner_types), Value::Tuple(values)) => inner_types
-                .iter()
-                .zip(values.iter())
-                .all(|(type_, value)| type_.inner_validate_value(value)),
+            (Type::Tuple(inner_types), Value::Tuple(values)) => {
+                inner_types.len() == values.len()
+                    && inner_types
+                        .iter()
+                        .zip(values.iter())
+                        .all(|(type_, value)| type_.inner_validate_value(value))
+            }
             (Type::Nullable(inner), value) => {
                 value == &Value::Null || inner.inner_validate_value(value)
             }
             (Type::Map(key, value), Value::Map(keys, values)) => {
-                keys.iter().all(|x| key.inner_validate_value(x))
+                keys.len() == values.len()
+                    && keys.iter().all(|x| key.inner_validate_value(x))
                     && values.iter().all(|x| value.inner_validate_value(x))
             }
             (_, _) => false,
@@ -948,6 +1066,52 @@ impl Type {
     }
 }
 
+/// Integer value of at most 128 bits, for widening conversions.
+#[derive(Clone, Copy)]
+pub(crate) enum IntValue {
+    Signed(i128, u32),
+    Unsigned(u128, u32),
+}
+
+impl IntValue {
+    pub(crate) fn from_value(value: &Value) -> Option<Self> {
+        Some(match value {
+            Value::Int8(x) => IntValue::Signed(*x as i128, 8),
+            Value::Int16(x) => IntValue::Signed(*x as i128, 16),
+            Value::Int32(x) => IntValue::Signed(*x as i128, 32),
+            Value::Int64(x) => IntValue::Signed(*x as i128, 64),
+            Value::Int128(x) => IntValue::Signed(*x, 128),
+            Value::UInt8(x) => IntValue::Unsigned(*x as u128, 8),
+            Value::UInt16(x) => IntValue::Unsigned(*x as u128, 16),
+            Value::UInt32(x) => IntValue::Unsigned(*x as u128, 32),
+            Value::UInt64(x) => IntValue::Unsigned(*x as u128, 64),
+            Value::UInt128(x) => IntValue::Unsigned(*x, 128),
+            _ => return None,
+        })
+    }
+
+    fn bits(&self) -> u32 {
+        match self {
+            IntValue::Signed(_, bits) | IntValue::Unsigned(_, bits) => *bits,
+        }
+    }
+
+    /// Whether every value of the source type fits in a signed integer of `bits`, excluding same-size types.
+    fn fits_signed(&self, bits: u32) -> bool {
+        match self {
+            IntValue::Signed(_, x) | IntValue::Unsigned(_, x) => *x < bits,
+        }
+    }
+
+    /// Value as an `i128`, only meaningful once checked with [`IntValue::fits_signed`] or [`IntValue::bits`].
+    fn value(&self) -> i128 {
+        match self {
+            IntValue::Signed(x, _) => *x,
+            IntValue::Unsigned(x, _) => *x as i128,
+        }
+    }
+}
+
 pub struct DeserializerState {}
 
 pub struct SerializerState {}
@@ -992,6 +1156,7 @@ pub trait Serializer {
         Ok(())
     }
 
+    #[allow(dead_code)]
     async fn write_suffix<W: ClickhouseWrite>(
         _type_: &Type,
         _value: &[Value],
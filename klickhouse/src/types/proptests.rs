@@ -0,0 +1,222 @@
+//! Property tests feeding arbitrary, mostly mismatched, type and value pairs through conversions and (de)serialization.
+//! Mismatches must surface as errors, never as panics.
+
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use chrono_tz::Tz;
+use proptest::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    convert::FromSql,
+    i256,
+    types::{DeserializerState, SerializerState, Type},
+    u256,
+    values::Value,
+    Date, DateTime, FixedPoint128, FixedPoint256, FixedPoint32, FixedPoint64, Ipv4, Ipv6,
+};
+
+fn arb_tz() -> impl Strategy<Value = Tz> {
+    prop_oneof![
+        Just(Tz::UTC),
+        Just(Tz::Europe__London),
+        Just(Tz::Asia__Tokyo)
+    ]
+}
+
+fn arb_type() -> impl Strategy<Value = Type> {
+    let leaf = prop_oneof![
+        Just(Type::Int8),
+        Just(Type::Int16),
+        Just(Type::Int32),
+        Just(Type::Int64),
+        Just(Type::Int128),
+        Just(Type::Int256),
+        Just(Type::UInt8),
+        Just(Type::UInt16),
+        Just(Type::UInt32),
+        Just(Type::UInt64),
+        Just(Type::UInt128),
+        Just(Type::UInt256),
+        Just(Type::Float32),
+        Just(Type::Float64),
+        (0usize..80).prop_map(Type::Decimal32),
+        (0usize..80).prop_map(Type::Decimal64),
+        (0usize..80).prop_map(Type::Decimal128),
+        (0usize..80).prop_map(Type::Decimal256),
+        Just(Type::String),
+        (0usize..64).prop_map(Type::FixedString),
+        Just(Type::Uuid),
+        Just(Type::Date),
+        arb_tz().prop_map(Type::DateTime),
+        (0usize..24, arb_tz()).prop_map(|(precision, tz)| Type::DateTime64(precision, tz)),
+        Just(Type::Ipv4),
+        Just(Type::Ipv6),
+        prop::collection::vec(("[a-z]{1,4}", any::<u8>()), 0..3).prop_map(Type::Enum8),
+        prop::collection::vec(("[a-z]{1,4}", any::<u16>()), 0..3).prop_map(Type::Enum16),
+    ];
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            inner
+                .clone()
+                .prop_map(|x| Type::LowCardinality(Box::new(x))),
+            inner.clone().prop_map(|x| Type::Array(Box::new(x))),
+            inner.clone().prop_map(|x| Type::Nullable(Box::new(x))),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(Type::Tuple),
+            (inner.clone(), inner).prop_map(|(k, v)| Type::Map(Box::new(k), Box::new(v))),
+        ]
+    })
+}
+
+fn arb_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        any::<i8>().prop_map(Value::Int8),
+        any::<i16>().prop_map(Value::Int16),
+        any::<i32>().prop_map(Value::Int32),
+        any::<i64>().prop_map(Value::Int64),
+        any::<i128>().prop_map(Value::Int128),
+        any::<[u8; 32]>().prop_map(|x| Value::Int256(i256(x))),
+        any::<u8>().prop_map(Value::UInt8),
+        any::<u16>().prop_map(Value::UInt16),
+        any::<u32>().prop_map(Value::UInt32),
+        any::<u64>().prop_map(Value::UInt64),
+        any::<u128>().prop_map(Value::UInt128),
+        any::<[u8; 32]>().prop_map(|x| Value::UInt256(u256(x))),
+        any::<u32>().prop_map(Value::Float32),
+        any::<u64>().prop_map(Value::Float64),
+        (0usize..80, any::<i32>()).prop_map(|(s, x)| Value::Decimal32(s, x)),
+        (0usize..80, any::<i64>()).prop_map(|(s, x)| Value::Decimal64(s, x)),
+        (0usize..80, any::<i128>()).prop_map(|(s, x)| Value::Decimal128(s, x)),
+        (0usize..80, any::<[u8; 32]>()).prop_map(|(s, x)| Value::Decimal256(s, i256(x))),
+        ".{0,12}".prop_map(Value::String),
+        any::<u128>().prop_map(|x| Value::Uuid(Uuid::from_u128(x))),
+        any::<u16>().prop_map(|x| Value::Date(Date(x))),
+        (arb_tz(), any::<u32>()).prop_map(|(tz, x)| Value::DateTime(DateTime(tz, x))),
+        (arb_tz(), 0usize..24, any::<u64>()).prop_map(|(tz, p, x)| Value::DateTime64(tz, p, x)),
+        any::<u8>().prop_map(Value::Enum8),
+        any::<u16>().prop_map(Value::Enum16),
+        Just(Value::Null),
+        any::<u32>().prop_map(|x| Value::Ipv4(Ipv4(Ipv4Addr::from(x)))),
+        any::<u128>().prop_map(|x| Value::Ipv6(Ipv6(Ipv6Addr::from(x)))),
+    ];
+    leaf.prop_recursive(3, 16, 3, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..3).prop_map(Value::Array),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(Value::Tuple),
+            (
+                prop::collection::vec(inner.clone(), 0..3),
+                prop::collection::vec(inner, 0..3)
+            )
+                .prop_map(|(k, v)| Value::Map(k, v)),
+        ]
+    })
+}
+
+/// Converts `value` into a selection of Rust types, discarding the results.
+fn convert_all(type_: &Type, value: &Value) {
+    macro_rules! convert {
+        ($($t:ty),* $(,)?) => {
+            $(
+                let _ = <$t>::from_sql(type_, value.clone());
+                let _ = crate::with_lenient_coercion(|| <$t>::from_sql(type_, value.clone()));
+            )*
+        };
+    }
+    convert!(
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        u8,
+        u16,
+        u32,
+        u64,
+        u128,
+        f32,
+        f64,
+        String,
+        Vec<u8>,
+        Uuid,
+        Date,
+        DateTime,
+        Ipv4,
+        Ipv6,
+        i256,
+        u256,
+        FixedPoint32<3>,
+        FixedPoint64<3>,
+        FixedPoint128<3>,
+        FixedPoint256<3>,
+        Option<i32>,
+        Option<String>,
+        Vec<i32>,
+        Vec<Option<String>>,
+        HashMap<String, u64>,
+        (i32,),
+        (String, Option<u8>),
+        Value,
+    );
+}
+
+async fn serialize(type_: &Type, values: &[Value]) -> anyhow::Result<Vec<u8>> {
+    let mut output = vec![];
+    let mut state = SerializerState {};
+    type_.serialize_prefix(&mut output, &mut state).await?;
+    type_
+        .serialize_column(values, &mut output, &mut state)
+        .await?;
+    Ok(output)
+}
+
+async fn deserialize(type_: &Type, input: Vec<u8>, rows: usize) -> anyhow::Result<Vec<Value>> {
+    let mut input = Cursor::new(input);
+    let mut state = DeserializerState {};
+    type_.deserialize_prefix(&mut input, &mut state).await?;
+    type_.deserialize_column(&mut input, rows, &mut state).await
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+proptest! {
+    #[test]
+    fn mismatched_values_do_not_panic(type_ in arb_type(), value in arb_value()) {
+        convert_all(&type_, &value);
+        let _ = type_.validate_value(&value);
+        let _ = type_.coerce_value(value.clone());
+        let _ = block_on(serialize(&type_, &[value]));
+    }
+
+    #[test]
+    fn valid_values_roundtrip(type_ in arb_type(), value in arb_value()) {
+        let value = type_.coerce_value(value);
+        let values = match type_.validate_value(&value) {
+            Ok(()) => vec![value, type_.default_value()],
+            Err(_) => vec![type_.default_value()],
+        };
+        convert_all(&type_, &values[0]);
+        // some valid values still cannot be written, i.e. LowCardinality nested in other types
+        if type_.validate(0).is_err() {
+            return Ok(());
+        }
+        if let Ok(output) = block_on(serialize(&type_, &values)) {
+            let deserialized = block_on(deserialize(&type_, output, values.len())).unwrap();
+            prop_assert_eq!(deserialized, values);
+        }
+    }
+
+    #[test]
+    fn arbitrary_type_names_do_not_panic(name in ".{0,40}") {
+        let _ = Type::from_str(&name);
+    }
+}
@@ -4,6 +4,7 @@ use tokio::io::AsyncWriteExt;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::{unexpected_type, unexpected_value};
 
 pub struct ArraySerializer;
 
@@ -20,16 +21,16 @@ impl Array2Serializer {
             Type::Array(inner) => {
                 let mut offset = 0;
                 for value in values {
-                    let inner = value.unwrap_array();
+                    let inner = value.unwrap_array()?;
                     offset += inner.len();
                     writer.write_u64_le(offset as u64).await?;
                 }
                 for value in values {
-                    let values = value.unwrap_array();
+                    let values = value.unwrap_array()?;
                     inner.serialize_column(values, writer, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -46,7 +47,7 @@ impl Serializer for ArraySerializer {
             Type::Array(inner) => {
                 inner.serialize_prefix(writer, state).await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -61,13 +62,13 @@ impl Serializer for ArraySerializer {
             (Type::Array(inner_type), Value::Array(inner)) => {
                 writer.write_u64_le(inner.len() as u64).await?;
                 if let Type::Array(_) = &**inner_type {
-                    return Array2Serializer::write(&**inner_type, &inner[..], writer, state).await;
+                    return Array2Serializer::write(inner_type, &inner[..], writer, state).await;
                 }
                 inner_type
                     .serialize_column(&inner[..], writer, state)
                     .await?;
             }
-            _ => unimplemented!(),
+            (_, value) => return Err(unexpected_value(type_, value)),
         }
         Ok(())
     }
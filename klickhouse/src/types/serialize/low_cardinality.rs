@@ -6,6 +6,7 @@ use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
 
+use crate::convert::unexpected_type;
 use crate::types::low_cardinality::*;
 
 pub struct LowCardinalitySerializer;
@@ -29,7 +30,7 @@ impl Serializer for LowCardinalitySerializer {
     ) -> Result<()> {
         let inner_type = match type_ {
             Type::LowCardinality(x) => &**x,
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         };
 
         if values.is_empty() {
@@ -99,6 +100,8 @@ impl Serializer for LowCardinalitySerializer {
         _writer: &mut W,
         _state: &mut SerializerState,
     ) -> Result<()> {
-        unimplemented!()
+        Err(anyhow!(
+            "LowCardinality values can only be written as part of a column"
+        ))
     }
 }
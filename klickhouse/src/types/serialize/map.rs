@@ -4,6 +4,7 @@ use tokio::io::AsyncWriteExt;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::unexpected_type;
 
 pub struct MapSerializer;
 
@@ -17,12 +18,12 @@ impl Serializer for MapSerializer {
         match type_ {
             Type::Map(key, value) => {
                 let nested = Type::Array(Box::new(Type::Tuple(vec![
-                    (&**key).clone(),
-                    (&**value).clone(),
+                    (**key).clone(),
+                    (**value).clone(),
                 ])));
                 nested.serialize_prefix(writer, state).await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -41,7 +42,7 @@ impl Serializer for MapSerializer {
                     .serialize_column(&values[..], writer, state)
                     .await?;
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -4,6 +4,7 @@ use tokio::io::AsyncWriteExt;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::unexpected_type;
 pub struct NullableSerializer;
 
 #[async_trait::async_trait]
@@ -17,7 +18,7 @@ impl Serializer for NullableSerializer {
         let inner_type = if let Type::Nullable(n) = type_ {
             &**n
         } else {
-            unimplemented!()
+            return Err(unexpected_type(type_));
         };
 
         match value {
@@ -4,6 +4,7 @@ use tokio::io::AsyncWriteExt;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::unexpected_value;
 
 pub struct SizedSerializer;
 
@@ -53,7 +54,7 @@ impl Serializer for SizedSerializer {
             Value::Ipv6(x) => writer.write_all(&x.octets()[..]).await?,
             Value::Enum8(x) => writer.write_u8(*x).await?,
             Value::Enum16(x) => writer.write_u16_le(*x).await?,
-            _ => unimplemented!(),
+            value => return Err(unexpected_value(type_, value)),
         }
         Ok(())
     }
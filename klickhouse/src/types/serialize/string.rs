@@ -4,6 +4,7 @@ use tokio::io::AsyncWriteExt;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::unexpected_value;
 
 pub struct StringSerializer;
 
@@ -28,10 +29,10 @@ impl Serializer for StringSerializer {
                         }
                     }
                 } else {
-                    writer.write_string(&**x).await?;
+                    writer.write_string(x).await?;
                 }
             }
-            _ => unimplemented!(),
+            value => return Err(unexpected_value(type_, value)),
         }
         Ok(())
     }
@@ -3,6 +3,7 @@ use anyhow::*;
 use crate::{io::ClickhouseWrite, values::Value};
 
 use super::{Serializer, SerializerState, Type};
+use crate::convert::{unexpected_type, unexpected_value};
 
 pub struct TupleSerializer;
 
@@ -19,7 +20,7 @@ impl Serializer for TupleSerializer {
                     item.serialize_prefix(writer, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_type(type_)),
         }
         Ok(())
     }
@@ -36,7 +37,7 @@ impl Serializer for TupleSerializer {
                     inner_type.serialize(value, writer, state).await?;
                 }
             }
-            _ => unimplemented!(),
+            _ => return Err(unexpected_value(type_, value)),
         }
         Ok(())
     }
@@ -183,12 +183,12 @@ async fn roundtrip_i256() {
 #[tokio::test]
 async fn roundtrip_f32() {
     let values = &[
-        Value::Float32((1.0 as f32).to_bits()),
-        Value::Float32((0.0 as f32).to_bits()),
-        Value::Float32((100.0 as f32).to_bits()),
-        Value::Float32((100000.0 as f32).to_bits()),
-        Value::Float32((1000000.0 as f32).to_bits()),
-        Value::Float32((-1000000.0 as f32).to_bits()),
+        Value::Float32(1.0_f32.to_bits()),
+        Value::Float32(0.0_f32.to_bits()),
+        Value::Float32(100.0_f32.to_bits()),
+        Value::Float32(100000.0_f32.to_bits()),
+        Value::Float32(1000000.0_f32.to_bits()),
+        Value::Float32((-1000000.0_f32).to_bits()),
         Value::Float32(f32::NAN.to_bits()),
         Value::Float32(f32::INFINITY.to_bits()),
         Value::Float32(f32::NEG_INFINITY.to_bits()),
@@ -202,12 +202,12 @@ async fn roundtrip_f32() {
 #[tokio::test]
 async fn roundtrip_f64() {
     let values = &[
-        Value::Float64((1.0 as f64).to_bits()),
-        Value::Float64((0.0 as f64).to_bits()),
-        Value::Float64((100.0 as f64).to_bits()),
-        Value::Float64((100000.0 as f64).to_bits()),
-        Value::Float64((1000000.0 as f64).to_bits()),
-        Value::Float64((-1000000.0 as f64).to_bits()),
+        Value::Float64(1.0_f64.to_bits()),
+        Value::Float64(0.0_f64.to_bits()),
+        Value::Float64(100.0_f64.to_bits()),
+        Value::Float64(100000.0_f64.to_bits()),
+        Value::Float64(1000000.0_f64.to_bits()),
+        Value::Float64((-1000000.0_f64).to_bits()),
         Value::Float64(f64::NAN.to_bits()),
         Value::Float64(f64::INFINITY.to_bits()),
         Value::Float64(f64::NEG_INFINITY.to_bits()),
@@ -821,3 +821,97 @@ async fn roundtrip_array_null() {
         .unwrap()
     );
 }
+
+#[test]
+fn test_coerce_value() {
+    let widened = [
+        (Type::UInt64, Value::UInt32(7), Value::UInt64(7)),
+        (
+            Type::Int64,
+            Value::UInt32(u32::MAX),
+            Value::Int64(u32::MAX as i64),
+        ),
+        (Type::Int32, Value::Int8(-3), Value::Int32(-3)),
+        (
+            Type::Int256,
+            Value::Int8(-1),
+            Value::Int256((u128::MAX, u128::MAX).into()),
+        ),
+        (
+            Type::Int256,
+            Value::UInt128(u128::MAX),
+            Value::Int256((0, u128::MAX).into()),
+        ),
+        (
+            Type::UInt256,
+            Value::UInt8(1),
+            Value::UInt256((0, 1).into()),
+        ),
+        (
+            Type::Float64,
+            Value::Float32(1.5_f32.to_bits()),
+            Value::Float64(1.5_f64.to_bits()),
+        ),
+        (
+            Type::Float64,
+            Value::Int32(-2),
+            Value::Float64((-2.0_f64).to_bits()),
+        ),
+        (
+            Type::Nullable(Box::new(Type::UInt64)),
+            Value::UInt8(1),
+            Value::UInt64(1),
+        ),
+        (
+            Type::Array(Box::new(Type::Int64)),
+            Value::Array(vec![Value::Int16(1), Value::Int16(2)]),
+            Value::Array(vec![Value::Int64(1), Value::Int64(2)]),
+        ),
+    ];
+    for (type_, value, expected) in widened {
+        let coerced = type_.coerce_value(value);
+        assert_eq!(coerced, expected);
+        type_.validate_value(&coerced).unwrap();
+    }
+
+    // lossy conversions are left for validation to reject
+    let rejected = [
+        (Type::UInt32, Value::UInt64(7)),
+        (Type::UInt64, Value::Int32(7)),
+        (Type::Int64, Value::UInt64(7)),
+        (Type::Float32, Value::Int32(7)),
+        (Type::Float32, Value::Float64(1.0_f64.to_bits())),
+    ];
+    for (type_, value) in rejected {
+        let coerced = type_.coerce_value(value.clone());
+        assert_eq!(coerced, value);
+        assert!(type_.validate_value(&coerced).is_err());
+    }
+}
+
+#[test]
+fn test_parse_enum() {
+    let type_: Type = "Enum8('a' = 1, 'b, (c)' = -2, 'it\\'s' = 3)"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        type_,
+        Type::Enum8(vec![
+            ("a".to_string(), 1),
+            ("b, (c)".to_string(), -2i8 as u8),
+            ("it's".to_string(), 3),
+        ])
+    );
+    assert_eq!(type_.to_string().parse::<Type>().unwrap(), type_);
+
+    let type_: Type = "Nullable(Enum16('x' = 1000))".parse().unwrap();
+    assert_eq!(
+        type_,
+        Type::Nullable(Box::new(Type::Enum16(vec![("x".to_string(), 1000)])))
+    );
+    assert_eq!(type_.to_string(), "Nullable(Enum16('x' = 1000))");
+
+    assert!("Enum8('a' = 1000)".parse::<Type>().is_err());
+    assert!("Enum8(a = 1)".parse::<Type>().is_err());
+    assert!("Enum8('a = 1)".parse::<Type>().is_err());
+}
@@ -1,5 +1,5 @@
 use crate::{
-    convert::{unexpected_type, FromSql},
+    convert::{unexpected_type, unexpected_value, FromSql},
     types::Type,
     Uuid,
 };
@@ -15,12 +15,16 @@ impl ToSql for Uuid {
 
 impl FromSql for Uuid {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Uuid) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Uuid(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Uuid)
+    }
 }
@@ -2,7 +2,10 @@ use chrono::{Duration, Utc};
 use chrono_tz::{Tz, UTC};
 
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{
+        is_lenient_coercion, lenient_type, strip_lenient_type, unexpected_type, unexpected_value,
+        FromSql, ToSql,
+    },
     types::Type,
     Value,
 };
@@ -20,14 +23,18 @@ impl ToSql for Date {
 
 impl FromSql for Date {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Date) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Date(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Date)
+    }
 }
 
 impl From<Date> for chrono::Date<Utc> {
@@ -54,12 +61,27 @@ impl ToSql for DateTime {
 
 impl FromSql for DateTime {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::DateTime(_)) {
-            return Err(unexpected_type(type_));
+        match (type_, value) {
+            (Type::DateTime(_), Value::DateTime(x)) => Ok(x),
+            (_, value) if is_lenient_coercion() => match (lenient_type(type_, &value)?, value) {
+                (Type::DateTime(_), Value::DateTime(x)) => Ok(x),
+                (Type::Date, Value::Date(x)) => (x.0 as u32)
+                    .checked_mul(86400)
+                    .map(|x| DateTime(UTC, x))
+                    .ok_or_else(|| anyhow!("date {:?} out of range for DateTime", x)),
+                _ => Err(unexpected_type(type_)),
+            },
+            _ => Err(unexpected_type(type_)),
         }
-        match value {
-            Value::DateTime(x) => Ok(x),
-            _ => unimplemented!(),
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        match type_ {
+            Type::DateTime(_) => true,
+            _ => {
+                is_lenient_coercion()
+                    && matches!(strip_lenient_type(type_), Type::DateTime(_) | Type::Date)
+            }
         }
     }
 }
@@ -110,14 +132,18 @@ impl<const PRECISION: usize> ToSql for DateTime64<PRECISION> {
 
 impl<const PRECISION: usize> FromSql for DateTime64<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::DateTime64(x, _) if *x == PRECISION) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::DateTime64(tz, _, value) => Ok(Self(tz, value)),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::DateTime64(x, _) if *x == PRECISION)
+    }
 }
 
 impl<const PRECISION: usize> Default for DateTime64<PRECISION> {
@@ -1,11 +1,15 @@
+use std::{fmt, str::FromStr};
+
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     i256,
     types::Type,
     Value,
 };
 use anyhow::*;
 
+use super::text::write_decimal;
+
 /// Wrapper type for Clickhouse `FixedPoint32` type.
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Default)]
 pub struct FixedPoint32<const PRECISION: u64>(pub i32);
@@ -32,14 +36,18 @@ impl<const PRECISION: u64> ToSql for FixedPoint32<PRECISION> {
 
 impl<const PRECISION: u64> FromSql for FixedPoint32<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal32(x) if *x == PRECISION as usize) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Decimal32(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Decimal32(x) if *x == PRECISION as usize)
+    }
 }
 
 impl<const PRECISION: u64> From<FixedPoint32<PRECISION>> for f64 {
@@ -60,14 +68,18 @@ impl<const PRECISION: u64> ToSql for FixedPoint64<PRECISION> {
 
 impl<const PRECISION: u64> FromSql for FixedPoint64<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal64(x) if *x == PRECISION as usize) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Decimal64(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Decimal64(x) if *x == PRECISION as usize)
+    }
 }
 
 impl<const PRECISION: u64> FixedPoint64<PRECISION> {
@@ -102,14 +114,18 @@ impl<const PRECISION: u64> ToSql for FixedPoint128<PRECISION> {
 
 impl<const PRECISION: u64> FromSql for FixedPoint128<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal128(x) if *x == PRECISION as usize) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Decimal128(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Decimal128(x) if *x == PRECISION as usize)
+    }
 }
 
 impl<const PRECISION: u64> FixedPoint128<PRECISION> {
@@ -144,12 +160,67 @@ impl<const PRECISION: u64> ToSql for FixedPoint256<PRECISION> {
 
 impl<const PRECISION: u64> FromSql for FixedPoint256<PRECISION> {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Decimal256(x) if *x == PRECISION as usize) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Decimal256(_, x) => Ok(Self(x)),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
+        }
+    }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Decimal256(x) if *x == PRECISION as usize)
+    }
+}
+
+impl<const PRECISION: u64> FixedPoint256<PRECISION> {
+    pub fn modulus(&self) -> i256 {
+        (0..PRECISION).fold(i256::ONE, |x, _| x * i256::from(10u8))
+    }
+
+    pub fn integer(&self) -> i256 {
+        self.0 / self.modulus()
+    }
+
+    pub fn fraction(&self) -> i256 {
+        self.0 % self.modulus()
+    }
+}
+
+/// Formats with exactly `PRECISION` fractional digits, i.e. `-1.500` for `FixedPoint256::<3>(-1500)`.
+impl<const PRECISION: u64> fmt::Display for FixedPoint256<PRECISION> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        write_decimal(self.0.to_string(), PRECISION as usize, &mut out);
+        f.pad(&out)
+    }
+}
+
+/// Parses a decimal number with at most `PRECISION` fractional digits, i.e. `-1.5`.
+impl<const PRECISION: u64> FromStr for FixedPoint256<PRECISION> {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (integer, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if fraction.len() > PRECISION as usize {
+            return Err(anyhow!(
+                "too many fractional digits for Decimal256({}): '{}'",
+                PRECISION,
+                s
+            ));
+        }
+        if fraction.starts_with(|c: char| !c.is_ascii_digit())
+            || !s.bytes().any(|c| c.is_ascii_digit())
+        {
+            return Err(anyhow!("invalid decimal: '{}'", s));
         }
+        let digits = format!(
+            "{}{:0<width$}",
+            integer,
+            fraction,
+            width = PRECISION as usize
+        );
+        Ok(Self(digits.parse()?))
     }
 }
@@ -1,21 +1,343 @@
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{unexpected_type, unexpected_value, FromSql, ToSql},
     types::Type,
     Value,
 };
 use anyhow::*;
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    ops::{Add, Div, Mul, Neg, Rem, Shl, Shr, Sub},
+    str::FromStr,
+};
+
+/// 64 bit words of a 256 bit integer, least significant first.
+type Limbs = [u64; 4];
+
+fn to_limbs(bytes: &[u8; 32]) -> Limbs {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&bytes[(3 - i) * 8..(4 - i) * 8]);
+        *limb = u64::from_be_bytes(buf);
+    }
+    limbs
+}
+
+fn from_limbs(limbs: Limbs) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    for (i, limb) in limbs.iter().enumerate() {
+        bytes[(3 - i) * 8..(4 - i) * 8].copy_from_slice(&limb.to_be_bytes());
+    }
+    bytes
+}
+
+fn add_limbs(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0u64; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (sum, c1) = a[i].overflowing_add(b[i]);
+        let (sum, c2) = sum.overflowing_add(carry as u64);
+        out[i] = sum;
+        carry = c1 || c2;
+    }
+    (out, carry)
+}
+
+fn sub_limbs(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0u64; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow as u64);
+        out[i] = diff;
+        borrow = b1 || b2;
+    }
+    (out, borrow)
+}
+
+/// Returns the low 256 bits of the product, and whether any higher bits were set.
+fn mul_limbs(a: Limbs, b: Limbs) -> (Limbs, bool) {
+    let mut out = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let current = a[i] as u128 * b[j] as u128 + out[i + j] as u128 + carry;
+            out[i + j] = current as u64;
+            carry = current >> 64;
+        }
+        out[i + 4] = carry as u64;
+    }
+    let overflow = out[4..].iter().any(|x| *x != 0);
+    ([out[0], out[1], out[2], out[3]], overflow)
+}
+
+fn shl_limbs(a: Limbs, shift: u32) -> Limbs {
+    let (words, bits) = ((shift / 64) as usize, shift % 64);
+    let mut out = [0u64; 4];
+    for i in words..4 {
+        out[i] = a[i - words] << bits;
+        if bits > 0 && i > words {
+            out[i] |= a[i - words - 1] >> (64 - bits);
+        }
+    }
+    out
+}
+
+/// Shifts right, filling with ones rather than zeros if `fill` is set.
+fn shr_limbs(a: Limbs, shift: u32, fill: bool) -> Limbs {
+    let (words, bits) = ((shift / 64) as usize, shift % 64);
+    let extension = if fill { u64::MAX } else { 0 };
+    let mut out = [extension; 4];
+    for i in 0..4 - words {
+        out[i] = a[i + words] >> bits;
+        if bits > 0 {
+            let next = a.get(i + words + 1).copied().unwrap_or(extension);
+            out[i] |= next << (64 - bits);
+        }
+    }
+    out
+}
+
+fn bits(a: &Limbs) -> u32 {
+    for i in (0..4).rev() {
+        if a[i] != 0 {
+            return i as u32 * 64 + 64 - a[i].leading_zeros();
+        }
+    }
+    0
+}
+
+/// Long division, bit by bit. `b` must not be zero.
+fn div_rem_limbs(a: Limbs, b: Limbs) -> (Limbs, Limbs) {
+    let mut quotient = [0u64; 4];
+    let mut remainder = [0u64; 4];
+    for bit in (0..bits(&a)).rev() {
+        remainder = shl_limbs(remainder, 1);
+        remainder[0] |= (a[bit as usize / 64] >> (bit % 64)) & 1;
+        let (diff, borrow) = sub_limbs(remainder, b);
+        if !borrow {
+            remainder = diff;
+            quotient[bit as usize / 64] |= 1 << (bit % 64);
+        }
+    }
+    (quotient, remainder)
+}
+
+fn div_rem_small(a: Limbs, b: u64) -> (Limbs, u64) {
+    let mut out = [0u64; 4];
+    let mut remainder = 0u128;
+    for i in (0..4).rev() {
+        let current = (remainder << 64) | a[i] as u128;
+        out[i] = (current / b as u128) as u64;
+        remainder = current % b as u128;
+    }
+    (out, remainder as u64)
+}
+
+/// Formats a 256 bit unsigned integer in decimal, with `f`'s padding and sign flags.
+fn fmt_limbs(mut limbs: Limbs, nonnegative: bool, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let mut digits = vec![];
+    while limbs.iter().any(|x| *x != 0) {
+        let (quotient, remainder) = div_rem_small(limbs, 10);
+        digits.push(b'0' + remainder as u8);
+        limbs = quotient;
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    f.pad_integral(nonnegative, "", std::str::from_utf8(&digits).unwrap())
+}
+
+/// Parses unsigned decimal digits.
+fn parse_limbs(digits: &str) -> Result<Limbs> {
+    if digits.is_empty() {
+        return Err(anyhow!("cannot parse integer from empty string"));
+    }
+    let mut limbs = [0u64; 4];
+    for digit in digits.bytes() {
+        if !digit.is_ascii_digit() {
+            return Err(anyhow!("invalid digit in integer: '{}'", digits));
+        }
+        let (product, overflow) = mul_limbs(limbs, [10, 0, 0, 0]);
+        let (sum, carry) = add_limbs(product, [(digit - b'0') as u64, 0, 0, 0]);
+        if overflow || carry {
+            return Err(anyhow!("integer too large for 256 bits: '{}'", digits));
+        }
+        limbs = sum;
+    }
+    Ok(limbs)
+}
 
 /// Wrapper type for Clickhouse `Int256` type.
-#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Default)]
+/// Stored as big endian two's complement.
+#[derive(Clone, Copy, Eq, Hash, PartialEq, Debug, Default)]
 #[allow(non_camel_case_types)]
 pub struct i256(pub [u8; 32]);
 
+impl i256 {
+    pub const MIN: i256 = {
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x80;
+        i256(bytes)
+    };
+    pub const MAX: i256 = {
+        let mut bytes = [0xffu8; 32];
+        bytes[0] = 0x7f;
+        i256(bytes)
+    };
+    pub const ZERO: i256 = i256([0u8; 32]);
+    pub const ONE: i256 = {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 1;
+        i256(bytes)
+    };
+
+    pub const fn is_negative(&self) -> bool {
+        self.0[0] & 0x80 != 0
+    }
+
+    /// The absolute value, which always fits in a `u256`.
+    pub fn unsigned_abs(self) -> u256 {
+        if self.is_negative() {
+            u256(self.0).wrapping_neg()
+        } else {
+            u256(self.0)
+        }
+    }
+
+    /// Applies a sign to a magnitude, or `None` if the result does not fit.
+    fn from_sign_magnitude(negative: bool, magnitude: u256) -> Option<i256> {
+        if negative {
+            if magnitude > u256(i256::MIN.0) {
+                return None;
+            }
+            Some(i256(magnitude.wrapping_neg().0))
+        } else {
+            let out = i256(magnitude.0);
+            if out.is_negative() {
+                return None;
+            }
+            Some(out)
+        }
+    }
+
+    pub fn checked_neg(self) -> Option<i256> {
+        if self == i256::MIN {
+            return None;
+        }
+        Some(i256(u256(self.0).wrapping_neg().0))
+    }
+
+    pub fn checked_add(self, rhs: i256) -> Option<i256> {
+        let out = i256(u256(self.0).wrapping_add(u256(rhs.0)).0);
+        if self.is_negative() == rhs.is_negative() && out.is_negative() != self.is_negative() {
+            return None;
+        }
+        Some(out)
+    }
+
+    pub fn checked_sub(self, rhs: i256) -> Option<i256> {
+        let out = i256(u256(self.0).wrapping_sub(u256(rhs.0)).0);
+        if self.is_negative() != rhs.is_negative() && out.is_negative() != self.is_negative() {
+            return None;
+        }
+        Some(out)
+    }
+
+    pub fn checked_mul(self, rhs: i256) -> Option<i256> {
+        let magnitude = self.unsigned_abs().checked_mul(rhs.unsigned_abs())?;
+        i256::from_sign_magnitude(self.is_negative() != rhs.is_negative(), magnitude)
+    }
+
+    /// Division rounding towards zero. `None` if `rhs` is zero or the result overflows.
+    pub fn checked_div(self, rhs: i256) -> Option<i256> {
+        let magnitude = self.unsigned_abs().checked_div(rhs.unsigned_abs())?;
+        i256::from_sign_magnitude(self.is_negative() != rhs.is_negative(), magnitude)
+    }
+
+    /// Remainder with the sign of `self`. `None` if `rhs` is zero or the division overflows.
+    pub fn checked_rem(self, rhs: i256) -> Option<i256> {
+        if self == i256::MIN && rhs == -i256::ONE {
+            return None;
+        }
+        let magnitude = self.unsigned_abs().checked_rem(rhs.unsigned_abs())?;
+        i256::from_sign_magnitude(self.is_negative(), magnitude)
+    }
+
+    /// `None` if `shift` is 256 or more.
+    pub fn checked_shl(self, shift: u32) -> Option<i256> {
+        Some(i256(u256(self.0).checked_shl(shift)?.0))
+    }
+
+    /// Arithmetic shift, keeping the sign. `None` if `shift` is 256 or more.
+    pub fn checked_shr(self, shift: u32) -> Option<i256> {
+        if shift >= 256 {
+            return None;
+        }
+        Some(i256(from_limbs(shr_limbs(
+            to_limbs(&self.0),
+            shift,
+            self.is_negative(),
+        ))))
+    }
+
+    fn to_i128(self) -> Option<i128> {
+        let (high, low): (u128, u128) = self.into();
+        let low = low as i128;
+        match high {
+            0 if low >= 0 => Some(low),
+            u128::MAX if low < 0 => Some(low),
+            _ => None,
+        }
+    }
+}
+
 impl From<i256> for u256 {
     fn from(i: i256) -> Self {
         u256(i.0)
     }
 }
 
+impl fmt::Display for i256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_limbs(to_limbs(&self.unsigned_abs().0), !self.is_negative(), f)
+    }
+}
+
+impl FromStr for i256 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let magnitude = u256(from_limbs(parse_limbs(digits)?));
+        i256::from_sign_magnitude(negative, magnitude)
+            .ok_or_else(|| anyhow!("integer out of range for Int256: '{}'", s))
+    }
+}
+
+impl Ord for i256 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // flipping the sign bit orders two's complement like unsigned
+        let mut a = self.0;
+        let mut b = other.0;
+        a[0] ^= 0x80;
+        b[0] ^= 0x80;
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for i256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl ToSql for i256 {
     fn to_sql(self) -> Result<Value> {
         Ok(Value::Int256(self))
@@ -24,14 +346,18 @@ impl ToSql for i256 {
 
 impl FromSql for i256 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Int256) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Int256(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Int256)
+    }
 }
 
 impl From<i256> for (u128, u128) {
@@ -55,10 +381,116 @@ impl From<(u128, u128)> for i256 {
 }
 
 /// Wrapper type for Clickhouse `UInt256` type.
+/// Stored as big endian.
 #[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd, Debug, Default)]
 #[allow(non_camel_case_types)]
 pub struct u256(pub [u8; 32]);
 
+impl u256 {
+    pub const MIN: u256 = u256([0u8; 32]);
+    pub const MAX: u256 = u256([0xffu8; 32]);
+    pub const ZERO: u256 = u256([0u8; 32]);
+    pub const ONE: u256 = {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 1;
+        u256(bytes)
+    };
+
+    pub fn wrapping_add(self, rhs: u256) -> u256 {
+        u256(from_limbs(add_limbs(to_limbs(&self.0), to_limbs(&rhs.0)).0))
+    }
+
+    pub fn wrapping_sub(self, rhs: u256) -> u256 {
+        u256(from_limbs(sub_limbs(to_limbs(&self.0), to_limbs(&rhs.0)).0))
+    }
+
+    pub fn wrapping_mul(self, rhs: u256) -> u256 {
+        u256(from_limbs(mul_limbs(to_limbs(&self.0), to_limbs(&rhs.0)).0))
+    }
+
+    pub fn wrapping_neg(self) -> u256 {
+        u256::ZERO.wrapping_sub(self)
+    }
+
+    pub fn checked_add(self, rhs: u256) -> Option<u256> {
+        match add_limbs(to_limbs(&self.0), to_limbs(&rhs.0)) {
+            (out, false) => Some(u256(from_limbs(out))),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_sub(self, rhs: u256) -> Option<u256> {
+        match sub_limbs(to_limbs(&self.0), to_limbs(&rhs.0)) {
+            (out, false) => Some(u256(from_limbs(out))),
+            (_, true) => None,
+        }
+    }
+
+    pub fn checked_mul(self, rhs: u256) -> Option<u256> {
+        match mul_limbs(to_limbs(&self.0), to_limbs(&rhs.0)) {
+            (out, false) => Some(u256(from_limbs(out))),
+            (_, true) => None,
+        }
+    }
+
+    /// `None` if `rhs` is zero.
+    pub fn checked_div(self, rhs: u256) -> Option<u256> {
+        if rhs == u256::ZERO {
+            return None;
+        }
+        let (quotient, _) = div_rem_limbs(to_limbs(&self.0), to_limbs(&rhs.0));
+        Some(u256(from_limbs(quotient)))
+    }
+
+    /// `None` if `rhs` is zero.
+    pub fn checked_rem(self, rhs: u256) -> Option<u256> {
+        if rhs == u256::ZERO {
+            return None;
+        }
+        let (_, remainder) = div_rem_limbs(to_limbs(&self.0), to_limbs(&rhs.0));
+        Some(u256(from_limbs(remainder)))
+    }
+
+    /// `None` if `shift` is 256 or more.
+    pub fn checked_shl(self, shift: u32) -> Option<u256> {
+        if shift >= 256 {
+            return None;
+        }
+        Some(u256(from_limbs(shl_limbs(to_limbs(&self.0), shift))))
+    }
+
+    /// `None` if `shift` is 256 or more.
+    pub fn checked_shr(self, shift: u32) -> Option<u256> {
+        if shift >= 256 {
+            return None;
+        }
+        Some(u256(from_limbs(shr_limbs(to_limbs(&self.0), shift, false))))
+    }
+
+    fn to_u128(self) -> Option<u128> {
+        match self.into() {
+            (0u128, low) => Some(low),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for u256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_limbs(to_limbs(&self.0), true, f)
+    }
+}
+
+impl FromStr for u256 {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(u256(from_limbs(parse_limbs(
+            s.strip_prefix('+').unwrap_or(s),
+        )?)))
+    }
+}
+
 impl ToSql for u256 {
     fn to_sql(self) -> Result<Value> {
         Ok(Value::UInt256(self))
@@ -67,14 +499,18 @@ impl ToSql for u256 {
 
 impl FromSql for u256 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::UInt256) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::UInt256(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::UInt256)
+    }
 }
 
 impl From<u256> for i256 {
@@ -102,3 +538,207 @@ impl From<(u128, u128)> for u256 {
         u256(buf)
     }
 }
+
+/// Operators panic on overflow, division by zero and shifts of 256 or more, in both debug and release builds.
+macro_rules! operator_impls {
+    ($($t:ty),+) => {
+        $(
+            impl Add for $t {
+                type Output = $t;
+
+                fn add(self, rhs: $t) -> $t {
+                    self.checked_add(rhs).expect("attempt to add with overflow")
+                }
+            }
+
+            impl Sub for $t {
+                type Output = $t;
+
+                fn sub(self, rhs: $t) -> $t {
+                    self.checked_sub(rhs).expect("attempt to subtract with overflow")
+                }
+            }
+
+            impl Mul for $t {
+                type Output = $t;
+
+                fn mul(self, rhs: $t) -> $t {
+                    self.checked_mul(rhs).expect("attempt to multiply with overflow")
+                }
+            }
+
+            impl Div for $t {
+                type Output = $t;
+
+                fn div(self, rhs: $t) -> $t {
+                    self.checked_div(rhs).expect("attempt to divide by zero or with overflow")
+                }
+            }
+
+            impl Rem for $t {
+                type Output = $t;
+
+                fn rem(self, rhs: $t) -> $t {
+                    self.checked_rem(rhs)
+                        .expect("attempt to calculate the remainder with a divisor of zero or with overflow")
+                }
+            }
+
+            impl Shl<u32> for $t {
+                type Output = $t;
+
+                fn shl(self, shift: u32) -> $t {
+                    self.checked_shl(shift).expect("attempt to shift left with overflow")
+                }
+            }
+
+            impl Shr<u32> for $t {
+                type Output = $t;
+
+                fn shr(self, shift: u32) -> $t {
+                    self.checked_shr(shift).expect("attempt to shift right with overflow")
+                }
+            }
+        )+
+    };
+}
+
+operator_impls!(i256, u256);
+
+impl Neg for i256 {
+    type Output = i256;
+
+    fn neg(self) -> i256 {
+        self.checked_neg().expect("attempt to negate with overflow")
+    }
+}
+
+macro_rules! unsigned_impls {
+    ($($t:ty),+) => {
+        $(
+            impl From<$t> for u256 {
+                fn from(x: $t) -> Self {
+                    u256::from((0u128, x as u128))
+                }
+            }
+
+            impl From<$t> for i256 {
+                fn from(x: $t) -> Self {
+                    i256::from((0u128, x as u128))
+                }
+            }
+
+            impl TryFrom<u256> for $t {
+                type Error = anyhow::Error;
+
+                fn try_from(x: u256) -> Result<Self> {
+                    x.to_u128()
+                        .and_then(|x| <$t>::try_from(x).ok())
+                        .ok_or_else(|| anyhow!("{} out of range for {}", x, stringify!($t)))
+                }
+            }
+
+            impl TryFrom<i256> for $t {
+                type Error = anyhow::Error;
+
+                fn try_from(x: i256) -> Result<Self> {
+                    Some(u256(x.0))
+                        .filter(|_| !x.is_negative())
+                        .and_then(|x| x.to_u128())
+                        .and_then(|x| <$t>::try_from(x).ok())
+                        .ok_or_else(|| anyhow!("{} out of range for {}", x, stringify!($t)))
+                }
+            }
+        )+
+    };
+}
+
+unsigned_impls!(u8, u16, u32, u64, u128, usize);
+
+macro_rules! signed_impls {
+    ($($t:ty),+) => {
+        $(
+            impl TryFrom<$t> for u256 {
+                type Error = anyhow::Error;
+
+                fn try_from(x: $t) -> Result<Self> {
+                    if x < 0 {
+                        return Err(anyhow!("{} out of range for u256", x));
+                    }
+                    Ok(u256::from((0u128, x as u128)))
+                }
+            }
+
+            impl From<$t> for i256 {
+                fn from(x: $t) -> Self {
+                    let extension = if x < 0 { u128::MAX } else { 0 };
+                    i256::from((extension, x as i128 as u128))
+                }
+            }
+
+            impl TryFrom<u256> for $t {
+                type Error = anyhow::Error;
+
+                fn try_from(x: u256) -> Result<Self> {
+                    x.to_u128()
+                        .and_then(|x| <$t>::try_from(x).ok())
+                        .ok_or_else(|| anyhow!("{} out of range for {}", x, stringify!($t)))
+                }
+            }
+
+            impl TryFrom<i256> for $t {
+                type Error = anyhow::Error;
+
+                fn try_from(x: i256) -> Result<Self> {
+                    x.to_i128()
+                        .and_then(|x| <$t>::try_from(x).ok())
+                        .ok_or_else(|| anyhow!("{} out of range for {}", x, stringify!($t)))
+                }
+            }
+        )+
+    };
+}
+
+signed_impls!(i8, i16, i32, i64, i128, isize);
+
+#[cfg(feature = "primitive-types")]
+impl From<primitive_types::U256> for u256 {
+    fn from(x: primitive_types::U256) -> Self {
+        u256(from_limbs(x.0))
+    }
+}
+
+#[cfg(feature = "primitive-types")]
+impl From<u256> for primitive_types::U256 {
+    fn from(x: u256) -> Self {
+        primitive_types::U256(to_limbs(&x.0))
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl From<ethnum::U256> for u256 {
+    fn from(x: ethnum::U256) -> Self {
+        u256(x.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl From<u256> for ethnum::U256 {
+    fn from(x: u256) -> Self {
+        ethnum::U256::from_be_bytes(x.0)
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl From<ethnum::I256> for i256 {
+    fn from(x: ethnum::I256) -> Self {
+        i256(x.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "ethnum")]
+impl From<i256> for ethnum::I256 {
+    fn from(x: i256) -> Self {
+        ethnum::I256::from_be_bytes(x.0)
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use crate::convert::{unexpected_type, unexpected_value};
 use std::{
     fmt,
     net::{Ipv4Addr, Ipv6Addr},
@@ -85,14 +86,18 @@ impl ToSql for Ipv4 {
 
 impl FromSql for Ipv4 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Ipv4) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Ipv4(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Ipv4)
+    }
 }
 
 impl ToSql for Ipv6 {
@@ -103,12 +108,16 @@ impl ToSql for Ipv6 {
 
 impl FromSql for Ipv6 {
     fn from_sql(type_: &Type, value: Value) -> Result<Self> {
-        if !matches!(type_, Type::Ipv6) {
+        if !Self::accepts_type(type_) {
             return Err(unexpected_type(type_));
         }
         match value {
             Value::Ipv6(x) => Ok(x),
-            _ => unimplemented!(),
+            value => Err(unexpected_value(type_, &value)),
         }
     }
+
+    fn accepts_type(type_: &Type) -> bool {
+        matches!(type_, Type::Ipv6)
+    }
 }
@@ -0,0 +1,631 @@
+use std::fmt::Write;
+
+use chrono::NaiveDate;
+
+use crate::{convert::ToSql, types::Type, Value};
+use anyhow::*;
+
+use super::text::{write_decimal, write_quoted_str};
+
+/// Renders `value` as a Clickhouse SQL literal, with types inferred from the value alone.
+/// Enum values are rendered as their numeric value. Prefer [`Value::to_sql_literal`] when the type is known.
+/// Used by [`crate::sql!`].
+pub fn sql_literal(value: impl ToSql) -> Result<String> {
+    let mut out = String::new();
+    value.to_sql()?.write_sql_literal(None, &mut out)?;
+    Ok(out)
+}
+
+/// Builds a SQL string with [`std::format!`] syntax, rendering each positional argument as an escaped SQL literal.
+/// Arguments can be anything implementing [`crate::ToSql`]. Returns a [`crate::Result`].
+/// Only `{}` and `{N}` placeholders are accepted, inline captures like `{name}` fail to compile.
+/// ```ignore
+/// let query = sql!("select * from t where name = {} and has({}, id)", name, vec![1u64, 2])?;
+/// ```
+#[macro_export]
+macro_rules! sql {
+    ($format:literal $(, $arg:expr)* $(,)?) => {{
+        const _: () = ::std::assert!(
+            $crate::sql_format_is_positional($format),
+            "sql! only accepts positional placeholders, inline captures would not be escaped"
+        );
+        (|| -> $crate::Result<::std::string::String> {
+            ::std::result::Result::Ok(::std::format!($format $(, $crate::sql_literal($arg)?)*))
+        })()
+    }};
+}
+
+/// Whether every placeholder in `format` is `{}` or `{N}`, optionally with a format spec without `$` arguments.
+/// Used by [`crate::sql!`] to reject inline captures, which would be formatted unescaped.
+#[doc(hidden)]
+pub const fn sql_format_is_positional(format: &str) -> bool {
+    let bytes = format.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'{' {
+            i += 1;
+            continue;
+        }
+        if i + 1 < bytes.len() && bytes[i + 1] == b'{' {
+            i += 2;
+            continue;
+        }
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        if i < bytes.len() && bytes[i] == b':' {
+            while i < bytes.len() && bytes[i] != b'}' {
+                if bytes[i] == b'$' {
+                    return false;
+                }
+                i += 1;
+            }
+        }
+        if i >= bytes.len() || bytes[i] != b'}' {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+fn write_list<'a>(
+    values: impl Iterator<Item = (Option<&'a Type>, &'a Value)>,
+    out: &mut String,
+) -> Result<()> {
+    for (i, (type_, value)) in values.enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        value.write_sql_literal(type_, out)?;
+    }
+    Ok(())
+}
+
+impl Value {
+    /// Renders this value as an escaped Clickhouse SQL literal of type `type_`, i.e. `'it\'s'` or `toDecimal64('1.50', 2)`.
+    /// Fails if the value does not fit the type.
+    pub fn to_sql_literal(&self, type_: &Type) -> Result<String> {
+        type_.validate_value(self)?;
+        let mut out = String::new();
+        self.write_sql_literal(Some(type_), &mut out)?;
+        Ok(out)
+    }
+
+    fn write_sql_literal(&self, type_: Option<&Type>, out: &mut String) -> Result<()> {
+        match (type_, self) {
+            (Some(Type::LowCardinality(inner)), value) => {
+                return value.write_sql_literal(Some(inner), out)
+            }
+            (Some(Type::Nullable(_)) | None, Value::Null) => out.push_str("NULL"),
+            (Some(Type::Nullable(inner)), value) => {
+                return value.write_sql_literal(Some(inner), out)
+            }
+            (_, Value::Int8(x)) => write!(out, "{}", x)?,
+            (_, Value::Int16(x)) => write!(out, "{}", x)?,
+            (_, Value::Int32(x)) => write!(out, "{}", x)?,
+            (_, Value::Int64(x)) => write!(out, "{}", x)?,
+            (_, Value::Int128(x)) => write!(out, "toInt128('{}')", x)?,
+            (_, Value::Int256(x)) => write!(out, "toInt256('{}')", x)?,
+            (_, Value::UInt8(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt16(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt32(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt64(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt128(x)) => write!(out, "toUInt128('{}')", x)?,
+            (_, Value::UInt256(x)) => write!(out, "toUInt256('{}')", x)?,
+            (_, Value::Float32(x)) => {
+                let x = f32::from_bits(*x);
+                if x.is_nan() {
+                    out.push_str("toFloat32('nan')");
+                } else {
+                    write!(out, "toFloat32('{:?}')", x)?;
+                }
+            }
+            (_, Value::Float64(x)) => {
+                let x = f64::from_bits(*x);
+                if x.is_nan() {
+                    out.push_str("nan");
+                } else {
+                    write!(out, "{:?}", x)?;
+                }
+            }
+            (_, Value::Decimal32(scale, x)) => {
+                out.push_str("toDecimal32('");
+                write_decimal(x.to_string(), *scale, out);
+                write!(out, "', {})", scale)?;
+            }
+            (_, Value::Decimal64(scale, x)) => {
+                out.push_str("toDecimal64('");
+                write_decimal(x.to_string(), *scale, out);
+                write!(out, "', {})", scale)?;
+            }
+            (_, Value::Decimal128(scale, x)) => {
+                out.push_str("toDecimal128('");
+                write_decimal(x.to_string(), *scale, out);
+                write!(out, "', {})", scale)?;
+            }
+            (_, Value::Decimal256(scale, x)) => {
+                out.push_str("toDecimal256('");
+                write_decimal(x.to_string(), *scale, out);
+                write!(out, "', {})", scale)?;
+            }
+            (Some(Type::FixedString(n)), Value::String(x)) => {
+                out.push_str("toFixedString(");
+                write_quoted_str(x, out);
+                write!(out, ", {})", n)?;
+            }
+            (_, Value::String(x)) => write_quoted_str(x, out),
+            (_, Value::Uuid(x)) => write!(out, "toUUID('{}')", x)?,
+            (_, Value::Date(x)) => {
+                let date = NaiveDate::from_ymd(1970, 1, 1) + chrono::Duration::days(x.0 as i64);
+                write!(out, "toDate('{}')", date.format("%Y-%m-%d"))?;
+            }
+            // unix timestamps, as local times can be ambiguous around daylight saving changes
+            (_, Value::DateTime(x)) => write!(out, "toDateTime({}, '{}')", x.1, x.0.name())?,
+            (_, Value::DateTime64(tz, precision, ticks)) => {
+                let modulus = 10u64.pow(*precision as u32);
+                write!(out, "toDateTime64('{}", ticks / modulus)?;
+                if *precision > 0 {
+                    write!(out, ".{:0>width$}", ticks % modulus, width = *precision)?;
+                }
+                write!(out, "', {}, '{}')", precision, tz.name())?;
+            }
+            (Some(Type::Enum8(entries)), Value::Enum8(x)) => {
+                match entries.iter().find(|(_, index)| index == x) {
+                    Some((name, _)) => write_quoted_str(name, out),
+                    None => return Err(anyhow!("unknown Enum8 value {}", x)),
+                }
+            }
+            (Some(Type::Enum16(entries)), Value::Enum16(x)) => {
+                match entries.iter().find(|(_, index)| index == x) {
+                    Some((name, _)) => write_quoted_str(name, out),
+                    None => return Err(anyhow!("unknown Enum16 value {}", x)),
+                }
+            }
+            (None, Value::Enum8(x)) => write!(out, "{}", x)?,
+            (None, Value::Enum16(x)) => write!(out, "{}", x)?,
+            (type_, Value::Array(values)) => {
+                let inner = type_.map(|x| x.unwrap_array()).transpose()?;
+                out.push('[');
+                write_list(values.iter().map(|x| (inner, x)), out)?;
+                out.push(']');
+            }
+            (type_, Value::Tuple(values)) => {
+                let types = type_.map(|x| x.unwrap_tuple()).transpose()?;
+                out.push_str("tuple(");
+                write_list(
+                    values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| (types.and_then(|types| types.get(i)), x)),
+                    out,
+                )?;
+                out.push(')');
+            }
+            (type_, Value::Map(keys, values)) => {
+                let (key_type, value_type) = match type_ {
+                    Some(type_) => {
+                        let (key, value) = type_.unwrap_map()?;
+                        (Some(key), Some(value))
+                    }
+                    None => (None, None),
+                };
+                out.push_str("map(");
+                write_list(
+                    keys.iter()
+                        .zip(values)
+                        .flat_map(|(key, value)| [(key_type, key), (value_type, value)]),
+                    out,
+                )?;
+                out.push(')');
+            }
+            (_, Value::Ipv4(x)) => write!(out, "toIPv4('{}')", x)?,
+            (_, Value::Ipv6(x)) => write!(out, "toIPv6('{}')", x)?,
+            (Some(type_), value) => {
+                return Err(anyhow!(
+                    "could not format value '{:?}' as type '{}'",
+                    value,
+                    type_
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, Ipv6Addr},
+        str::FromStr,
+    };
+
+    use chrono_tz::Tz;
+    use proptest::prelude::*;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::{i256, u256, Date, DateTime, Ipv4, Ipv6};
+
+    /// Parser for the literals rendered by [`Value::to_sql_literal`], guided by the expected type.
+    struct Parser<'a> {
+        input: &'a str,
+    }
+
+    impl<'a> Parser<'a> {
+        fn eat(&mut self, prefix: &str) -> bool {
+            self.input = self.input.trim_start();
+            match self.input.strip_prefix(prefix) {
+                Some(rest) => {
+                    self.input = rest;
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn expect(&mut self, prefix: &str) {
+            assert!(
+                self.eat(prefix),
+                "expected {:?} at {:?}",
+                prefix,
+                self.input
+            );
+        }
+
+        fn token(&mut self) -> &'a str {
+            self.input = self.input.trim_start();
+            let end = self.input.find([',', ')', ']']).unwrap_or(self.input.len());
+            let (token, rest) = self.input.split_at(end);
+            self.input = rest;
+            token
+        }
+
+        fn string(&mut self) -> String {
+            self.expect("'");
+            let mut out = String::new();
+            let mut chars = self.input.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\'' => {
+                        self.input = &self.input[i + 1..];
+                        return out;
+                    }
+                    '\\' => out.push(match chars.next().unwrap().1 {
+                        't' => '\t',
+                        'n' => '\n',
+                        'r' => '\r',
+                        '0' => '\0',
+                        'b' => '\u{8}',
+                        'f' => '\u{c}',
+                        c => c,
+                    }),
+                    c => out.push(c),
+                }
+            }
+            panic!("unterminated string");
+        }
+
+        fn call(&mut self, function: &str) -> String {
+            self.expect(function);
+            self.expect("(");
+            let out = self.string();
+            self.expect(")");
+            out
+        }
+
+        fn decimal(&mut self, function: &str, scale: usize) -> String {
+            self.expect(function);
+            self.expect("(");
+            let value = self.string();
+            self.expect(",");
+            assert_eq!(self.token().parse::<usize>().unwrap(), scale);
+            self.expect(")");
+            if scale > 0 {
+                assert_eq!(value.len() - value.find('.').unwrap() - 1, scale);
+            }
+            value.replace('.', "")
+        }
+
+        fn list(&mut self, end: &str, mut item: impl FnMut(&mut Self, usize)) {
+            let mut i = 0;
+            while !self.eat(end) {
+                if i > 0 {
+                    self.expect(",");
+                }
+                item(self, i);
+                i += 1;
+            }
+        }
+
+        fn value(&mut self, type_: &Type) -> Value {
+            match type_ {
+                Type::Nullable(_) if self.eat("NULL") => Value::Null,
+                Type::Nullable(inner) | Type::LowCardinality(inner) => self.value(inner),
+                Type::Int8 => Value::Int8(self.token().parse().unwrap()),
+                Type::Int16 => Value::Int16(self.token().parse().unwrap()),
+                Type::Int32 => Value::Int32(self.token().parse().unwrap()),
+                Type::Int64 => Value::Int64(self.token().parse().unwrap()),
+                Type::Int128 => Value::Int128(self.call("toInt128").parse().unwrap()),
+                Type::Int256 => Value::Int256(self.call("toInt256").parse().unwrap()),
+                Type::UInt8 => Value::UInt8(self.token().parse().unwrap()),
+                Type::UInt16 => Value::UInt16(self.token().parse().unwrap()),
+                Type::UInt32 => Value::UInt32(self.token().parse().unwrap()),
+                Type::UInt64 => Value::UInt64(self.token().parse().unwrap()),
+                Type::UInt128 => Value::UInt128(self.call("toUInt128").parse().unwrap()),
+                Type::UInt256 => Value::UInt256(self.call("toUInt256").parse().unwrap()),
+                Type::Float32 => {
+                    Value::Float32(self.call("toFloat32").parse::<f32>().unwrap().to_bits())
+                }
+                Type::Float64 => Value::Float64(self.token().parse::<f64>().unwrap().to_bits()),
+                Type::Decimal32(s) => {
+                    Value::Decimal32(*s, self.decimal("toDecimal32", *s).parse().unwrap())
+                }
+                Type::Decimal64(s) => {
+                    Value::Decimal64(*s, self.decimal("toDecimal64", *s).parse().unwrap())
+                }
+                Type::Decimal128(s) => {
+                    Value::Decimal128(*s, self.decimal("toDecimal128", *s).parse().unwrap())
+                }
+                Type::Decimal256(s) => {
+                    Value::Decimal256(*s, self.decimal("toDecimal256", *s).parse().unwrap())
+                }
+                Type::String => Value::String(self.string()),
+                Type::FixedString(n) => {
+                    self.expect("toFixedString(");
+                    let value = self.string();
+                    self.expect(",");
+                    assert_eq!(self.token().parse::<usize>().unwrap(), *n);
+                    self.expect(")");
+                    Value::String(value)
+                }
+                Type::Uuid => Value::Uuid(Uuid::parse_str(&self.call("toUUID")).unwrap()),
+                Type::Date => {
+                    let date = chrono::NaiveDate::from_str(&self.call("toDate")).unwrap();
+                    let days = date - NaiveDate::from_ymd(1970, 1, 1);
+                    Value::Date(Date(days.num_days() as u16))
+                }
+                Type::DateTime(tz) => {
+                    self.expect("toDateTime(");
+                    let seconds = self.token().parse().unwrap();
+                    self.expect(",");
+                    assert_eq!(&self.string(), tz.name());
+                    self.expect(")");
+                    Value::DateTime(DateTime(*tz, seconds))
+                }
+                Type::DateTime64(precision, tz) => {
+                    self.expect("toDateTime64(");
+                    let value = self.string();
+                    self.expect(",");
+                    assert_eq!(self.token().parse::<usize>().unwrap(), *precision);
+                    self.expect(",");
+                    assert_eq!(&self.string(), tz.name());
+                    self.expect(")");
+                    let (seconds, fraction) = value.split_once('.').unwrap_or((&value, "0"));
+                    let ticks = seconds.parse::<u64>().unwrap() * 10u64.pow(*precision as u32)
+                        + fraction.parse::<u64>().unwrap();
+                    Value::DateTime64(*tz, *precision, ticks)
+                }
+                Type::Ipv4 => Value::Ipv4(Ipv4(self.call("toIPv4").parse().unwrap())),
+                Type::Ipv6 => Value::Ipv6(Ipv6(self.call("toIPv6").parse().unwrap())),
+                Type::Enum8(entries) => {
+                    let name = self.string();
+                    Value::Enum8(entries.iter().find(|x| x.0 == name).unwrap().1)
+                }
+                Type::Enum16(entries) => {
+                    let name = self.string();
+                    Value::Enum16(entries.iter().find(|x| x.0 == name).unwrap().1)
+                }
+                Type::Array(inner) => {
+                    self.expect("[");
+                    let mut values = vec![];
+                    self.list("]", |parser, _| values.push(parser.value(inner)));
+                    Value::Array(values)
+                }
+                Type::Tuple(types) => {
+                    self.expect("tuple(");
+                    let mut values = vec![];
+                    self.list(")", |parser, i| values.push(parser.value(&types[i])));
+                    Value::Tuple(values)
+                }
+                Type::Map(key, value) => {
+                    self.expect("map(");
+                    let (mut keys, mut values) = (vec![], vec![]);
+                    self.list(")", |parser, i| {
+                        if i % 2 == 0 {
+                            keys.push(parser.value(key));
+                        } else {
+                            values.push(parser.value(value));
+                        }
+                    });
+                    Value::Map(keys, values)
+                }
+            }
+        }
+    }
+
+    fn parse(type_: &Type, literal: &str) -> Value {
+        let mut parser = Parser { input: literal };
+        let value = parser.value(type_);
+        assert_eq!(parser.input, "", "trailing input");
+        value
+    }
+
+    fn arb_tz() -> impl Strategy<Value = Tz> {
+        prop_oneof![
+            Just(Tz::UTC),
+            Just(Tz::Europe__London),
+            Just(Tz::America__New_York)
+        ]
+    }
+
+    fn arb_leaf() -> impl Strategy<Value = (Type, Value)> {
+        let leaf = prop_oneof![
+            any::<i8>().prop_map(|x| (Type::Int8, Value::Int8(x))),
+            any::<i16>().prop_map(|x| (Type::Int16, Value::Int16(x))),
+            any::<i32>().prop_map(|x| (Type::Int32, Value::Int32(x))),
+            any::<i64>().prop_map(|x| (Type::Int64, Value::Int64(x))),
+            any::<i128>().prop_map(|x| (Type::Int128, Value::Int128(x))),
+            any::<[u8; 32]>().prop_map(|x| (Type::Int256, Value::Int256(i256(x)))),
+            any::<u8>().prop_map(|x| (Type::UInt8, Value::UInt8(x))),
+            any::<u16>().prop_map(|x| (Type::UInt16, Value::UInt16(x))),
+            any::<u32>().prop_map(|x| (Type::UInt32, Value::UInt32(x))),
+            any::<u64>().prop_map(|x| (Type::UInt64, Value::UInt64(x))),
+            any::<u128>().prop_map(|x| (Type::UInt128, Value::UInt128(x))),
+            any::<[u8; 32]>().prop_map(|x| (Type::UInt256, Value::UInt256(u256(x)))),
+            any::<f32>()
+                .prop_filter("nan", |x| !x.is_nan())
+                .prop_map(|x| (Type::Float32, Value::Float32(x.to_bits()))),
+            any::<f64>()
+                .prop_filter("nan", |x| !x.is_nan())
+                .prop_map(|x| (Type::Float64, Value::Float64(x.to_bits()))),
+            (0usize..10, any::<i32>())
+                .prop_map(|(s, x)| (Type::Decimal32(s), Value::Decimal32(s, x))),
+            (0usize..19, any::<i64>())
+                .prop_map(|(s, x)| (Type::Decimal64(s), Value::Decimal64(s, x))),
+            (0usize..39, any::<i128>())
+                .prop_map(|(s, x)| (Type::Decimal128(s), Value::Decimal128(s, x))),
+            (0usize..77, any::<[u8; 32]>())
+                .prop_map(|(s, x)| (Type::Decimal256(s), Value::Decimal256(s, i256(x)))),
+            any::<String>().prop_map(|x| (Type::String, Value::String(x))),
+            "[ -~]{0,4}".prop_map(|x| (Type::FixedString(4), Value::String(x))),
+            any::<u128>().prop_map(|x| (Type::Uuid, Value::Uuid(Uuid::from_u128(x)))),
+            any::<u16>().prop_map(|x| (Type::Date, Value::Date(Date(x)))),
+            (arb_tz(), any::<u32>())
+                .prop_map(|(tz, x)| (Type::DateTime(tz), Value::DateTime(DateTime(tz, x)))),
+            (arb_tz(), 0usize..10, 0u64..1 << 62)
+                .prop_map(|(tz, p, x)| (Type::DateTime64(p, tz), Value::DateTime64(tz, p, x))),
+            any::<u32>().prop_map(|x| (Type::Ipv4, Value::Ipv4(Ipv4(Ipv4Addr::from(x))))),
+            any::<u128>().prop_map(|x| (Type::Ipv6, Value::Ipv6(Ipv6(Ipv6Addr::from(x))))),
+            prop_oneof![Just(1u8), Just(2u8)].prop_map(|x| (
+                Type::Enum8(vec![("a".to_string(), 1), ("it's".to_string(), 2)]),
+                Value::Enum8(x)
+            )),
+        ];
+        prop_oneof![
+            3 => leaf.clone(),
+            1 => leaf.prop_map(|(type_, value)| (Type::Nullable(Box::new(type_)), value)),
+            1 => Just((Type::Nullable(Box::new(Type::String)), Value::Null)),
+        ]
+    }
+
+    fn arb_typed_value() -> impl Strategy<Value = (Type, Value)> {
+        arb_leaf().prop_recursive(3, 24, 4, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..4).prop_map(|items| {
+                    let type_ = items.first().map(|x| x.0.clone()).unwrap_or(Type::String);
+                    let values = items
+                        .into_iter()
+                        .filter(|x| x.0 == type_)
+                        .map(|x| x.1)
+                        .collect();
+                    (Type::Array(Box::new(type_)), Value::Array(values))
+                }),
+                prop::collection::vec(inner.clone(), 1..4).prop_map(|items| {
+                    let (types, values) = items.into_iter().unzip();
+                    (Type::Tuple(types), Value::Tuple(values))
+                }),
+                prop::collection::vec(("[a-z']{0,3}", inner), 0..4).prop_map(|items| {
+                    let type_ = items
+                        .first()
+                        .map(|x| x.1 .0.clone())
+                        .unwrap_or(Type::String);
+                    let (keys, values) = items
+                        .into_iter()
+                        .filter(|x| x.1 .0 == type_)
+                        .map(|(key, x)| (Value::String(key), x.1))
+                        .unzip();
+                    (
+                        Type::Map(Box::new(Type::String), Box::new(type_)),
+                        Value::Map(keys, values),
+                    )
+                }),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn literals_roundtrip((type_, value) in arb_typed_value()) {
+            prop_assume!(type_.validate(0).is_ok());
+            let literal = value.to_sql_literal(&type_).unwrap();
+            prop_assert_eq!(parse(&type_, &literal), value, "literal: {}", literal);
+        }
+    }
+
+    #[test]
+    fn test_literals() {
+        let literal = |type_: &str, value: Value| {
+            value
+                .to_sql_literal(&Type::from_str(type_).unwrap())
+                .unwrap()
+        };
+        assert_eq!(
+            literal("String", Value::String("it's\n\\".to_string())),
+            r"'it\'s\n\\'"
+        );
+        assert_eq!(
+            literal("FixedString(2)", Value::String("a".to_string())),
+            "toFixedString('a', 2)"
+        );
+        assert_eq!(
+            literal("Decimal(10, 2)", Value::Decimal64(2, -150)),
+            "toDecimal64('-1.50', 2)"
+        );
+        assert_eq!(
+            literal(
+                "DateTime('Europe/London')",
+                Value::DateTime(DateTime(Tz::Europe__London, 5))
+            ),
+            "toDateTime(5, 'Europe/London')"
+        );
+        assert_eq!(
+            literal(
+                "Array(Nullable(Int32))",
+                Value::Array(vec![Value::Int32(-1), Value::Null])
+            ),
+            "[-1, NULL]"
+        );
+        assert_eq!(
+            literal(
+                "Tuple(UInt8, Map(String, Array(String)))",
+                Value::Tuple(vec![
+                    Value::UInt8(1),
+                    Value::Map(
+                        vec![Value::String("k".to_string())],
+                        vec![Value::Array(vec![Value::String("v".to_string())])]
+                    )
+                ])
+            ),
+            "tuple(1, map('k', ['v']))"
+        );
+        assert!(Value::String("x".to_string())
+            .to_sql_literal(&Type::UInt8)
+            .is_err());
+    }
+
+    #[test]
+    fn test_sql_macro() {
+        assert_eq!(
+            crate::sql!(
+                "select * from t where name = {} and has({}, id) and x = {}",
+                "'; drop table t; --",
+                vec![1u64, 2],
+                Option::<u8>::None,
+            )
+            .unwrap(),
+            r"select * from t where name = '\'; drop table t; --' and has([1, 2], id) and x = NULL"
+        );
+    }
+
+    #[test]
+    fn test_sql_format_is_positional() {
+        assert!(sql_format_is_positional(
+            "select {}, {0}, {1:?}, '{{name}}'"
+        ));
+        assert!(!sql_format_is_positional("select {name}"));
+        assert!(!sql_format_is_positional("select {0:>width$}"));
+        assert!(!sql_format_is_positional("select {"));
+    }
+}
@@ -3,7 +3,7 @@ use std::borrow::Cow;
 use chrono_tz::Tz;
 
 use crate::{
-    convert::{unexpected_type, FromSql, ToSql},
+    convert::{FromSql, ToSql},
     types::Type,
 };
 use anyhow::*;
@@ -13,11 +13,15 @@ mod date;
 mod fixed_point;
 mod int256;
 mod ip;
+mod literal;
+mod text;
 
 pub use date::*;
 pub use fixed_point::*;
 pub use int256::*;
 pub use ip::*;
+pub use literal::{sql_format_is_positional, sql_literal};
+pub(crate) use text::{write_quoted_identifier, write_quoted_str};
 
 #[cfg(test)]
 mod tests;
@@ -74,20 +78,56 @@ pub enum Value {
 }
 
 impl Value {
-    pub(crate) fn index_value(&self) -> usize {
-        match self {
+    pub(crate) fn index_value(&self) -> Result<usize> {
+        Ok(match self {
             Value::UInt8(x) => *x as usize,
             Value::UInt16(x) => *x as usize,
             Value::UInt32(x) => *x as usize,
             Value::UInt64(x) => *x as usize,
-            _ => unimplemented!(),
+            _ => return Err(anyhow!("unexpected index value: {:?}", self)),
+        })
+    }
+
+    pub(crate) fn unwrap_array(&self) -> Result<&[Value]> {
+        match self {
+            Value::Array(a) => Ok(&a[..]),
+            _ => Err(anyhow!("expected array value, got {:?}", self)),
         }
     }
 
-    pub(crate) fn unwrap_array(&self) -> &[Value] {
+    /// Approximate size of this value once serialized in a block, used to bound buffered inserts.
+    pub(crate) fn estimated_size(&self) -> usize {
         match self {
-            Value::Array(a) => &a[..],
-            _ => unimplemented!(),
+            Value::Int8(_) | Value::UInt8(_) | Value::Enum8(_) | Value::Null => 1,
+            Value::Int16(_) | Value::UInt16(_) | Value::Enum16(_) | Value::Date(_) => 2,
+            Value::Int32(_)
+            | Value::UInt32(_)
+            | Value::Float32(_)
+            | Value::Decimal32(..)
+            | Value::DateTime(_)
+            | Value::Ipv4(_) => 4,
+            Value::Int64(_)
+            | Value::UInt64(_)
+            | Value::Float64(_)
+            | Value::Decimal64(..)
+            | Value::DateTime64(..) => 8,
+            Value::Int128(_)
+            | Value::UInt128(_)
+            | Value::Decimal128(..)
+            | Value::Uuid(_)
+            | Value::Ipv6(_) => 16,
+            Value::Int256(_) | Value::UInt256(_) | Value::Decimal256(..) => 32,
+            Value::String(x) => x.len() + 1,
+            Value::Array(x) | Value::Tuple(x) => {
+                8 + x.iter().map(|x| x.estimated_size()).sum::<usize>()
+            }
+            Value::Map(keys, values) => {
+                8 + keys
+                    .iter()
+                    .chain(values.iter())
+                    .map(|x| x.estimated_size())
+                    .sum::<usize>()
+            }
         }
     }
 
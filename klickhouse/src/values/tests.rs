@@ -1,5 +1,8 @@
+use std::convert::TryFrom;
+
 use chrono_tz::UTC;
 use indexmap::IndexMap;
+use proptest::prelude::*;
 use uuid::Uuid;
 
 use crate::{
@@ -109,12 +112,12 @@ fn roundtrip_i256() {
 #[test]
 fn roundtrip_f32() {
     const FLOATS: &[f32] = &[
-        (1.0 as f32),
-        (0.0 as f32),
-        (100.0 as f32),
-        (100000.0 as f32),
-        (1000000.0 as f32),
-        (-1000000.0 as f32),
+        1.0_f32,
+        0.0_f32,
+        100.0_f32,
+        100000.0_f32,
+        1000000.0_f32,
+        -1000000.0_f32,
         f32::NAN,
         f32::INFINITY,
         f32::NEG_INFINITY,
@@ -128,12 +131,12 @@ fn roundtrip_f32() {
 #[test]
 fn roundtrip_f64() {
     const FLOATS: &[f64] = &[
-        (1.0 as f64),
-        (0.0 as f64),
-        (100.0 as f64),
-        (100000.0 as f64),
-        (1000000.0 as f64),
-        (-1000000.0 as f64),
+        1.0_f64,
+        0.0_f64,
+        100.0_f64,
+        100000.0_f64,
+        1000000.0_f64,
+        -1000000.0_f64,
         f64::NAN,
         f64::INFINITY,
         f64::NEG_INFINITY,
@@ -481,3 +484,150 @@ fn roundtrip_map() {
         )
     );
 }
+
+#[test]
+fn int256_display_and_parse() {
+    assert_eq!(
+        i256::MIN.to_string(),
+        format!("-{}", i256::MIN.unsigned_abs())
+    );
+    assert_eq!(
+        i256::MAX.to_string(),
+        "57896044618658097711785492504343953926634992332820282019728792003956564819967"
+    );
+    assert_eq!(
+        u256::MAX.to_string(),
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+    assert_eq!(i256::MIN.to_string().parse::<i256>().unwrap(), i256::MIN);
+    assert_eq!(u256::MAX.to_string().parse::<u256>().unwrap(), u256::MAX);
+    assert_eq!("-0".parse::<i256>().unwrap(), i256::ZERO);
+    assert_eq!("+7".parse::<u256>().unwrap(), u256::from(7u8));
+    assert_eq!(format!("{:>5}", i256::from(-12)), "  -12");
+    assert!("".parse::<u256>().is_err());
+    assert!("-1".parse::<u256>().is_err());
+    assert!("1_000".parse::<i256>().is_err());
+    assert!(format!("{}0", u256::MAX).parse::<u256>().is_err());
+    assert!(format!("{}", u256::from(i256::MIN))
+        .parse::<i256>()
+        .is_err());
+}
+
+#[test]
+fn int256_overflow() {
+    assert_eq!(i256::MAX.checked_add(i256::ONE), None);
+    assert_eq!(i256::MIN.checked_sub(i256::ONE), None);
+    assert_eq!(i256::MIN.checked_neg(), None);
+    assert_eq!(i256::MIN.checked_div(-i256::ONE), None);
+    assert_eq!(i256::MIN.checked_rem(-i256::ONE), None);
+    assert_eq!(i256::MIN.checked_mul(-i256::ONE), None);
+    assert_eq!(i256::MIN.checked_mul(i256::ONE), Some(i256::MIN));
+    assert_eq!(i256::ONE.checked_div(i256::ZERO), None);
+    assert_eq!(u256::MAX.checked_add(u256::ONE), None);
+    assert_eq!(u256::ZERO.checked_sub(u256::ONE), None);
+    assert_eq!((u256::ONE << 128).checked_mul(u256::ONE << 128), None);
+    assert_eq!(u256::ONE.checked_shl(256), None);
+    assert_eq!(i256::MIN >> 255, -i256::ONE);
+    assert_eq!(u256::MAX >> 255, u256::ONE);
+    assert_eq!(i256::ONE << 255, i256::MIN);
+    assert!(i256::MIN < i256::ZERO && i256::ZERO < i256::MAX);
+    assert_eq!(u128::try_from(i256::from(u128::MAX)).unwrap(), u128::MAX);
+    assert!(u8::try_from(i256::from(-1)).is_err());
+    assert!(i128::try_from(u256::from(u128::MAX)).is_err());
+    assert!(u256::try_from(-1i8).is_err());
+}
+
+#[test]
+fn fixed_point256_display_and_parse() {
+    let fixed = FixedPoint256::<3>(i256::from(-1500));
+    assert_eq!(fixed.to_string(), "-1.500");
+    assert_eq!(fixed.integer(), i256::from(-1));
+    assert_eq!(fixed.fraction(), i256::from(-500));
+    assert_eq!("-1.5".parse::<FixedPoint256<3>>().unwrap(), fixed);
+    assert_eq!("-1.500".parse::<FixedPoint256<3>>().unwrap(), fixed);
+    assert_eq!(FixedPoint256::<3>(i256::from(5)).to_string(), "0.005");
+    assert_eq!(FixedPoint256::<0>(i256::from(5)).to_string(), "5");
+    assert_eq!(
+        "12".parse::<FixedPoint256<2>>().unwrap().0,
+        i256::from(1200)
+    );
+    assert!("1.2345".parse::<FixedPoint256<3>>().is_err());
+    assert!("1.-5".parse::<FixedPoint256<3>>().is_err());
+    assert!(".".parse::<FixedPoint256<3>>().is_err());
+}
+
+proptest! {
+    #[test]
+    fn int256_matches_i128(a in any::<i64>(), b in any::<i64>(), shift in 0u32..64) {
+        let (x, y) = (i256::from(a), i256::from(b));
+        let (a, b) = (a as i128, b as i128);
+        prop_assert_eq!(x + y, i256::from(a + b));
+        prop_assert_eq!(x - y, i256::from(a - b));
+        prop_assert_eq!(x * y, i256::from(a * b));
+        prop_assert_eq!(x.checked_div(y), a.checked_div(b).map(i256::from));
+        prop_assert_eq!(x.checked_rem(y), a.checked_rem(b).map(i256::from));
+        prop_assert_eq!(x << shift, i256::from(a << shift));
+        prop_assert_eq!(x >> shift, i256::from(a >> shift));
+        prop_assert_eq!(x.cmp(&y), a.cmp(&b));
+        prop_assert_eq!(x.to_string(), a.to_string());
+        prop_assert_eq!(i128::try_from(x).unwrap(), a);
+    }
+
+    #[test]
+    fn uint256_matches_u128(a in any::<u64>(), b in any::<u64>(), shift in 0u32..64) {
+        let (x, y) = (u256::from(a), u256::from(b));
+        let (a, b) = (a as u128, b as u128);
+        prop_assert_eq!(x + y, u256::from(a + b));
+        prop_assert_eq!(x.checked_sub(y), a.checked_sub(b).map(u256::from));
+        prop_assert_eq!(x * y, u256::from(a * b));
+        prop_assert_eq!(x.checked_div(y), a.checked_div(b).map(u256::from));
+        prop_assert_eq!(x.checked_rem(y), a.checked_rem(b).map(u256::from));
+        prop_assert_eq!(x << shift, u256::from(a << shift));
+        prop_assert_eq!(x >> shift, u256::from(a >> shift));
+        prop_assert_eq!(x.to_string(), a.to_string());
+    }
+
+    #[test]
+    fn int256_full_range(a in any::<[u8; 32]>(), b in any::<[u8; 32]>()) {
+        let (x, y) = (i256(a), i256(b));
+        prop_assert_eq!(x.to_string().parse::<i256>().unwrap(), x);
+        prop_assert_eq!(u256(a).to_string().parse::<u256>().unwrap(), u256(a));
+        if let Some(sum) = x.checked_add(y) {
+            prop_assert_eq!(sum - y, x);
+        }
+        if let (Some(quotient), Some(remainder)) = (x.checked_div(y), x.checked_rem(y)) {
+            prop_assert_eq!(quotient * y + remainder, x);
+            prop_assert!(remainder.unsigned_abs() < y.unsigned_abs());
+        }
+        prop_assert_eq!(x < y, x.to_string().parse::<i256>().unwrap() < y);
+    }
+}
+
+#[cfg(feature = "ethnum")]
+proptest! {
+    #[test]
+    fn int256_matches_ethnum(a in any::<[u8; 32]>(), b in any::<[u8; 32]>(), shift in 0u32..256) {
+        let (x, y) = (ethnum::I256::from(i256(a)), ethnum::I256::from(i256(b)));
+        prop_assert_eq!(i256(a).checked_mul(i256(b)).map(ethnum::I256::from), x.checked_mul(y));
+        prop_assert_eq!(i256(a).checked_div(i256(b)).map(ethnum::I256::from), x.checked_div(y));
+        prop_assert_eq!(i256(a).checked_rem(i256(b)).map(ethnum::I256::from), x.checked_rem(y));
+        prop_assert_eq!(ethnum::I256::from(i256(a) >> shift), x >> shift);
+        prop_assert_eq!(i256(a).cmp(&i256(b)), x.cmp(&y));
+        prop_assert_eq!(i256(a).to_string(), x.to_string());
+        let (x, y) = (ethnum::U256::from(u256(a)), ethnum::U256::from(u256(b)));
+        prop_assert_eq!(u256(a).checked_mul(u256(b)).map(ethnum::U256::from), x.checked_mul(y));
+        prop_assert_eq!(u256(a).checked_div(u256(b)).map(ethnum::U256::from), x.checked_div(y));
+        prop_assert_eq!(ethnum::U256::from(u256(a) << shift), x << shift);
+        prop_assert_eq!(u256(a).to_string(), x.to_string());
+    }
+}
+
+#[cfg(feature = "primitive-types")]
+proptest! {
+    #[test]
+    fn uint256_matches_primitive_types(a in any::<[u8; 32]>()) {
+        let x = primitive_types::U256::from(u256(a));
+        prop_assert_eq!(u256::from(x), u256(a));
+        prop_assert_eq!(x.to_string(), u256(a).to_string());
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+
+use crate::{types::Type, Value};
+use anyhow::*;
+
+/// Writes `value` with Clickhouse escape sequences, as in the `TabSeparated` format.
+fn write_escaped_str(value: &str, out: &mut String) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Writes `value` as a single quoted Clickhouse string literal.
+pub(crate) fn write_quoted_str(value: &str, out: &mut String) {
+    out.push('\'');
+    write_escaped_str(value, out);
+    out.push('\'');
+}
+
+/// Writes `name` as a backtick quoted Clickhouse identifier.
+pub(crate) fn write_quoted_identifier(name: &str, out: &mut String) {
+    out.push('`');
+    for c in name.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '`' => out.push_str("\\`"),
+            c => out.push(c),
+        }
+    }
+    out.push('`');
+}
+
+fn write_datetime(tz: Tz, seconds: i64, out: &mut String) {
+    let datetime = Utc.timestamp(seconds, 0).with_timezone(&tz);
+    write!(out, "{}", datetime.format("%Y-%m-%d %H:%M:%S")).unwrap();
+}
+
+pub(super) fn write_decimal(value: String, scale: usize, out: &mut String) {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", &value[..]),
+    };
+    out.push_str(sign);
+    if scale == 0 {
+        out.push_str(digits);
+        return;
+    }
+    let digits = format!("{:0>width$}", digits, width = scale + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale);
+    out.push_str(integer);
+    out.push('.');
+    out.push_str(fraction);
+}
+
+fn write_float(value: f64, out: &mut String) {
+    if value.is_nan() {
+        out.push_str("nan");
+    } else {
+        write!(out, "{}", value).unwrap();
+    }
+}
+
+fn write_enum<T: PartialEq + ToString>(entries: &[(String, T)], index: T, out: &mut String) {
+    match entries.iter().find(|(_, x)| *x == index) {
+        Some((name, _)) => write_escaped_str(name, out),
+        None => out.push_str(&index.to_string()),
+    }
+}
+
+impl Value {
+    /// Writes this value in Clickhouse's escaped text format, as parsed from `TabSeparated` data or query parameters.
+    pub(crate) fn write_escaped(&self, type_: &Type, out: &mut String) -> Result<()> {
+        self.write_text(type_, false, out)
+    }
+
+    fn write_text(&self, type_: &Type, quoted: bool, out: &mut String) -> Result<()> {
+        let quote = |out: &mut String| {
+            if quoted {
+                out.push('\'');
+            }
+        };
+        match (type_, self) {
+            (Type::LowCardinality(inner), value) => return value.write_text(inner, quoted, out),
+            (Type::Nullable(_), Value::Null) => out.push_str(if quoted { "NULL" } else { "\\N" }),
+            (Type::Nullable(inner), value) => return value.write_text(inner, quoted, out),
+            (_, Value::Int8(x)) => write!(out, "{}", x)?,
+            (_, Value::Int16(x)) => write!(out, "{}", x)?,
+            (_, Value::Int32(x)) => write!(out, "{}", x)?,
+            (_, Value::Int64(x)) => write!(out, "{}", x)?,
+            (_, Value::Int128(x)) => write!(out, "{}", x)?,
+            (_, Value::Int256(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt8(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt16(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt32(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt64(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt128(x)) => write!(out, "{}", x)?,
+            (_, Value::UInt256(x)) => write!(out, "{}", x)?,
+            (_, Value::Float32(x)) => write_float(f32::from_bits(*x) as f64, out),
+            (_, Value::Float64(x)) => write_float(f64::from_bits(*x), out),
+            (_, Value::Decimal32(scale, x)) => write_decimal(x.to_string(), *scale, out),
+            (_, Value::Decimal64(scale, x)) => write_decimal(x.to_string(), *scale, out),
+            (_, Value::Decimal128(scale, x)) => write_decimal(x.to_string(), *scale, out),
+            (_, Value::Decimal256(scale, x)) => write_decimal(x.to_string(), *scale, out),
+            (_, Value::String(x)) => {
+                quote(out);
+                write_escaped_str(x, out);
+                quote(out);
+            }
+            (_, Value::Uuid(x)) => {
+                quote(out);
+                write!(out, "{}", x)?;
+                quote(out);
+            }
+            (_, Value::Date(x)) => {
+                let date = NaiveDate::from_ymd(1970, 1, 1) + chrono::Duration::days(x.0 as i64);
+                quote(out);
+                write!(out, "{}", date.format("%Y-%m-%d"))?;
+                quote(out);
+            }
+            (_, Value::DateTime(x)) => {
+                quote(out);
+                write_datetime(x.0, x.1 as i64, out);
+                quote(out);
+            }
+            (_, Value::DateTime64(tz, precision, ticks)) => {
+                let modulus = 10u64.pow(*precision as u32);
+                quote(out);
+                write_datetime(*tz, (ticks / modulus) as i64, out);
+                if *precision > 0 {
+                    write!(out, ".{:0>width$}", ticks % modulus, width = *precision)?;
+                }
+                quote(out);
+            }
+            (Type::Enum8(entries), Value::Enum8(x)) => {
+                quote(out);
+                write_enum(entries, *x, out);
+                quote(out);
+            }
+            (Type::Enum16(entries), Value::Enum16(x)) => {
+                quote(out);
+                write_enum(entries, *x, out);
+                quote(out);
+            }
+            (Type::Array(inner), Value::Array(values)) => {
+                out.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    value.write_text(inner, true, out)?;
+                }
+                out.push(']');
+            }
+            (Type::Tuple(types), Value::Tuple(values)) if types.len() == values.len() => {
+                out.push('(');
+                for (i, (type_, value)) in types.iter().zip(values).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    value.write_text(type_, true, out)?;
+                }
+                out.push(')');
+            }
+            (Type::Map(key_type, value_type), Value::Map(keys, values))
+                if keys.len() == values.len() =>
+            {
+                out.push('{');
+                for (i, (key, value)) in keys.iter().zip(values).enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    key.write_text(key_type, true, out)?;
+                    out.push(':');
+                    value.write_text(value_type, true, out)?;
+                }
+                out.push('}');
+            }
+            (_, Value::Ipv4(x)) => {
+                quote(out);
+                write!(out, "{}", x)?;
+                quote(out);
+            }
+            (_, Value::Ipv6(x)) => {
+                quote(out);
+                write!(out, "{}", x)?;
+                quote(out);
+            }
+            (type_, value) => {
+                return Err(anyhow!(
+                    "could not format value '{:?}' as type '{}'",
+                    value,
+                    type_
+                ))
+            }
+        }
+        Ok(())
+    }
+}
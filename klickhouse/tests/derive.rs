@@ -0,0 +1,488 @@
+use klickhouse::{DateTime, DateTime64, Row, Type, Value};
+
+#[derive(Row, Debug, Default, PartialEq, Clone)]
+pub struct Header {
+    id: u64,
+    source: String,
+}
+
+#[derive(Row, Debug, Default, PartialEq, Clone)]
+pub struct Geo {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Row, Debug, Default, PartialEq, Clone)]
+pub struct Event {
+    #[klickhouse(flatten)]
+    header: Header,
+    kind: String,
+    #[klickhouse(flatten(prefix = "geo_"))]
+    geo: Geo,
+    #[klickhouse(flatten(prefix = "origin_"))]
+    origin: Geo,
+}
+
+fn event() -> Event {
+    Event {
+        header: Header {
+            id: 5,
+            source: "web".to_string(),
+        },
+        kind: "click".to_string(),
+        geo: Geo { lat: 1.5, lon: 2.5 },
+        origin: Geo { lat: 3.5, lon: 4.5 },
+    }
+}
+
+fn types(name: &str) -> Type {
+    match name {
+        "id" => Type::UInt64,
+        "source" | "kind" => Type::String,
+        _ => Type::Float64,
+    }
+}
+
+#[test]
+fn test_flatten_serialize() {
+    let row = event().serialize_row().unwrap();
+    assert_eq!(
+        row.iter().map(|(name, _)| *name).collect::<Vec<_>>(),
+        vec![
+            "id",
+            "source",
+            "kind",
+            "geo_lat",
+            "geo_lon",
+            "origin_lat",
+            "origin_lon"
+        ]
+    );
+    assert_eq!(row[3].1, Value::Float64(1.5f64.to_bits()));
+}
+
+#[test]
+fn test_flatten_roundtrip() {
+    let row = event().serialize_row().unwrap();
+    let types = row.iter().map(|(name, _)| types(name)).collect::<Vec<_>>();
+    let columns = row
+        .into_iter()
+        .zip(types.iter())
+        .map(|((name, value), type_)| (name, type_, value))
+        .rev()
+        .collect();
+    assert_eq!(Event::deserialize_row(columns).unwrap(), event());
+}
+
+#[test]
+fn test_flatten_missing() {
+    let (id, kind) = (types("id"), types("kind"));
+    let columns = vec![
+        ("id", &id, Value::UInt64(5)),
+        ("kind", &kind, Value::String("click".to_string())),
+    ];
+    assert_eq!(
+        Event::deserialize_row(columns).unwrap_err().to_string(),
+        "missing field 'source' from struct"
+    );
+}
+
+#[derive(Row, Debug, Default)]
+#[klickhouse(order_by = "(id, created)", partition_by = "toYYYYMM(created)")]
+pub struct Visit {
+    id: u64,
+    #[klickhouse(type = "LowCardinality(String)", codec = "ZSTD(3)")]
+    page: String,
+    referrer: Option<String>,
+    tags: Vec<String>,
+    #[klickhouse(default = "now()")]
+    created: DateTime,
+    elapsed: DateTime64<3>,
+    #[klickhouse(flatten(prefix = "geo_"))]
+    geo: Geo,
+}
+
+// deriving Row does not require a schema for each field
+#[derive(Row, Debug)]
+pub struct Opaque {
+    value: Value,
+}
+
+#[test]
+fn test_schema() {
+    assert_eq!(
+        Visit::clickhouse_schema(),
+        vec![
+            ("id", Type::UInt64),
+            ("page", Type::LowCardinality(Box::new(Type::String))),
+            ("referrer", Type::Nullable(Box::new(Type::String))),
+            ("tags", Type::Array(Box::new(Type::String))),
+            ("created", Type::DateTime(chrono_tz::UTC)),
+            ("elapsed", Type::DateTime64(3, chrono_tz::UTC)),
+            ("geo_lat", Type::Float64),
+            ("geo_lon", Type::Float64),
+        ]
+    );
+    let opaque = Opaque {
+        value: Value::UInt8(1),
+    };
+    assert_eq!(opaque.serialize_row().unwrap().len(), 1);
+}
+
+#[derive(Row, Debug)]
+pub struct Status {
+    #[klickhouse(type = "Enum8('active' = 1, 'disabled' = 2)")]
+    status: String,
+}
+
+#[test]
+fn test_schema_type_attribute() {
+    assert_eq!(
+        Status::create_table_sql("statuses", "Memory"),
+        "CREATE TABLE statuses (
+    `status` Enum8('active' = 1,'disabled' = 2)
+) ENGINE = Memory"
+    );
+}
+
+#[test]
+fn test_create_table_sql() {
+    assert_eq!(
+        Visit::create_table_sql("analytics.visits", "MergeTree"),
+        "CREATE TABLE analytics.visits (
+    `id` UInt64,
+    `page` LowCardinality(String) CODEC(ZSTD(3)),
+    `referrer` Nullable(String),
+    `tags` Array(String),
+    `created` DateTime('UTC') DEFAULT now(),
+    `elapsed` DateTime64(3,'UTC'),
+    `geo_lat` Float64,
+    `geo_lon` Float64
+) ENGINE = MergeTree
+PARTITION BY toYYYYMM(created)
+ORDER BY (id, created)"
+    );
+    assert_eq!(
+        Event::create_table_sql("events", "Memory"),
+        "CREATE TABLE events (
+    `id` UInt64,
+    `source` String,
+    `kind` String,
+    `geo_lat` Float64,
+    `geo_lon` Float64,
+    `origin_lat` Float64,
+    `origin_lon` Float64
+) ENGINE = Memory"
+    );
+}
+
+#[derive(Row, Debug, Default)]
+#[klickhouse(rename_all = "camelCase")]
+pub struct Renamed {
+    user_id: u64,
+    #[klickhouse(rename = "display name")]
+    name: String,
+    #[klickhouse(skip_deserializing)]
+    cached: u64,
+}
+
+#[test]
+fn test_column_names() {
+    assert_eq!(Renamed::COLUMN_NAMES, &["userId", "display name"]);
+    assert_eq!(
+        Renamed::column_names().unwrap(),
+        Renamed::COLUMN_NAMES.to_vec()
+    );
+    assert_eq!(Renamed::default().serialize_row().unwrap().len(), 3);
+    assert_eq!(
+        Event::column_names().unwrap(),
+        vec![
+            "id",
+            "source",
+            "kind",
+            "geo_lat",
+            "geo_lon",
+            "origin_lat",
+            "origin_lon"
+        ]
+    );
+    assert_eq!(<(u64, String)>::column_names(), None);
+}
+
+#[test]
+fn test_expected_columns() {
+    let expected = Event::expected_columns().unwrap();
+    assert!(!expected.deny_unknown);
+    assert_eq!(
+        expected
+            .columns
+            .iter()
+            .map(|column| column.name)
+            .collect::<Vec<_>>(),
+        Event::column_names().unwrap()
+    );
+    let (uint64, float64, nullable) = (
+        Type::UInt64,
+        Type::Float64,
+        Type::Nullable(Box::new(Type::Float64)),
+    );
+    assert_eq!(
+        expected.diff(vec![
+            ("id", &uint64),
+            ("kind", &uint64),
+            ("geo_lat", &float64),
+            ("geo_lon", &nullable),
+            ("origin_lat", &float64),
+            ("origin_lon", &float64),
+            ("other", &float64),
+        ]),
+        vec![
+            "missing column `source`",
+            "column `kind` has type UInt64, expected String",
+            "column `geo_lon` has type Nullable(Float64), expected Float64",
+        ]
+    );
+}
+
+#[derive(klickhouse::ToSql, klickhouse::FromSql, Debug, Default, PartialEq, Clone, Copy)]
+#[klickhouse(transparent)]
+pub struct UserId(u64);
+
+#[derive(klickhouse::ToSql, klickhouse::FromSql, Debug, Default, PartialEq, Clone)]
+#[klickhouse(transparent)]
+pub struct Email {
+    address: String,
+}
+
+#[derive(klickhouse::ToSql, klickhouse::FromSql, Debug, Default, PartialEq, Clone)]
+pub struct Point {
+    x: f64,
+    y: f64,
+}
+
+#[derive(klickhouse::ToSql, klickhouse::FromSql, Debug, PartialEq, Clone)]
+#[klickhouse(into = "String", try_from = "String")]
+pub struct Domain(String);
+
+impl From<Domain> for String {
+    fn from(domain: Domain) -> String {
+        domain.0
+    }
+}
+
+impl std::convert::TryFrom<String> for Domain {
+    type Error = klickhouse::Error;
+
+    fn try_from(domain: String) -> klickhouse::Result<Domain> {
+        if !domain.contains('.') {
+            return Err(klickhouse::Error::msg(format!(
+                "invalid domain: {}",
+                domain
+            )));
+        }
+        Ok(Domain(domain))
+    }
+}
+
+#[derive(Row, Debug, PartialEq, Clone)]
+pub struct Account {
+    id: UserId,
+    email: Email,
+    home: Point,
+    domain: Domain,
+}
+
+#[test]
+fn test_expected_columns_validating() {
+    // `Domain` rejects strings without a dot, which must not fail the check of the column type
+    let expected = Account::expected_columns().unwrap();
+    let (uint64, string) = (Type::UInt64, Type::String);
+    let point = Type::Tuple(vec![Type::Float64, Type::Float64]);
+    assert!(expected
+        .diff(vec![
+            ("id", &uint64),
+            ("email", &string),
+            ("home", &point),
+            ("domain", &string),
+        ])
+        .is_empty());
+    assert_eq!(
+        expected.diff(vec![
+            ("id", &string),
+            ("email", &string),
+            ("home", &Type::Tuple(vec![Type::Float64])),
+            ("domain", &uint64),
+        ]),
+        vec![
+            "column `id` has type String, which cannot be converted to `derive::UserId`",
+            "column `home` has type Tuple(Float64), which cannot be converted to `derive::Point`",
+            "column `domain` has type UInt64, which cannot be converted to `derive::Domain`",
+        ]
+    );
+}
+
+#[test]
+fn test_derive_sql() {
+    use klickhouse::{FromSql, ToSql};
+
+    let account = Account {
+        id: UserId(7),
+        email: Email {
+            address: "a@b.c".to_string(),
+        },
+        home: Point { x: 1.0, y: 2.0 },
+        domain: Domain("b.c".to_string()),
+    };
+    let row = account.clone().serialize_row().unwrap();
+    assert_eq!(
+        row,
+        vec![
+            ("id", Value::UInt64(7)),
+            ("email", Value::String("a@b.c".to_string())),
+            (
+                "home",
+                Value::Tuple(vec![
+                    Value::Float64(1.0f64.to_bits()),
+                    Value::Float64(2.0f64.to_bits())
+                ])
+            ),
+            ("domain", Value::String("b.c".to_string())),
+        ]
+    );
+
+    let types = [
+        Type::UInt64,
+        Type::String,
+        Type::Tuple(vec![
+            Type::Float64,
+            Type::LowCardinality(Box::new(Type::Float64)),
+        ]),
+        Type::String,
+    ];
+    let columns = row
+        .into_iter()
+        .zip(types.iter())
+        .map(|((name, value), type_)| (name, type_, value))
+        .collect();
+    assert_eq!(Account::deserialize_row(columns).unwrap(), account);
+
+    assert_eq!(
+        Point::from_sql(&types[2], Value::Tuple(vec![Value::Float64(0)]))
+            .unwrap_err()
+            .to_string(),
+        "unexpected tuple length, got 1 expecting 2"
+    );
+    assert_eq!(
+        Point::from_sql(&Type::Float64, Value::Float64(0))
+            .unwrap_err()
+            .to_string(),
+        "unexpected type: Float64"
+    );
+    assert_eq!(
+        Domain::from_sql(&Type::String, "localhost".to_sql().unwrap())
+            .unwrap_err()
+            .to_string(),
+        "invalid domain: localhost"
+    );
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+fn serialize_upper(value: String) -> klickhouse::Result<Value> {
+    Ok(Value::String(value.to_uppercase()))
+}
+
+fn deserialize_lower(type_: &Type, value: Value) -> klickhouse::Result<String> {
+    use klickhouse::FromSql;
+    Ok(String::from_sql(type_, value)?.to_lowercase())
+}
+
+mod csv {
+    use klickhouse::{FromSql, Result, ToSql, Type, Value};
+
+    pub fn to_sql(value: Vec<String>) -> Result<Value> {
+        value.join(",").to_sql()
+    }
+
+    pub fn from_sql(type_: &Type, value: Value) -> Result<Vec<String>> {
+        Ok(String::from_sql(type_, value)?
+            .split(',')
+            .filter(|x| !x.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+}
+
+#[derive(Row, Debug, PartialEq)]
+pub struct Job {
+    id: u64,
+    #[klickhouse(skip_serializing_if = "is_zero", default)]
+    priority: u32,
+    #[klickhouse(default = "default_retries")]
+    retries: u32,
+    #[klickhouse(
+        serialize_with = "serialize_upper",
+        deserialize_with = "deserialize_lower"
+    )]
+    queue: String,
+    #[klickhouse(with = "csv", default)]
+    labels: Vec<String>,
+}
+
+#[test]
+fn test_field_functions() {
+    let job = Job {
+        id: 1,
+        priority: 0,
+        retries: 5,
+        queue: "Main".to_string(),
+        labels: vec!["a".to_string(), "b".to_string()],
+    };
+    assert_eq!(
+        job.serialize_row().unwrap(),
+        vec![
+            ("id", Value::UInt64(1)),
+            ("retries", Value::UInt32(5)),
+            ("queue", Value::String("MAIN".to_string())),
+            ("labels", Value::String("a,b".to_string())),
+        ]
+    );
+
+    let columns = vec![
+        ("id", &Type::UInt64, Value::UInt64(2)),
+        ("queue", &Type::String, Value::String("BATCH".to_string())),
+    ];
+    assert_eq!(
+        Job::deserialize_row(columns).unwrap(),
+        Job {
+            id: 2,
+            priority: 0,
+            retries: 3,
+            queue: "batch".to_string(),
+            labels: vec![],
+        }
+    );
+
+    let columns = vec![
+        ("id", &Type::UInt64, Value::UInt64(2)),
+        ("priority", &Type::UInt32, Value::UInt32(7)),
+        ("queue", &Type::String, Value::String("BATCH".to_string())),
+        ("labels", &Type::String, Value::String("x".to_string())),
+    ];
+    let job = Job::deserialize_row(columns).unwrap();
+    assert_eq!(job.priority, 7);
+    assert_eq!(job.labels, vec!["x".to_string()]);
+
+    // columns without a default are required
+    let columns = vec![("id", &Type::UInt64, Value::UInt64(2))];
+    assert_eq!(
+        Job::deserialize_row(columns).unwrap_err().to_string(),
+        "missing field 'queue' from struct"
+    );
+}
@@ -0,0 +1,314 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::FutureExt;
+use indexmap::IndexMap;
+use klickhouse::{
+    testing::{MockResponse, MockServer},
+    Block, BlockInfo, InsertErrorPolicy, InsertStats, InserterOptions, Progress, Type,
+};
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct MockRow {
+    id: u32,
+    name: String,
+}
+
+fn mock_row(id: u32) -> MockRow {
+    MockRow {
+        id,
+        name: id.to_string(),
+    }
+}
+
+fn insert_handler(query: &str) -> Vec<MockResponse> {
+    insert_handler_with_progress(query, 0)
+}
+
+fn insert_handler_with_progress(query: &str, progress: usize) -> Vec<MockResponse> {
+    if query.starts_with("insert") {
+        let mut column_types = IndexMap::new();
+        column_types.insert("id".to_string(), Type::UInt32);
+        column_types.insert("name".to_string(), Type::String);
+        let mut column_data = IndexMap::new();
+        column_data.insert("id".to_string(), vec![]);
+        column_data.insert("name".to_string(), vec![]);
+        let mut responses = vec![MockResponse::Data(Block {
+            info: BlockInfo::default(),
+            rows: 0,
+            column_types,
+            column_data,
+        })];
+        for _ in 0..progress {
+            responses.push(MockResponse::Progress(Progress {
+                read_rows: 0,
+                read_bytes: 0,
+                new_total_rows_to_read: 0,
+                new_written_rows: None,
+                new_written_bytes: None,
+            }));
+        }
+        responses.push(MockResponse::ReceiveData);
+        responses.push(MockResponse::EndOfStream);
+        responses
+    } else {
+        vec![MockResponse::EndOfStream]
+    }
+}
+
+const QUERY: &str = "insert into test format native";
+
+#[tokio::test]
+async fn test_inserter_max_rows() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(
+        QUERY,
+        InserterOptions {
+            max_rows: 4,
+            max_latency: Duration::from_secs(60),
+            ..Default::default()
+        },
+    );
+    for id in 0..10 {
+        inserter.write(mock_row(id)).await.unwrap();
+    }
+    let stats = inserter.close().await.unwrap();
+    assert_eq!(stats.rows, 10);
+    assert_eq!(stats.blocks, 3);
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].query, QUERY);
+    let sizes = queries[0]
+        .blocks
+        .iter()
+        .map(|block| block.rows)
+        .collect::<Vec<_>>();
+    assert_eq!(sizes, vec![4, 4, 2]);
+}
+
+#[tokio::test]
+async fn test_inserter_rotate() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(
+        QUERY,
+        InserterOptions {
+            max_rows: 2,
+            rotate_period: Some(Duration::ZERO),
+            ..Default::default()
+        },
+    );
+    for id in 0..6 {
+        inserter.write(mock_row(id)).await.unwrap();
+    }
+    inserter.close().await.unwrap();
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 3);
+    for query in &queries {
+        assert_eq!(query.blocks.len(), 1);
+        assert_eq!(query.blocks[0].rows, 2);
+    }
+}
+
+#[tokio::test]
+async fn test_inserter_latency() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let flushes = Arc::new(Mutex::new(Vec::<InsertStats>::new()));
+    let flushes_ref = flushes.clone();
+    let inserter = client.inserter(
+        QUERY,
+        InserterOptions {
+            max_latency: Duration::from_millis(50),
+            on_flush: Some(Arc::new(move |stats| {
+                flushes_ref.lock().unwrap().push(*stats)
+            })),
+            ..Default::default()
+        },
+    );
+    inserter.write(mock_row(1)).await.unwrap();
+    inserter.write(mock_row(2)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    {
+        let flushes = flushes.lock().unwrap();
+        assert_eq!(flushes.len(), 1);
+        assert_eq!(flushes[0].rows, 2);
+        assert!(flushes[0].bytes > 0);
+    }
+
+    inserter.write(mock_row(3)).await.unwrap();
+    let stats = inserter.close().await.unwrap();
+    assert_eq!(stats.rows, 3);
+    assert_eq!(stats.blocks, 2);
+    assert_eq!(flushes.lock().unwrap().len(), 2);
+    // the idle insert was ended while waiting, the last row starts a new one
+    let queries = server.queries();
+    assert_eq!(queries.len(), 2);
+    assert_eq!(queries[0].blocks.len(), 1);
+    assert_eq!(queries[1].blocks.len(), 1);
+}
+
+#[tokio::test]
+async fn test_inserter_server_error() {
+    let server = MockServer::start(|_| {
+        vec![MockResponse::Exception {
+            code: 60,
+            name: "DB::Exception".to_string(),
+            message: "Table default.test doesn't exist".to_string(),
+        }]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(QUERY, InserterOptions::default());
+    inserter.write(mock_row(1)).await.unwrap();
+    assert!(inserter.flush().await.is_err());
+    assert!(inserter.write(mock_row(2)).await.is_err());
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct WrongRow {
+    id: String,
+    name: String,
+}
+
+#[tokio::test]
+async fn test_inserter_error_policy() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(
+        QUERY,
+        InserterOptions {
+            error_policy: InsertErrorPolicy::Skip,
+            ..Default::default()
+        },
+    );
+    inserter
+        .write(WrongRow {
+            id: "1".to_string(),
+            name: "1".to_string(),
+        })
+        .await
+        .unwrap();
+    let stats = inserter.close().await.unwrap();
+    assert_eq!(stats.rows, 0);
+    assert_eq!(stats.skipped, 1);
+
+    let inserter = client.inserter(QUERY, InserterOptions::default());
+    inserter
+        .write(WrongRow {
+            id: "1".to_string(),
+            name: "1".to_string(),
+        })
+        .await
+        .unwrap();
+    assert!(inserter.close().await.is_err());
+
+    // the failed insert was ended, so the connection is still usable
+    client.execute("select 1").await.unwrap();
+    let queries = server.queries();
+    assert_eq!(queries.len(), 3);
+    assert_eq!(queries[2].query, "select 1");
+}
+
+#[tokio::test]
+async fn test_inserter_exclusive() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(QUERY, InserterOptions::default());
+    inserter.write(mock_row(1)).await.unwrap();
+    inserter.flush().await.unwrap();
+
+    // queries on the same connection wait for the open insert to end
+    let mut select = tokio::spawn({
+        let client = client.clone();
+        async move { client.execute("select 1").await }
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!((&mut select).now_or_never().is_none());
+
+    inserter.write(mock_row(2)).await.unwrap();
+    let stats = inserter.close().await.unwrap();
+    assert_eq!(stats.rows, 2);
+    select.await.unwrap().unwrap();
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 2);
+    assert_eq!(queries[0].query, QUERY);
+    assert_eq!(queries[0].blocks.len(), 2);
+    assert_eq!(queries[1].query, "select 1");
+}
+
+#[tokio::test]
+async fn test_inserter_progress() {
+    // more progress packets than the response channel holds
+    let server = MockServer::start(|query| insert_handler_with_progress(query, 100))
+        .await
+        .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let inserter = client.inserter(QUERY, InserterOptions::default());
+    inserter.write(mock_row(1)).await.unwrap();
+    inserter.flush().await.unwrap();
+    // progress arrives while the insert is idle
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    inserter.write(mock_row(2)).await.unwrap();
+    let flushed = tokio::time::timeout(Duration::from_secs(5), inserter.flush()).await;
+    flushed.unwrap().unwrap();
+    let stats = inserter.close().await.unwrap();
+    assert_eq!(stats.rows, 2);
+}
+
+#[tokio::test]
+async fn test_inserter_idle() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    for options in [
+        InserterOptions {
+            max_latency: Duration::from_millis(100),
+            ..Default::default()
+        },
+        InserterOptions {
+            max_latency: Duration::from_secs(60),
+            rotate_period: Some(Duration::from_millis(100)),
+            ..Default::default()
+        },
+    ] {
+        let inserter = client.inserter(QUERY, options);
+        inserter.write(mock_row(1)).await.unwrap();
+        inserter.flush().await.unwrap();
+
+        // the idle insert is ended, so queries on the same connection still run
+        let select = tokio::time::timeout(Duration::from_secs(5), client.execute("select 1"));
+        select.await.unwrap().unwrap();
+
+        inserter.write(mock_row(2)).await.unwrap();
+        let stats = inserter.close().await.unwrap();
+        assert_eq!(stats.rows, 2);
+    }
+
+    let queries = server
+        .queries()
+        .into_iter()
+        .map(|query| (query.query, query.blocks.len()))
+        .collect::<Vec<_>>();
+    let expected = [(QUERY, 1), ("select 1", 0), (QUERY, 1)];
+    let expected = expected
+        .iter()
+        .chain(expected.iter())
+        .map(|(query, blocks)| (query.to_string(), *blocks))
+        .collect::<Vec<_>>();
+    assert_eq!(queries, expected);
+}
@@ -0,0 +1,1414 @@
+use futures::StreamExt;
+use indexmap::IndexMap;
+use klickhouse::{
+    protocol::TableColumns,
+    testing::{MockResponse, MockServer},
+    Block, BlockInfo, ClientOptions, ColumnInfo, CompressionMethod, DefaultKind, InsertErrorPolicy,
+    Progress, Row, Type, Value,
+};
+use std::sync::{Arc, Mutex};
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct MockRow {
+    id: u32,
+    name: String,
+}
+
+fn mock_block(rows: &[(u32, &str)]) -> Block {
+    let mut column_types = IndexMap::new();
+    column_types.insert("id".to_string(), Type::UInt32);
+    column_types.insert("name".to_string(), Type::String);
+    let mut column_data = IndexMap::new();
+    column_data.insert(
+        "id".to_string(),
+        rows.iter().map(|(id, _)| Value::UInt32(*id)).collect(),
+    );
+    column_data.insert(
+        "name".to_string(),
+        rows.iter()
+            .map(|(_, name)| Value::String(name.to_string()))
+            .collect(),
+    );
+    Block {
+        info: BlockInfo::default(),
+        rows: rows.len() as u64,
+        column_types,
+        column_data,
+    }
+}
+
+#[tokio::test]
+async fn test_mock_select() {
+    let server = MockServer::start(|_| {
+        vec![
+            MockResponse::Data(mock_block(&[])),
+            MockResponse::Data(mock_block(&[(1, "one"), (2, "two")])),
+            MockResponse::Data(mock_block(&[(3, "three")])),
+            MockResponse::EndOfStream,
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query::<MockRow>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        rows,
+        vec![
+            MockRow {
+                id: 1,
+                name: "one".to_string()
+            },
+            MockRow {
+                id: 2,
+                name: "two".to_string()
+            },
+            MockRow {
+                id: 3,
+                name: "three".to_string()
+            },
+        ]
+    );
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].query, "select id, name from test");
+}
+
+#[tokio::test]
+async fn test_mock_insert() {
+    let server = MockServer::start(|query| {
+        if query.starts_with("insert") {
+            vec![
+                MockResponse::Data(mock_block(&[])),
+                MockResponse::ReceiveData,
+                MockResponse::EndOfStream,
+            ]
+        } else {
+            vec![MockResponse::EndOfStream]
+        }
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    client
+        .insert_native_block(
+            "insert into test format native",
+            vec![
+                MockRow {
+                    id: 1,
+                    name: "one".to_string(),
+                },
+                MockRow {
+                    id: 2,
+                    name: "two".to_string(),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+    // the insert is fully processed by the server once a following query completes
+    let rows = client
+        .query_raw("select 1")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(rows.is_empty());
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 2);
+    assert_eq!(queries[0].query, "insert into test format native");
+    assert_eq!(queries[0].blocks.len(), 1);
+    let block = &queries[0].blocks[0];
+    assert_eq!(block.rows, 2);
+    assert_eq!(
+        block.column_data["id"],
+        vec![Value::UInt32(1), Value::UInt32(2)]
+    );
+    assert_eq!(
+        block.column_data["name"],
+        vec![
+            Value::String("one".to_string()),
+            Value::String("two".to_string())
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_mock_exception() {
+    let server = MockServer::start(|_| {
+        vec![
+            MockResponse::Data(mock_block(&[(1, "one")])),
+            MockResponse::Exception {
+                code: 60,
+                name: "DB::Exception".to_string(),
+                message: "Table default.missing doesn't exist".to_string(),
+            },
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let blocks = client
+        .query_raw("select * from missing")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(blocks.len(), 1);
+
+    // the exception ends the stream of deserialized rows
+    let rows = client
+        .query::<MockRow>("select * from missing")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].is_ok());
+    assert!(rows[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("Table default.missing doesn't exist"));
+
+    // the connection remains usable after an exception
+    assert_eq!(
+        client
+            .query_collect::<MockRow>("select * from missing")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "server error 60 DB::Exception: Table default.missing doesn't exist\n"
+    );
+}
+
+fn scalar_handler(query: &str) -> Vec<MockResponse> {
+    let rows: &[(u32, &str)] = match query {
+        "select none" => &[],
+        "select one" => &[(1, "one")],
+        _ => &[(1, "one"), (2, "two")],
+    };
+    vec![
+        MockResponse::Data(mock_block(&[])),
+        MockResponse::Data(mock_block(rows)),
+        MockResponse::EndOfStream,
+    ]
+}
+
+#[tokio::test]
+async fn test_mock_query_one() {
+    let server = MockServer::start(scalar_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let one = MockRow {
+        id: 1,
+        name: "one".to_string(),
+    };
+    assert_eq!(
+        client.query_one::<MockRow>("select one").await.unwrap(),
+        one
+    );
+    assert_eq!(
+        client
+            .query_one::<MockRow>("select none")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "query returned no rows"
+    );
+    assert_eq!(
+        client
+            .query_one::<MockRow>("select two")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "query returned more than one row"
+    );
+
+    assert_eq!(
+        client.query_opt::<MockRow>("select one").await.unwrap(),
+        Some(one)
+    );
+    assert_eq!(
+        client.query_opt::<MockRow>("select none").await.unwrap(),
+        None
+    );
+    assert!(client.query_opt::<MockRow>("select two").await.is_err());
+
+    assert_eq!(
+        client
+            .query_collect::<(u32, String)>("select two")
+            .await
+            .unwrap(),
+        vec![(1, "one".to_string()), (2, "two".to_string())]
+    );
+    assert!(client
+        .query_collect::<MockRow>("select none")
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_mock_query_scalar() {
+    let server = MockServer::start(|_| {
+        let mut column_types = IndexMap::new();
+        column_types.insert("count()".to_string(), Type::UInt64);
+        let mut column_data = IndexMap::new();
+        column_data.insert("count()".to_string(), vec![Value::UInt64(42)]);
+        vec![
+            MockResponse::Data(Block {
+                info: BlockInfo::default(),
+                rows: 1,
+                column_types,
+                column_data,
+            }),
+            MockResponse::EndOfStream,
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    assert_eq!(
+        client
+            .query_scalar::<u64>("select count() from test")
+            .await
+            .unwrap(),
+        42
+    );
+    assert!(client
+        .query_scalar::<String>("select count() from test")
+        .await
+        .is_err());
+
+    let server = MockServer::start(scalar_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+    assert_eq!(
+        client
+            .query_scalar::<u32>("select one")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "unexpected column count, got 2 expecting 1"
+    );
+}
+
+#[tokio::test]
+async fn test_mock_query_with() {
+    let server = MockServer::start(scalar_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let name = "it's".to_string();
+    let rows = client
+        .query_with::<MockRow>(
+            "select one where id = {id:UInt64} and name = {name:String} and tag in {tags:Array(String)}",
+            klickhouse::params! {
+                "id" => 5u64,
+                "name" => name,
+                "tags" => vec!["a", "b"],
+            },
+        )
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        server.queries()[0].parameters,
+        vec![
+            ("id".to_string(), "'5'".to_string()),
+            ("name".to_string(), r"'it\\\'s'".to_string()),
+            ("tags".to_string(), r"'[\'a\',\'b\']'".to_string()),
+        ]
+    );
+
+    assert_eq!(
+        client
+            .query_with::<MockRow>("select {id:UInt64}", klickhouse::params! {})
+            .await
+            .err()
+            .unwrap()
+            .to_string(),
+        "missing parameter `id`"
+    );
+    assert_eq!(server.queries().len(), 1);
+}
+
+#[tokio::test]
+async fn test_mock_query_with_old_server() {
+    let server = MockServer::start_with_revision(54458, scalar_handler)
+        .await
+        .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query_with::<MockRow>("select {id:UInt64}", klickhouse::params! { "id" => 5u64 })
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        rows[0].as_ref().unwrap_err().to_string(),
+        "server does not support query parameters"
+    );
+    // nothing was sent for the failed query, and the connection remains usable
+    let rows = client.query_collect::<MockRow>("select one").await.unwrap();
+    assert_eq!(rows.len(), 1);
+    let queries = server.queries();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].query, "select one");
+}
+
+#[tokio::test]
+async fn test_mock_select_builder() {
+    let server = MockServer::start(scalar_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .select::<MockRow>("db.my`table")
+        .filter("id > {id:UInt32}")
+        .filter("name != ''")
+        .order_by("id")
+        .limit(10)
+        .param("id", 1u32)
+        .fetch_all()
+        .await
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    let queries = server.queries();
+    assert_eq!(
+        queries[0].query,
+        "SELECT `id`, `name` FROM `db`.`my\\`table` WHERE (id > {id:UInt32}) AND (name != '') ORDER BY id LIMIT 10"
+    );
+    assert_eq!(
+        queries[0].parameters,
+        vec![("id".to_string(), "'1'".to_string())]
+    );
+
+    assert_eq!(
+        client
+            .select::<(u32, String)>("test")
+            .sql()
+            .unwrap_err()
+            .to_string(),
+        "cannot select positional rows by column name"
+    );
+}
+
+#[derive(klickhouse::RowRef, Debug, PartialEq)]
+pub struct BorrowedRow<'a> {
+    id: u32,
+    name: &'a str,
+    #[klickhouse(skip)]
+    skipped: Option<&'a str>,
+}
+
+#[derive(klickhouse::RowRef, Debug, PartialEq)]
+#[klickhouse(deny_unknown_fields)]
+pub struct BorrowedBytes<'a> {
+    #[klickhouse(default)]
+    id: u64,
+    name: std::borrow::Cow<'a, [u8]>,
+}
+
+#[tokio::test]
+async fn test_mock_query_blocks() {
+    let server = MockServer::start(|_| {
+        vec![
+            MockResponse::Data(mock_block(&[])),
+            MockResponse::Data(mock_block(&[(1, "one"), (2, "two")])),
+            MockResponse::Data(mock_block(&[(3, "three")])),
+            MockResponse::EndOfStream,
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let blocks = client
+        .query_blocks("select id, name from test")
+        .await
+        .unwrap()
+        .map(|block| block.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(blocks.len(), 2);
+    let rows = blocks
+        .iter()
+        .flat_map(|block| block.deserialize_rows::<BorrowedRow>())
+        .map(|row| row.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        rows,
+        vec![
+            BorrowedRow {
+                id: 1,
+                name: "one",
+                skipped: None,
+            },
+            BorrowedRow {
+                id: 2,
+                name: "two",
+                skipped: None,
+            },
+            BorrowedRow {
+                id: 3,
+                name: "three",
+                skipped: None,
+            },
+        ]
+    );
+    let name = &blocks[0].column_data["name"][1];
+    assert!(std::ptr::eq(
+        rows[1].name,
+        match name {
+            Value::String(x) => x.as_str(),
+            _ => unreachable!(),
+        }
+    ));
+
+    let tuples = blocks[1]
+        .deserialize_rows::<(u32, &str)>()
+        .collect::<klickhouse::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(tuples, vec![(3, "three")]);
+
+    assert_eq!(
+        blocks[1]
+            .deserialize_rows::<BorrowedBytes>()
+            .next()
+            .unwrap()
+            .unwrap_err()
+            .to_string(),
+        "unexpected type: UInt32"
+    );
+}
+
+#[tokio::test]
+async fn test_mock_execute() {
+    let progress = |written_rows| Progress {
+        read_rows: 0,
+        read_bytes: 0,
+        new_total_rows_to_read: 0,
+        new_written_rows: Some(written_rows),
+        new_written_bytes: Some(written_rows * 8),
+    };
+    let server = MockServer::start(move |query| {
+        if query.starts_with("drop") {
+            return vec![MockResponse::Exception {
+                code: 60,
+                name: "DB::Exception".to_string(),
+                message: "Table default.missing doesn't exist".to_string(),
+            }];
+        }
+        vec![
+            MockResponse::Progress(progress(3)),
+            MockResponse::Progress(progress(4)),
+            MockResponse::EndOfStream,
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    assert_eq!(
+        client
+            .execute("insert into test select * from other")
+            .await
+            .unwrap(),
+        7
+    );
+    assert!(client.execute("drop table missing").await.is_err());
+    assert_eq!(
+        client.execute("create table test2 as test").await.unwrap(),
+        7
+    );
+}
+
+/// Row which fails to serialize for multiples of 3, and has a mistyped id for multiples of 5.
+struct FallibleRow(u32);
+
+impl Row for FallibleRow {
+    fn deserialize_row(_map: Vec<(&str, &Type, Value)>) -> klickhouse::Result<Self> {
+        unimplemented!()
+    }
+
+    fn serialize_row(self) -> klickhouse::Result<Vec<(&'static str, Value)>> {
+        if self.0.is_multiple_of(3) {
+            return Err(klickhouse::Error::msg("unserializable"));
+        }
+        let id = if self.0.is_multiple_of(5) {
+            Value::String(self.0.to_string())
+        } else {
+            Value::UInt32(self.0)
+        };
+        Ok(vec![
+            ("id", id),
+            ("name", Value::String(self.0.to_string())),
+        ])
+    }
+}
+
+fn insert_handler(query: &str) -> Vec<MockResponse> {
+    if query.starts_with("insert") {
+        vec![
+            MockResponse::Data(mock_block(&[])),
+            MockResponse::ReceiveData,
+            MockResponse::EndOfStream,
+        ]
+    } else {
+        vec![MockResponse::EndOfStream]
+    }
+}
+
+fn fallible_rows() -> futures::stream::Iter<std::vec::IntoIter<Vec<FallibleRow>>> {
+    futures::stream::iter(vec![
+        (1..5).map(FallibleRow).collect(),
+        (5..8).map(FallibleRow).collect(),
+    ])
+}
+
+#[tokio::test]
+async fn test_mock_insert_fail() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let error = client
+        .insert_native("insert into test format native", fallible_rows())
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("row 2"), "{}", error);
+
+    // the insert was ended, so the connection remains usable
+    assert_eq!(client.execute("select 1").await.unwrap(), 0);
+    assert_eq!(server.queries()[1].query, "select 1");
+}
+
+#[tokio::test]
+async fn test_mock_insert_server_exception() {
+    let server = MockServer::start(|query| {
+        if query.starts_with("insert") {
+            vec![
+                MockResponse::Data(mock_block(&[])),
+                MockResponse::ReceiveData,
+                MockResponse::Exception {
+                    code: 252,
+                    name: "DB::Exception".to_string(),
+                    message: "Too many parts".to_string(),
+                },
+            ]
+        } else {
+            vec![MockResponse::EndOfStream]
+        }
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let error = client
+        .insert_native_block(
+            "insert into test format native",
+            vec![MockRow {
+                id: 1,
+                name: "one".to_string(),
+            }],
+        )
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Too many parts"), "{}", error);
+    assert_eq!(client.execute("select 1").await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_mock_insert_skip() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let report = client
+        .insert_native_with_policy(
+            "insert into test format native",
+            fallible_rows(),
+            InsertErrorPolicy::Skip,
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.rows, 4);
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|x| (x.index, x.row.is_some()))
+        .collect::<Vec<_>>();
+    // rows 3 and 6 fail to serialize, row 5 has the wrong type
+    assert_eq!(skipped, vec![(2, false), (4, true), (5, false)]);
+    assert_eq!(report.skipped[0].error.to_string(), "unserializable");
+
+    client
+        .query_raw("select 1")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    let queries = server.queries();
+    let ids = queries[0]
+        .blocks
+        .iter()
+        .flat_map(|block| block.column_data["id"].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        vec![
+            Value::UInt32(1),
+            Value::UInt32(2),
+            Value::UInt32(4),
+            Value::UInt32(7)
+        ]
+    );
+    assert_eq!(queries[0].blocks[1].rows, 1);
+}
+
+#[tokio::test]
+async fn test_mock_insert_dead_letter() {
+    let server = MockServer::start(insert_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let dead = Arc::new(Mutex::new(vec![]));
+    let dead_ref = dead.clone();
+    let report = client
+        .insert_native_with_policy(
+            "insert into test format native",
+            fallible_rows(),
+            InsertErrorPolicy::DeadLetter(Arc::new(move |error| {
+                dead_ref.lock().unwrap().push(error.index)
+            })),
+        )
+        .await
+        .unwrap();
+    assert_eq!(report.rows, 4);
+    assert!(report.skipped.is_empty());
+    assert_eq!(*dead.lock().unwrap(), vec![2, 4, 5]);
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct PartialRow {
+    name: String,
+    id: u32,
+}
+
+fn schema_handler(query: &str) -> Vec<MockResponse> {
+    if !query.starts_with("insert") {
+        return vec![MockResponse::EndOfStream];
+    }
+    let mut column_types = IndexMap::new();
+    column_types.insert("id".to_string(), Type::UInt64);
+    column_types.insert("extra".to_string(), Type::Nullable(Box::new(Type::String)));
+    column_types.insert("name".to_string(), Type::String);
+    column_types.insert("created".to_string(), Type::UInt32);
+    let column_data = column_types
+        .keys()
+        .map(|name| (name.clone(), vec![]))
+        .collect();
+    vec![
+        MockResponse::TableColumns(TableColumns {
+            name: "test".to_string(),
+            description: "columns format version: 1\n4 columns:\n`id` UInt64\n`extra` Nullable(String)\n`name` String\n`created` UInt32\tDEFAULT\tnow()\n".to_string(),
+        }),
+        MockResponse::Data(Block {
+            info: BlockInfo::default(),
+            rows: 0,
+            column_types,
+            column_data,
+        }),
+        MockResponse::ReceiveData,
+        MockResponse::EndOfStream,
+    ]
+}
+
+#[tokio::test]
+async fn test_mock_insert_schema() {
+    let server = MockServer::start(schema_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    client
+        .insert_native_block(
+            "insert into test format native",
+            vec![PartialRow {
+                name: "one".to_string(),
+                id: 1,
+            }],
+        )
+        .await
+        .unwrap();
+    client
+        .query_raw("select 1")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+
+    let queries = server.queries();
+    let block = &queries[0].blocks[0];
+    // omitted columns are filled with type defaults, or left to the server if they have a default expression
+    assert_eq!(
+        block.column_types.keys().collect::<Vec<_>>(),
+        vec!["id", "extra", "name"]
+    );
+    assert_eq!(block.column_data["id"], vec![Value::UInt64(1)]);
+    assert_eq!(block.column_data["extra"], vec![Value::Null]);
+    assert_eq!(
+        block.column_data["name"],
+        vec![Value::String("one".to_string())]
+    );
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct DefaultedRow {
+    id: u64,
+    name: String,
+    #[klickhouse(skip_serializing_if = "is_zero")]
+    created: u32,
+}
+
+#[tokio::test]
+async fn test_mock_insert_skip_serializing_if() {
+    let server = MockServer::start(schema_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let row = |id, created| DefaultedRow {
+        id,
+        name: "one".to_string(),
+        created,
+    };
+    client
+        .insert_native_block("insert into test format native", vec![row(1, 0), row(2, 0)])
+        .await
+        .unwrap();
+    client
+        .insert_native_block("insert into test format native", vec![row(3, 0), row(4, 5)])
+        .await
+        .unwrap();
+    client
+        .query_raw("select 1")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+
+    let queries = server.queries();
+    // skipped by every row, the column is left to its default expression
+    let block = &queries[0].blocks[0];
+    assert!(!block.column_types.contains_key("created"));
+    // otherwise skipped rows take the default value of the type
+    let block = &queries[1].blocks[0];
+    assert_eq!(
+        block.column_data["created"],
+        vec![Value::UInt32(0), Value::UInt32(5)]
+    );
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct UnknownFieldRow {
+    id: u32,
+    missing: String,
+}
+
+#[tokio::test]
+async fn test_mock_insert_unknown_field() {
+    let server = MockServer::start(schema_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let error = client
+        .insert_native_block(
+            "insert into test format native",
+            vec![UnknownFieldRow::default()],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.root_cause().to_string(),
+        "field `missing` has no matching column in table test"
+    );
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct WideRow {
+    #[klickhouse(lenient)]
+    id: u64,
+    name: String,
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+#[klickhouse(lenient)]
+pub struct NarrowRow {
+    id: u8,
+    name: String,
+}
+
+fn select_handler(_query: &str) -> Vec<MockResponse> {
+    vec![
+        MockResponse::Data(mock_block(&[(1, "one"), (300, "many")])),
+        MockResponse::EndOfStream,
+    ]
+}
+
+#[tokio::test]
+async fn test_mock_lenient_field() {
+    let server = MockServer::start(select_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query::<WideRow>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap().id)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows, vec![1, 300]);
+
+    // checked narrowing
+    let rows = client
+        .query::<NarrowRow>("select id, name from test")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows[0].as_ref().unwrap().id, 1);
+    assert!(rows[1].is_err());
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct StrictWideRow {
+    id: u64,
+    name: String,
+}
+
+#[tokio::test]
+async fn test_mock_lenient_client() {
+    let server = MockServer::start(select_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+    let rows = client
+        .query::<StrictWideRow>("select id, name from test")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert!(rows[0].is_err());
+
+    let client = klickhouse::Client::connect(
+        server.local_addr(),
+        ClientOptions {
+            lenient_coercion: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+    let rows = client
+        .query::<StrictWideRow>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap().id)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows, vec![1, 300]);
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+#[klickhouse(deny_unknown_fields)]
+pub struct MismatchedRow {
+    id: String,
+    #[klickhouse(type = "UInt8")]
+    count: u8,
+    #[klickhouse(default)]
+    tag: String,
+}
+
+#[tokio::test]
+async fn test_mock_expected_columns() {
+    let server = MockServer::start(select_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query::<MismatchedRow>("select id, name from test")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        rows[0].as_ref().unwrap_err().to_string(),
+        "query columns do not match row `mock::MismatchedRow`:
+  missing column `count`
+  column `id` has type UInt32, expected String
+  unexpected column `name`"
+    );
+}
+
+async fn roundtrip_compression(compression: CompressionMethod) {
+    let server = MockServer::start(|query| {
+        if query.starts_with("insert") {
+            vec![
+                MockResponse::Data(mock_block(&[])),
+                MockResponse::ReceiveData,
+                MockResponse::EndOfStream,
+            ]
+        } else {
+            vec![
+                MockResponse::Data(mock_block(&[(1, "one"), (2, "two")])),
+                MockResponse::EndOfStream,
+            ]
+        }
+    })
+    .await
+    .unwrap();
+    let client = server
+        .connect()
+        .await
+        .unwrap()
+        .with_compression(compression);
+
+    client
+        .insert_native_block(
+            "insert into test format native",
+            vec![MockRow {
+                id: 3,
+                name: "three".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+    let rows = client
+        .query::<MockRow>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].name, "two");
+
+    let queries = server.queries();
+    assert_eq!(queries.len(), 2);
+    for query in &queries {
+        assert_eq!(query.compression, compression);
+    }
+    assert_eq!(
+        queries[0].blocks[0].column_data["id"],
+        vec![Value::UInt32(3)]
+    );
+}
+
+#[tokio::test]
+async fn test_mock_no_compression() {
+    roundtrip_compression(CompressionMethod::None).await;
+}
+
+#[cfg(any(feature = "compression", feature = "lz4-pure"))]
+#[tokio::test]
+async fn test_mock_lz4() {
+    roundtrip_compression(CompressionMethod::LZ4).await;
+}
+
+#[cfg(feature = "zstd")]
+#[tokio::test]
+async fn test_mock_zstd() {
+    roundtrip_compression(CompressionMethod::ZSTD).await;
+}
+
+#[derive(klickhouse::Row, Debug, Default, PartialEq)]
+pub struct MockTupleRow(u32, String);
+
+#[tokio::test]
+async fn test_mock_select_tuple() {
+    let server = MockServer::start(select_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query::<(u32, String)>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        rows,
+        vec![(1, "one".to_string()), (300, "many".to_string())]
+    );
+
+    let rows = client
+        .query::<MockTupleRow>("select id, name from test")
+        .await
+        .unwrap()
+        .map(|row| row.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(
+        rows,
+        vec![
+            MockTupleRow(1, "one".to_string()),
+            MockTupleRow(300, "many".to_string())
+        ]
+    );
+
+    // columns are matched by position, so counts must line up
+    let error = client
+        .query::<(u32,)>("select id, name from test")
+        .await
+        .unwrap()
+        .next()
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unexpected column count, got 2 expecting 1"
+    );
+    let error = client
+        .query::<(String, u32)>("select id, name from test")
+        .await
+        .unwrap()
+        .next()
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "failed to deserialize column `id`");
+}
+
+#[tokio::test]
+async fn test_mock_insert_tuple() {
+    let server = MockServer::start(schema_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    client
+        .insert_native_block(
+            "insert into test format native",
+            vec![(1u64, Some("two".to_string()), "one".to_string(), 3u32)],
+        )
+        .await
+        .unwrap();
+    client
+        .query_raw("select 1")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+
+    let queries = server.queries();
+    let block = &queries[0].blocks[0];
+    assert_eq!(block.column_data["id"], vec![Value::UInt64(1)]);
+    assert_eq!(
+        block.column_data["extra"],
+        vec![Value::String("two".to_string())]
+    );
+    assert_eq!(
+        block.column_data["name"],
+        vec![Value::String("one".to_string())]
+    );
+    assert_eq!(block.column_data["created"], vec![Value::UInt32(3)]);
+}
+
+#[tokio::test]
+async fn test_mock_insert_tuple_mismatch() {
+    let server = MockServer::start(schema_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let error = client
+        .insert_native_block(
+            "insert into test format native",
+            vec![MockTupleRow(1, "one".to_string())],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.root_cause().to_string(),
+        "row has 2 values but table test has 4 columns"
+    );
+}
+
+fn table_block(columns: &[(&str, Type)], rows: Vec<Vec<Value>>) -> Block {
+    let mut column_types = IndexMap::new();
+    let mut column_data = IndexMap::new();
+    for (i, (name, type_)) in columns.iter().enumerate() {
+        column_types.insert(name.to_string(), type_.clone());
+        column_data.insert(
+            name.to_string(),
+            rows.iter().map(|row| row[i].clone()).collect(),
+        );
+    }
+    Block {
+        info: BlockInfo::default(),
+        rows: rows.len() as u64,
+        column_types,
+        column_data,
+    }
+}
+
+fn schema_info_handler(query: &str) -> Vec<MockResponse> {
+    let string = |x: &str| Value::String(x.to_string());
+    let block = if query.contains("system.columns") {
+        if !query.contains("table = 'events'") {
+            table_block(&[("name", Type::String)], vec![])
+        } else {
+            let mut columns = vec![("name", Type::String), ("type", Type::String)];
+            for name in [
+                "default_kind",
+                "default_expression",
+                "comment",
+                "compression_codec",
+            ] {
+                columns.push((name, Type::String));
+            }
+            for name in [
+                "is_in_partition_key",
+                "is_in_sorting_key",
+                "is_in_primary_key",
+                "is_in_sampling_key",
+            ] {
+                columns.push((name, Type::UInt8));
+            }
+            table_block(
+                &columns,
+                vec![
+                    vec![
+                        string("id"),
+                        string("UInt64"),
+                        string(""),
+                        string(""),
+                        string("event id"),
+                        string("CODEC(ZSTD(1))"),
+                        Value::UInt8(0),
+                        Value::UInt8(1),
+                        Value::UInt8(1),
+                        Value::UInt8(0),
+                    ],
+                    vec![
+                        string("created"),
+                        string("Nullable(DateTime('UTC'))"),
+                        string("DEFAULT"),
+                        string("now()"),
+                        string(""),
+                        string(""),
+                        Value::UInt8(1),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                    ],
+                    vec![
+                        string("status"),
+                        string("Enum8('active' = 1, 'disabled' = 2)"),
+                        string(""),
+                        string(""),
+                        string(""),
+                        string(""),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                    ],
+                    vec![
+                        string("total"),
+                        string("AggregateFunction(sum, UInt64)"),
+                        string(""),
+                        string(""),
+                        string(""),
+                        string(""),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                        Value::UInt8(0),
+                    ],
+                ],
+            )
+        }
+    } else if query.contains("system.tables where database = 'default' and name") {
+        let columns = [
+            "database",
+            "name",
+            "engine",
+            "engine_full",
+            "partition_key",
+            "sorting_key",
+            "primary_key",
+            "sampling_key",
+        ]
+        .iter()
+        .map(|name| (*name, Type::String))
+        .collect::<Vec<_>>();
+        table_block(
+            &columns,
+            vec![vec![
+                string("default"),
+                string("events"),
+                string("MergeTree"),
+                string("MergeTree PARTITION BY created ORDER BY id"),
+                string("created"),
+                string("id"),
+                string("id"),
+                string(""),
+            ]],
+        )
+    } else {
+        table_block(
+            &[("name", Type::String)],
+            vec![vec![string("default")], vec![string("system")]],
+        )
+    };
+    vec![MockResponse::Data(block), MockResponse::EndOfStream]
+}
+
+#[tokio::test]
+async fn test_mock_describe_table() {
+    let server = MockServer::start(schema_info_handler).await.unwrap();
+    let client = server.connect().await.unwrap();
+
+    let columns = client.describe_table("default", "events").await.unwrap();
+    assert_eq!(
+        server.queries()[0].query,
+        "select name, type, default_kind, default_expression, comment, compression_codec, is_in_partition_key, is_in_sorting_key, is_in_primary_key, is_in_sampling_key from system.columns where database = 'default' and table = 'events' order by position"
+    );
+    assert_eq!(
+        columns,
+        vec![
+            ColumnInfo {
+                name: "id".to_string(),
+                type_name: "UInt64".to_string(),
+                type_: Some(Type::UInt64),
+                default_kind: None,
+                default_expression: "".to_string(),
+                comment: "event id".to_string(),
+                codec: "CODEC(ZSTD(1))".to_string(),
+                is_in_partition_key: false,
+                is_in_sorting_key: true,
+                is_in_primary_key: true,
+                is_in_sampling_key: false,
+            },
+            ColumnInfo {
+                name: "created".to_string(),
+                type_name: "Nullable(DateTime('UTC'))".to_string(),
+                type_: Some(Type::Nullable(Box::new(Type::DateTime(chrono_tz::UTC)))),
+                default_kind: Some(DefaultKind::Default),
+                default_expression: "now()".to_string(),
+                comment: "".to_string(),
+                codec: "".to_string(),
+                is_in_partition_key: true,
+                is_in_sorting_key: false,
+                is_in_primary_key: false,
+                is_in_sampling_key: false,
+            },
+            ColumnInfo {
+                name: "status".to_string(),
+                type_name: "Enum8('active' = 1, 'disabled' = 2)".to_string(),
+                type_: Some(Type::Enum8(vec![
+                    ("active".to_string(), 1),
+                    ("disabled".to_string(), 2)
+                ])),
+                default_kind: None,
+                default_expression: "".to_string(),
+                comment: "".to_string(),
+                codec: "".to_string(),
+                is_in_partition_key: false,
+                is_in_sorting_key: false,
+                is_in_primary_key: false,
+                is_in_sampling_key: false,
+            },
+            // types without a parsed form are still described
+            ColumnInfo {
+                name: "total".to_string(),
+                type_name: "AggregateFunction(sum, UInt64)".to_string(),
+                type_: None,
+                default_kind: None,
+                default_expression: "".to_string(),
+                comment: "".to_string(),
+                codec: "".to_string(),
+                is_in_partition_key: false,
+                is_in_sorting_key: false,
+                is_in_primary_key: false,
+                is_in_sampling_key: false,
+            },
+        ]
+    );
+    assert_eq!(
+        client
+            .describe_table("default", "missing")
+            .await
+            .unwrap_err()
+            .to_string(),
+        "table `default`.`missing` does not exist"
+    );
+
+    let table = client.table_info("default", "events").await.unwrap();
+    assert_eq!(table.engine, "MergeTree");
+    assert_eq!(table.partition_key, "created");
+    assert_eq!(table.sorting_key, "id");
+
+    assert_eq!(
+        client.list_databases().await.unwrap(),
+        vec!["default".to_string(), "system".to_string()]
+    );
+    assert_eq!(
+        client.list_tables("default").await.unwrap(),
+        vec!["default".to_string(), "system".to_string()]
+    );
+    assert_eq!(
+        server.queries().last().unwrap().query,
+        "select name from system.tables where database = 'default' order by name"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize, Debug, PartialEq)]
+pub struct SerdeMockRow {
+    id: u64,
+    name: String,
+}
+
+#[cfg(feature = "serde")]
+#[tokio::test]
+async fn test_mock_serde_row() {
+    use klickhouse::SerdeRow;
+
+    let server = MockServer::start(|_| {
+        vec![
+            MockResponse::Data(mock_block(&[(1, "one"), (2, "two")])),
+            MockResponse::EndOfStream,
+        ]
+    })
+    .await
+    .unwrap();
+    let client = server.connect().await.unwrap();
+
+    let rows = client
+        .query_collect::<SerdeRow<SerdeMockRow>>("select id, name from test")
+        .await
+        .unwrap();
+    assert_eq!(
+        rows.into_iter()
+            .map(SerdeRow::into_inner)
+            .collect::<Vec<_>>(),
+        vec![
+            SerdeMockRow {
+                id: 1,
+                name: "one".to_string(),
+            },
+            SerdeMockRow {
+                id: 2,
+                name: "two".to_string(),
+            },
+        ]
+    );
+}
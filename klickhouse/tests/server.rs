@@ -0,0 +1,110 @@
+use futures::StreamExt;
+use indexmap::IndexMap;
+use klickhouse::{
+    protocol::{ClientPacket, QueryKind, ServerData, ServerHello, ServerPacket},
+    Block, BlockInfo, Client, ClientOptions, Progress, ServerConnection, Type, Value,
+};
+use tokio::net::TcpListener;
+
+fn hello(revision_version: u64) -> ServerHello {
+    ServerHello {
+        server_name: "ClickHouse".to_string(),
+        major_version: 21,
+        minor_version: 6,
+        revision_version,
+        timezone: Some("UTC".to_string()),
+        display_name: Some("test".to_string()),
+        patch_version: 0,
+    }
+}
+
+async fn roundtrip(revision_version: u64) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut connection = ServerConnection::accept(stream, hello(revision_version))
+            .await
+            .unwrap();
+        assert_eq!(connection.client_hello().username, "default");
+        assert_eq!(
+            connection.revision(),
+            revision_version.min(klickhouse::protocol::DBMS_TCP_PROTOCOL_VERSION)
+        );
+
+        let query = match connection.receive_packet().await.unwrap() {
+            ClientPacket::Query(query) => query,
+            packet => panic!("unexpected packet {:?}", packet),
+        };
+        assert_eq!(query.query, "select value from test");
+        assert_eq!(query.info.kind, QueryKind::InitialQuery);
+        assert_eq!(query.info.client_name, "ClickHouseclient");
+        match connection.receive_packet().await.unwrap() {
+            ClientPacket::Data(data) => assert_eq!(data.block.rows, 0),
+            packet => panic!("unexpected packet {:?}", packet),
+        }
+
+        let mut column_types = IndexMap::new();
+        column_types.insert("value".to_string(), Type::String);
+        let mut column_data = IndexMap::new();
+        column_data.insert(
+            "value".to_string(),
+            vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+            ],
+        );
+        let packets = vec![
+            ServerPacket::Progress(Progress {
+                read_rows: 2,
+                read_bytes: 4,
+                new_total_rows_to_read: 2,
+                new_written_rows: None,
+                new_written_bytes: None,
+            }),
+            ServerPacket::Data(ServerData {
+                table_name: String::new(),
+                block: Block {
+                    info: BlockInfo::default(),
+                    rows: 2,
+                    column_types,
+                    column_data,
+                },
+            }),
+            ServerPacket::EndOfStream,
+        ];
+        for packet in &packets {
+            connection.send_packet(packet).await.unwrap();
+        }
+    });
+
+    let client = Client::connect(addr, ClientOptions::default())
+        .await
+        .unwrap();
+    let blocks = client
+        .query_raw("select value from test")
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(
+        blocks[0].column_data["value"],
+        vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string())
+        ]
+    );
+    server.await.unwrap();
+}
+
+#[tokio::test]
+async fn test_server_connection() {
+    roundtrip(klickhouse::protocol::DBMS_TCP_PROTOCOL_VERSION).await;
+}
+
+#[tokio::test]
+async fn test_server_connection_older_revision() {
+    roundtrip(54441).await;
+}
@@ -0,0 +1,8 @@
+// Compile tests for the attribute surface of the derives.
+// Regenerate the expected compiler output with `TRYBUILD=overwrite cargo test --test ui`.
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
@@ -0,0 +1,23 @@
+pub struct NoDefault(u64);
+
+impl klickhouse::ToSql for NoDefault {
+    fn to_sql(self) -> klickhouse::Result<klickhouse::Value> {
+        Ok(klickhouse::Value::UInt64(self.0))
+    }
+}
+
+impl klickhouse::FromSql for NoDefault {
+    fn from_sql(type_: &klickhouse::Type, value: klickhouse::Value) -> klickhouse::Result<Self> {
+        Ok(NoDefault(u64::from_sql(type_, value)?))
+    }
+}
+
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(default)]
+    value: NoDefault,
+    #[klickhouse(skip_deserializing)]
+    skipped: NoDefault,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(default = "missing_default")]
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[derive(klickhouse::Row, Default)]
+pub struct Inner {
+    a: u8,
+}
+
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(flatten, rename = "x", skip_serializing_if = "is_empty")]
+    inner: Inner,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[derive(klickhouse::Row, Default)]
+pub struct Inner {
+    a: u8,
+}
+
+#[derive(klickhouse::Row)]
+pub struct Row(u64, #[klickhouse(flatten)] Inner);
+
+fn main() {}
@@ -0,0 +1,12 @@
+#[derive(klickhouse::Row)]
+pub struct Raw {
+    value: u64,
+}
+
+#[derive(klickhouse::Row)]
+#[klickhouse(from = "Raw", try_from = "Raw")]
+pub struct Row {
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[derive(klickhouse::Row)]
+#[klickhouse(rename_all = "Title Case")]
+pub struct Row {
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+#[derive(klickhouse::RowRef)]
+pub struct Row<'a, 'b> {
+    #[klickhouse(lenient)]
+    value: u64,
+    name: &'a str,
+    other: &'b str,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use klickhouse::{Type, Value};
+
+fn to_sql(value: &u64) -> klickhouse::Result<Value> {
+    Ok(Value::UInt64(*value))
+}
+
+fn from_sql(_type_: &Type, _value: Value) -> klickhouse::Result<String> {
+    Ok(String::new())
+}
+
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(serialize_with = "to_sql", deserialize_with = "from_sql")]
+    value: u64,
+}
+
+fn main() {}
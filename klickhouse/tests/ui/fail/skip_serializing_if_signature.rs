@@ -0,0 +1,11 @@
+fn is_zero(value: u64) -> bool {
+    value == 0
+}
+
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(skip_serializing_if = "is_zero")]
+    value: u64,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[derive(klickhouse::Row)]
+pub struct Row(u64, #[klickhouse(skip_serializing_if = "String::is_empty")] String);
+
+fn main() {}
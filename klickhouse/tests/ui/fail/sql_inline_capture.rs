@@ -0,0 +1,4 @@
+fn main() {
+    let name = "'; drop table t; --";
+    let _ = klickhouse::sql!("select * from t where name = {name}");
+}
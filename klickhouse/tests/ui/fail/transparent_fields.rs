@@ -0,0 +1,8 @@
+#[derive(klickhouse::ToSql, klickhouse::FromSql)]
+#[klickhouse(transparent)]
+pub struct Pair(u64, u64);
+
+#[derive(klickhouse::ToSql)]
+pub struct Skipped(u64, #[klickhouse(skip)] u64);
+
+fn main() {}
@@ -0,0 +1,11 @@
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(type = "Enum8('active' = 1, 'disabled' = 2)")]
+    status: String,
+    #[klickhouse(type = "Nullable(Strin)")]
+    name: Option<String>,
+    #[klickhouse(type = "DateTime64(3, 'Mars/Olympus')")]
+    created: u64,
+}
+
+fn main() {}
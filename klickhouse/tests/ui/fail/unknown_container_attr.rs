@@ -0,0 +1,7 @@
+#[derive(klickhouse::Row)]
+#[klickhouse(deny_unknown_columns)]
+pub struct Row {
+    value: u64,
+}
+
+fn main() {}
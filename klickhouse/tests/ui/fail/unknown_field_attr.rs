@@ -0,0 +1,7 @@
+#[derive(klickhouse::Row)]
+pub struct Row {
+    #[klickhouse(skip_if = "Option::is_none")]
+    value: Option<u64>,
+}
+
+fn main() {}
@@ -0,0 +1,52 @@
+use std::convert::TryFrom;
+
+use klickhouse::Row;
+
+#[derive(Row, Default)]
+#[klickhouse(rename_all = "camelCase", deny_unknown_fields, default, lenient)]
+#[klickhouse(order_by = "userId", partition_by = "toYYYYMM(created)")]
+pub struct Container {
+    user_id: u64,
+    created: u32,
+}
+
+#[derive(Row)]
+#[klickhouse(bound = "T: klickhouse::ToSql + klickhouse::FromSql")]
+pub struct Bounded<T> {
+    value: T,
+}
+
+#[derive(Row)]
+pub struct Raw {
+    value: u64,
+}
+
+#[derive(Row)]
+#[klickhouse(from = "Raw", into = "Raw")]
+pub struct Converted(u64);
+
+impl From<Raw> for Converted {
+    fn from(raw: Raw) -> Self {
+        Converted(raw.value)
+    }
+}
+
+impl From<Converted> for Raw {
+    fn from(converted: Converted) -> Self {
+        Raw { value: converted.0 }
+    }
+}
+
+#[derive(Row)]
+#[klickhouse(try_from = "Raw")]
+pub struct Checked(u64);
+
+impl TryFrom<Raw> for Checked {
+    type Error = klickhouse::Error;
+
+    fn try_from(raw: Raw) -> klickhouse::Result<Self> {
+        Ok(Checked(raw.value))
+    }
+}
+
+fn main() {}
@@ -0,0 +1,61 @@
+use klickhouse::{FromSql, Result, Row, ToSql, Type, Value};
+
+fn is_empty(value: &str) -> bool {
+    value.is_empty()
+}
+
+fn default_count() -> u64 {
+    1
+}
+
+pub fn to_text(value: u64) -> Result<Value> {
+    value.to_string().to_sql()
+}
+
+pub fn from_text(type_: &Type, value: Value) -> Result<u64> {
+    Ok(String::from_sql(type_, value)?.parse()?)
+}
+
+mod text {
+    pub use super::{from_text as from_sql, to_text as to_sql};
+}
+
+#[derive(Row, Default)]
+pub struct Inner {
+    a: u8,
+}
+
+#[derive(Row)]
+pub struct Fields {
+    #[klickhouse(rename = "ID")]
+    id: u64,
+    #[klickhouse(default)]
+    a: u32,
+    #[klickhouse(default = "default_count")]
+    b: u64,
+    #[klickhouse(skip)]
+    c: u8,
+    #[klickhouse(skip_serializing)]
+    d: u8,
+    #[klickhouse(skip_deserializing)]
+    e: u8,
+    #[klickhouse(skip_serializing_if = "is_empty")]
+    f: String,
+    #[klickhouse(serialize_with = "to_text", deserialize_with = "from_text")]
+    g: u64,
+    #[klickhouse(with = "text")]
+    h: u64,
+    #[klickhouse(lenient)]
+    i: u64,
+    #[klickhouse(flatten)]
+    j: Inner,
+    #[klickhouse(flatten(prefix = "k_"))]
+    k: Inner,
+    #[klickhouse(type = "LowCardinality(String)", codec = "ZSTD(1)", default = "'x'")]
+    l: String,
+}
+
+#[derive(Row)]
+pub struct Positional(u64, #[klickhouse(skip_serializing)] String, #[klickhouse(default)] u32);
+
+fn main() {}
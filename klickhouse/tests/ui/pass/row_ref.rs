@@ -0,0 +1,17 @@
+use klickhouse::RowRef;
+
+#[derive(RowRef)]
+#[klickhouse(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Borrowed<'a> {
+    user_id: u64,
+    name: &'a str,
+    #[klickhouse(default)]
+    bytes: Option<&'a [u8]>,
+    #[klickhouse(skip)]
+    skipped: u8,
+}
+
+#[derive(RowRef)]
+pub struct Owned(u64, String);
+
+fn main() {}
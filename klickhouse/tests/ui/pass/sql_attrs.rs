@@ -0,0 +1,53 @@
+use klickhouse::{FromSql, ToSql};
+
+#[derive(ToSql, FromSql)]
+#[klickhouse(transparent)]
+pub struct UserId(u64);
+
+#[derive(ToSql, FromSql)]
+#[klickhouse(transparent)]
+pub struct Email {
+    #[klickhouse(with = "lower")]
+    address: String,
+}
+
+mod lower {
+    use klickhouse::{FromSql, Result, ToSql, Type, Value};
+
+    pub fn to_sql(value: String) -> Result<Value> {
+        value.to_lowercase().to_sql()
+    }
+
+    pub fn from_sql(type_: &Type, value: Value) -> Result<String> {
+        String::from_sql(type_, value)
+    }
+}
+
+#[derive(ToSql, FromSql)]
+pub struct Point(f64, f64);
+
+#[derive(ToSql, FromSql, Clone)]
+#[klickhouse(into = "String", from = "String")]
+pub struct Name(String);
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Name(name)
+    }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self {
+        name.0
+    }
+}
+
+#[derive(klickhouse::Row)]
+pub struct Account {
+    id: UserId,
+    email: Email,
+    home: Point,
+    name: Name,
+}
+
+fn main() {}
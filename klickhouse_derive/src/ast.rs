@@ -13,6 +13,8 @@ pub struct Container<'a> {
     pub attrs: attr::Container,
     /// The contents of the struct or enum.
     pub data: Struct<'a>,
+    /// Whether the fields are named or positional.
+    pub style: Style,
     /// Any generics on the struct or enum.
     pub generics: &'a syn::Generics,
     /// Original input.
@@ -24,6 +26,15 @@ pub struct Container<'a> {
 /// Analogous to `syn::Data`.
 pub type Struct<'a> = Vec<Field<'a>>;
 
+/// How fields are matched to columns.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Style {
+    /// Named fields, matched by name.
+    Struct,
+    /// Tuple struct fields, matched by position.
+    Tuple,
+}
+
 /// A field of a struct.
 pub struct Field<'a> {
     pub member: syn::Member,
@@ -37,7 +48,7 @@ impl<'a> Container<'a> {
     pub fn from_ast(cx: &Ctxt, item: &'a syn::DeriveInput) -> Option<Container<'a>> {
         let attrs = attr::Container::from_ast(cx, item);
 
-        let mut data = match &item.data {
+        let (style, mut data) = match &item.data {
             syn::Data::Struct(data) => struct_from_ast(cx, &data.fields, attrs.default()),
             syn::Data::Union(_) => {
                 cx.error_spanned_by(item, "Klickhouse Row does not support unions");
@@ -57,6 +68,7 @@ impl<'a> Container<'a> {
             ident: item.ident.clone(),
             attrs,
             data,
+            style,
             generics: &item.generics,
             original: item,
         };
@@ -69,16 +81,19 @@ fn struct_from_ast<'a>(
     cx: &Ctxt,
     fields: &'a syn::Fields,
     container_default: &attr::Default,
-) -> Vec<Field<'a>> {
+) -> (Style, Vec<Field<'a>>) {
     match fields {
-        syn::Fields::Named(fields) => fields_from_ast(cx, &fields.named, container_default),
-        syn::Fields::Unnamed(fields) => {
-            cx.error_spanned_by(fields, "Klickhouse Row does not support tuple structs");
-            vec![]
-        }
+        syn::Fields::Named(fields) => (
+            Style::Struct,
+            fields_from_ast(cx, &fields.named, container_default),
+        ),
+        syn::Fields::Unnamed(fields) => (
+            Style::Tuple,
+            fields_from_ast(cx, &fields.unnamed, container_default),
+        ),
         syn::Fields::Unit => {
             cx.error_spanned_by(fields, "Klickhouse Row does not support unit structs");
-            vec![]
+            (Style::Struct, vec![])
         }
     }
 }
@@ -108,6 +108,10 @@ impl Name {
     pub fn name(&self) -> String {
         self.name.clone()
     }
+
+    pub fn renamed(&self) -> bool {
+        self.renamed
+    }
 }
 
 /// Represents struct or enum attribute information.
@@ -119,6 +123,10 @@ pub struct Container {
     type_from: Option<syn::Type>,
     type_try_from: Option<syn::Type>,
     type_into: Option<syn::Type>,
+    lenient: bool,
+    transparent: bool,
+    order_by: Option<String>,
+    partition_by: Option<String>,
     is_packed: bool,
 }
 
@@ -133,6 +141,10 @@ impl Container {
         let mut type_from = Attr::none(cx, FROM);
         let mut type_try_from = Attr::none(cx, TRY_FROM);
         let mut type_into = Attr::none(cx, INTO);
+        let mut lenient = BoolAttr::none(cx, LENIENT);
+        let mut transparent = BoolAttr::none(cx, TRANSPARENT);
+        let mut order_by = Attr::none(cx, ORDER_BY);
+        let mut partition_by = Attr::none(cx, PARTITION_BY);
 
         for meta_item in item
             .attrs
@@ -165,6 +177,16 @@ impl Container {
                     deny_unknown_fields.set_true(word);
                 }
 
+                // Parse `#[klickhouse(lenient)]`
+                Meta(Path(word)) if word == LENIENT => {
+                    lenient.set_true(word);
+                }
+
+                // Parse `#[klickhouse(transparent)]`
+                Meta(Path(word)) if word == TRANSPARENT => {
+                    transparent.set_true(word);
+                }
+
                 // Parse `#[klickhouse(default)]`
                 Meta(Path(word)) if word == DEFAULT => match &item.data {
                     syn::Data::Struct(syn::DataStruct { fields, .. }) => match fields {
@@ -245,6 +267,20 @@ impl Container {
                     }
                 }
 
+                // Parse `#[klickhouse(order_by = "...")]`
+                Meta(NameValue(m)) if m.path == ORDER_BY => {
+                    if let Ok(s) = get_lit_str(cx, ORDER_BY, &m.lit) {
+                        order_by.set(&m.path, s.value());
+                    }
+                }
+
+                // Parse `#[klickhouse(partition_by = "...")]`
+                Meta(NameValue(m)) if m.path == PARTITION_BY => {
+                    if let Ok(s) = get_lit_str(cx, PARTITION_BY, &m.lit) {
+                        partition_by.set(&m.path, s.value());
+                    }
+                }
+
                 Meta(meta_item) => {
                     let path = meta_item
                         .path()
@@ -288,6 +324,10 @@ impl Container {
             type_from: type_from.get(),
             type_try_from: type_try_from.get(),
             type_into: type_into.get(),
+            lenient: lenient.get(),
+            transparent: transparent.get(),
+            order_by: order_by.get(),
+            partition_by: partition_by.get(),
             is_packed,
         }
     }
@@ -320,6 +360,25 @@ impl Container {
         self.type_into.as_ref()
     }
 
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Whether `ToSql`/`FromSql` convert the only field directly, rather than as a tuple.
+    pub fn transparent(&self) -> bool {
+        self.transparent
+    }
+
+    /// `ORDER BY` expression for the generated `CREATE TABLE` statement.
+    pub fn order_by(&self) -> Option<&str> {
+        self.order_by.as_deref()
+    }
+
+    /// `PARTITION BY` expression for the generated `CREATE TABLE` statement.
+    pub fn partition_by(&self) -> Option<&str> {
+        self.partition_by.as_deref()
+    }
+
     pub fn is_packed(&self) -> bool {
         self.is_packed
     }
@@ -335,6 +394,11 @@ pub struct Field {
     serialize_with: Option<syn::ExprPath>,
     deserialize_with: Option<syn::ExprPath>,
     bound: Option<Vec<syn::WherePredicate>>,
+    lenient: bool,
+    flatten: Option<String>,
+    sql_type: Option<String>,
+    sql_default: Option<String>,
+    codec: Option<String>,
 }
 
 #[allow(clippy::enum_variant_names)]
@@ -364,6 +428,11 @@ impl Field {
         let mut serialize_with = Attr::none(cx, SERIALIZE_WITH);
         let mut deserialize_with = Attr::none(cx, DESERIALIZE_WITH);
         let mut bound = Attr::none(cx, BOUND);
+        let mut lenient = BoolAttr::none(cx, LENIENT);
+        let mut flatten = Attr::none(cx, FLATTEN);
+        let mut sql_type = Attr::none(cx, TYPE);
+        let mut sql_default = Attr::none(cx, DEFAULT);
+        let mut codec = Attr::none(cx, CODEC);
 
         let ident = match &field.ident {
             Some(ident) => unraw(ident),
@@ -389,10 +458,34 @@ impl Field {
                     default.set(word, Default::Default);
                 }
 
-                // Parse `#[klickhouse(default = "...")]`
+                // Parse `#[klickhouse(default = "...")]`, either a path to a function giving the
+                // default when deserializing, or else a column `DEFAULT` expression such as `now()`
                 Meta(NameValue(m)) if m.path == DEFAULT => {
-                    if let Ok(path) = parse_lit_into_expr_path(cx, DEFAULT, &m.lit) {
-                        default.set(&m.path, Default::Path(path));
+                    if let Ok(s) = get_lit_str(cx, DEFAULT, &m.lit) {
+                        match parse_lit_str(s) {
+                            Ok(path) => default.set(&m.path, Default::Path(path)),
+                            Err(_) => sql_default.set(&m.path, s.value()),
+                        }
+                    }
+                }
+
+                // Parse `#[klickhouse(type = "...")]`
+                Meta(NameValue(m)) if m.path == TYPE => {
+                    if let Ok(s) = get_lit_str(cx, TYPE, &m.lit) {
+                        match crate::sql_type::validate(&s.value()) {
+                            Ok(()) => sql_type.set(&m.path, s.value()),
+                            Err(err) => cx.error_spanned_by(
+                                s,
+                                format!("invalid type `{}`: {}", s.value(), err),
+                            ),
+                        }
+                    }
+                }
+
+                // Parse `#[klickhouse(codec = "...")]`
+                Meta(NameValue(m)) if m.path == CODEC => {
+                    if let Ok(s) = get_lit_str(cx, CODEC, &m.lit) {
+                        codec.set(&m.path, s.value());
                     }
                 }
 
@@ -412,6 +505,37 @@ impl Field {
                     skip_deserializing.set_true(word);
                 }
 
+                // Parse `#[klickhouse(lenient)]`
+                Meta(Path(word)) if word == LENIENT => {
+                    lenient.set_true(word);
+                }
+
+                // Parse `#[klickhouse(flatten)]`
+                Meta(Path(word)) if word == FLATTEN => {
+                    flatten.set(word, String::new());
+                }
+
+                // Parse `#[klickhouse(flatten(prefix = "..."))]`
+                Meta(List(m)) if m.path == FLATTEN => {
+                    let mut prefix = Attr::none(cx, PREFIX);
+                    for nested in &m.nested {
+                        match nested {
+                            Meta(NameValue(m)) if m.path == PREFIX => {
+                                if let Ok(s) = get_lit_str(cx, PREFIX, &m.lit) {
+                                    prefix.set(&m.path, s.value());
+                                }
+                            }
+                            _ => {
+                                cx.error_spanned_by(
+                                    nested,
+                                    "expected `prefix = \"...\"` in klickhouse flatten attribute",
+                                );
+                            }
+                        }
+                    }
+                    flatten.set(&m.path, prefix.get().unwrap_or_default());
+                }
+
                 // Parse `#[klickhouse(skip_serializing_if = "...")]`
                 Meta(NameValue(m)) if m.path == SKIP_SERIALIZING_IF => {
                     if let Ok(path) = parse_lit_into_expr_path(cx, SKIP_SERIALIZING_IF, &m.lit) {
@@ -494,6 +618,11 @@ impl Field {
             serialize_with: serialize_with.get(),
             deserialize_with: deserialize_with.get(),
             bound: bound.get(),
+            lenient: lenient.get(),
+            flatten: flatten.get(),
+            sql_type: sql_type.get(),
+            sql_default: sql_default.get(),
+            codec: codec.get(),
         }
     }
 
@@ -534,6 +663,30 @@ impl Field {
     pub fn bound(&self) -> Option<&[syn::WherePredicate]> {
         self.bound.as_ref().map(|vec| &vec[..])
     }
+
+    pub fn lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Column name prefix of a flattened field, empty if flattened without a prefix.
+    pub fn flatten(&self) -> Option<&str> {
+        self.flatten.as_deref()
+    }
+
+    /// Column type overriding the `SqlType` of the field type.
+    pub fn sql_type(&self) -> Option<&str> {
+        self.sql_type.as_deref()
+    }
+
+    /// Column `DEFAULT` expression.
+    pub fn sql_default(&self) -> Option<&str> {
+        self.sql_default.as_deref()
+    }
+
+    /// Column codecs.
+    pub fn codec(&self) -> Option<&str> {
+        self.codec.as_deref()
+    }
 }
 
 pub fn get_klickhouse_meta_items(
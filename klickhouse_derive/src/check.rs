@@ -1,10 +1,13 @@
-use crate::ast::Container;
+use crate::ast::{Container, Style};
+use crate::attr;
 use crate::ctxt::Ctxt;
 
 /// Cross-cutting checks that require looking at more than a single attrs
 /// object. Simpler checks should happen when parsing and building the attrs.
 pub fn check(cx: &Ctxt, cont: &mut Container) {
     check_from_and_try_from(cx, cont);
+    check_flatten(cx, cont);
+    check_positional(cx, cont);
 }
 
 fn check_from_and_try_from(cx: &Ctxt, cont: &mut Container) {
@@ -15,3 +18,60 @@ fn check_from_and_try_from(cx: &Ctxt, cont: &mut Container) {
         );
     }
 }
+
+fn check_flatten(cx: &Ctxt, cont: &Container) {
+    for field in &cont.data {
+        if field.attrs.flatten().is_none() {
+            continue;
+        }
+        if cont.style == Style::Tuple {
+            cx.error_spanned_by(
+                field.original,
+                "#[klickhouse(flatten)] is not supported on tuple struct fields",
+            );
+        }
+        let conflicts = [
+            ("rename", field.attrs.name().renamed()),
+            (
+                "default",
+                !field.attrs.skip_deserializing()
+                    && !matches!(field.attrs.default(), attr::Default::None),
+            ),
+            (
+                "skip_serializing_if",
+                field.attrs.skip_serializing_if().is_some(),
+            ),
+            ("serialize_with", field.attrs.serialize_with().is_some()),
+            ("deserialize_with", field.attrs.deserialize_with().is_some()),
+            ("type", field.attrs.sql_type().is_some()),
+            ("codec", field.attrs.codec().is_some()),
+            ("default", field.attrs.sql_default().is_some()),
+        ];
+        for (name, conflict) in conflicts {
+            if conflict {
+                cx.error_spanned_by(
+                    field.original,
+                    format!(
+                        "#[klickhouse(flatten)] cannot be combined with #[klickhouse({})]",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// Positional rows are matched to columns by index, so every row must serialize the same fields.
+fn check_positional(cx: &Ctxt, cont: &Container) {
+    if cont.style != Style::Tuple {
+        return;
+    }
+    for field in &cont.data {
+        if field.attrs.skip_serializing_if().is_some() {
+            cx.error_spanned_by(
+                field.original,
+                "#[klickhouse(skip_serializing_if = \"...\")] is not supported on tuple struct fields",
+            );
+        }
+    }
+}
@@ -15,6 +15,9 @@ mod internal;
 mod receiver;
 mod respan;
 mod row;
+mod row_ref;
+mod sql;
+mod sql_type;
 mod symbol;
 
 use proc_macro::TokenStream;
@@ -32,3 +35,27 @@ pub fn derive_serialize(input: TokenStream) -> TokenStream {
         .unwrap_or_else(to_compile_errors)
         .into()
 }
+
+#[proc_macro_derive(RowRef, attributes(klickhouse))]
+pub fn derive_row_ref(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    row_ref::expand_derive_row_ref(&mut input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
+#[proc_macro_derive(ToSql, attributes(klickhouse))]
+pub fn derive_to_sql(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    sql::expand_derive_to_sql(&mut input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
+
+#[proc_macro_derive(FromSql, attributes(klickhouse))]
+pub fn derive_from_sql(input: TokenStream) -> TokenStream {
+    let mut input = parse_macro_input!(input as DeriveInput);
+    sql::expand_derive_from_sql(&mut input)
+        .unwrap_or_else(to_compile_errors)
+        .into()
+}
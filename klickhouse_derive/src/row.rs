@@ -1,4 +1,4 @@
-use crate::ast::{Container, Field};
+use crate::ast::{Container, Field, Style};
 use crate::ctxt::Ctxt;
 use crate::fragment::{Expr, Fragment, Match, Stmts};
 use crate::receiver::replace_receiver;
@@ -43,20 +43,35 @@ fn build_generics(cont: &Container) -> syn::Generics {
 
     match cont.attrs.bound() {
         Some(predicates) => bound::with_where_predicates(&generics, predicates),
-        None => bound::with_bound(
-            cont,
-            &generics,
-            needs_serialize_bound,
-            &[
-                &parse_quote!(::klickhouse::FromSql),
-                &parse_quote!(::klickhouse::ToSql),
-            ],
-        ),
+        None => {
+            let generics = bound::with_bound(
+                cont,
+                &generics,
+                needs_serialize_bound,
+                &[
+                    &parse_quote!(::klickhouse::FromSql),
+                    &parse_quote!(::klickhouse::ToSql),
+                ],
+            );
+            bound::with_bound(
+                cont,
+                &generics,
+                needs_row_bound,
+                &[&parse_quote!(::klickhouse::Row)],
+            )
+        }
     }
 }
 
 fn needs_serialize_bound(field: &attr::Field) -> bool {
-    !field.skip_serializing() && field.serialize_with().is_none() && field.bound().is_none()
+    !field.skip_serializing()
+        && field.serialize_with().is_none()
+        && field.bound().is_none()
+        && field.flatten().is_none()
+}
+
+fn needs_row_bound(field: &attr::Field) -> bool {
+    !field.skip_serializing() && field.bound().is_none() && field.flatten().is_some()
 }
 
 impl Parameters {
@@ -95,6 +110,8 @@ pub fn expand_derive_serialize(
     let (impl_generics, ty_generics, where_clause) = params.generics.split_for_impl();
     let serialize_body = Stmts(serialize_body(&cont, &params));
     let deserialize_body = Stmts(deserialize_body(&cont, &params));
+    let column_names_body = Stmts(column_names_body(&cont));
+    let expected_columns_body = Stmts(expected_columns_body(&cont));
 
     let impl_block = quote! {
         use ::klickhouse::{ToSql as _, FromSql as _};
@@ -107,41 +124,336 @@ pub fn expand_derive_serialize(
             fn serialize_row(self) -> ::klickhouse::Result<Vec<(&'static str, ::klickhouse::Value)>> {
                 #serialize_body
             }
+
+            fn column_names() -> ::std::option::Option<Vec<&'static str>> {
+                #column_names_body
+            }
+
+            fn expected_columns() -> ::std::option::Option<::klickhouse::ExpectedColumns> {
+                #expected_columns_body
+            }
         }
     };
 
-    Ok(dummy::wrap_in_const(impl_block))
+    let column_names_block = column_names_const(&cont);
+    let schema_block = schema_impl(&cont);
+
+    Ok(dummy::wrap_in_const(quote! {
+        #impl_block
+        #column_names_block
+        #schema_block
+    }))
+}
+
+fn deserialized_fields<'a>(cont: &'a Container) -> impl Iterator<Item = &'a Field<'a>> {
+    cont.data
+        .iter()
+        .filter(|field| !field.attrs.skip_deserializing())
+}
+
+fn column_names_body(cont: &Container) -> Fragment {
+    if let Some(type_from) = cont
+        .attrs
+        .type_from()
+        .or_else(|| cont.attrs.type_try_from())
+    {
+        return quote_expr! {
+            <#type_from as ::klickhouse::Row>::column_names()
+        };
+    }
+    if cont.style == Style::Tuple {
+        return quote_expr!(::std::option::Option::None);
+    }
+    let names = deserialized_fields(cont).map(|field| {
+        let field_ty = field.ty;
+        match field.attrs.flatten() {
+            Some("") => quote! {
+                out.extend(<#field_ty as ::klickhouse::Row>::column_names()?);
+            },
+            Some(prefix) => quote! {{
+                static _PREFIXED: ::klickhouse::PrefixedColumnNames = ::klickhouse::PrefixedColumnNames::new(#prefix);
+                for _name in <#field_ty as ::klickhouse::Row>::column_names()? {
+                    out.push(_PREFIXED.get(_name));
+                }
+            }},
+            None => {
+                let name = field.attrs.name().name();
+                quote!(out.push(#name);)
+            }
+        }
+    });
+    quote_block! {
+        let mut out = vec![];
+        #(#names)*
+        ::std::option::Option::Some(out)
+    }
+}
+
+fn expected_columns_body(cont: &Container) -> Fragment {
+    if let Some(type_from) = cont
+        .attrs
+        .type_from()
+        .or_else(|| cont.attrs.type_try_from())
+    {
+        return quote_expr! {
+            <#type_from as ::klickhouse::Row>::expected_columns()
+        };
+    }
+    if cont.style == Style::Tuple {
+        return quote_expr!(::std::option::Option::None);
+    }
+    let container_default = !matches!(cont.attrs.default(), attr::Default::None);
+    let deny_unknown = cont.attrs.deny_unknown_fields();
+    let columns = deserialized_fields(cont).map(|field| {
+        let field_ty = field.ty;
+        let lenient = field.attrs.lenient() || cont.attrs.lenient();
+        if let Some(prefix) = field.attrs.flatten() {
+            let nested = quote!(<#field_ty as ::klickhouse::Row>::expected_columns()?);
+            let nested = if lenient {
+                quote!(::klickhouse::with_lenient_coercion(|| #nested))
+            } else {
+                nested
+            };
+            return quote! {{
+                static _PREFIXED: ::klickhouse::PrefixedColumnNames = ::klickhouse::PrefixedColumnNames::new(#prefix);
+                let _nested = #nested;
+                out.deny_unknown &= _nested.deny_unknown;
+                for _column in _nested.columns {
+                    out.columns.push(::klickhouse::ExpectedColumn {
+                        name: _PREFIXED.get(_column.name),
+                        .._column
+                    });
+                }
+            }};
+        }
+        let name = field.attrs.name().name();
+        let expected = match field.attrs.sql_type() {
+            Some(type_) => {
+                quote!(<::klickhouse::Type as ::std::str::FromStr>::from_str(#type_).ok())
+            }
+            None => quote! {{
+                use ::klickhouse::{ExpectedSqlType as _, ExpectedTypeFallback as _};
+                (&::klickhouse::ExpectedType::<#field_ty>::new()).expected_type()
+            }},
+        };
+        let optional = container_default || !matches!(field.attrs.default(), attr::Default::None);
+        // a `deserialize_with` function can only be checked against the `type` attribute
+        let accepts = match (field.attrs.deserialize_with(), field.attrs.sql_type()) {
+            (None, _) => quote!(<#field_ty as ::klickhouse::FromSql>::accepts_type(_type_)),
+            (Some(_), Some(type_)) => quote! {
+                <::klickhouse::Type as ::std::str::FromStr>::from_str(#type_)
+                    .map_or(true, |_expected| _type_ == _expected.strip_low_cardinality())
+            },
+            (Some(_), None) => quote!(true),
+        };
+        let accepts = if lenient {
+            quote!(::klickhouse::with_lenient_coercion(|| #accepts))
+        } else {
+            accepts
+        };
+        quote! {
+            out.columns.push(::klickhouse::ExpectedColumn {
+                name: #name,
+                expected: #expected,
+                rust_type: ::std::any::type_name::<#field_ty>(),
+                optional: #optional,
+                accepts: |_type_| #accepts,
+            });
+        }
+    });
+    quote_block! {
+        let mut out = ::klickhouse::ExpectedColumns {
+            columns: vec![],
+            deny_unknown: #deny_unknown,
+        };
+        #(#columns)*
+        ::std::option::Option::Some(out)
+    }
+}
+
+/// Adds an inherent `COLUMN_NAMES` constant for structs with named fields.
+/// Flattened columns are only known at runtime, so structs with flattened fields only have `Row::column_names`.
+fn column_names_const(cont: &Container) -> TokenStream {
+    if cont.style != Style::Struct
+        || cont.attrs.type_from().is_some()
+        || cont.attrs.type_try_from().is_some()
+        || deserialized_fields(cont).any(|field| field.attrs.flatten().is_some())
+    {
+        return TokenStream::new();
+    }
+    let ident = &cont.ident;
+    let generics = bound::without_defaults(cont.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let names = deserialized_fields(cont).map(|field| field.attrs.name().name());
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Names of the columns this row is deserialized from, in order.
+            pub const COLUMN_NAMES: &'static [&'static str] = &[#(#names),*];
+        }
+    }
+}
+
+/// Implements `RowSchema` and the inherent schema functions for structs with named fields.
+/// Field type bounds are higher-ranked, so that they are only checked where the schema is used.
+fn schema_impl(cont: &Container) -> TokenStream {
+    if cont.style != Style::Struct
+        || cont.attrs.type_from().is_some()
+        || cont.attrs.type_try_from().is_some()
+        || cont.attrs.type_into().is_some()
+    {
+        return TokenStream::new();
+    }
+    let ident = &cont.ident;
+    let generics = bound::without_defaults(cont.generics);
+    let mut schema_generics = generics.clone();
+    let mut columns = vec![];
+    for field in cont
+        .data
+        .iter()
+        .filter(|field| !field.attrs.skip_serializing())
+    {
+        let field_ty = field.ty;
+        if let Some(prefix) = field.attrs.flatten() {
+            schema_generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(for<'__schema> #field_ty: ::klickhouse::RowSchema));
+            columns.push(if prefix.is_empty() {
+                quote! {
+                    out.extend(<#field_ty as ::klickhouse::RowSchema>::columns());
+                }
+            } else {
+                quote! {{
+                    static _PREFIXED: ::klickhouse::PrefixedColumnNames = ::klickhouse::PrefixedColumnNames::new(#prefix);
+                    for _column in <#field_ty as ::klickhouse::RowSchema>::columns() {
+                        out.push(::klickhouse::ColumnDef {
+                            name: _PREFIXED.get(_column.name),
+                            .._column
+                        });
+                    }
+                }}
+            });
+            continue;
+        }
+        let name = field.attrs.name().name();
+        let type_ = match field.attrs.sql_type() {
+            Some(type_) => quote!(::klickhouse::parse_column_type(#name, #type_)),
+            None => {
+                schema_generics
+                    .make_where_clause()
+                    .predicates
+                    .push(parse_quote!(for<'__schema> #field_ty: ::klickhouse::SqlType));
+                quote!(<#field_ty as ::klickhouse::SqlType>::sql_type())
+            }
+        };
+        let default = option_str(field.attrs.sql_default());
+        let codec = option_str(field.attrs.codec());
+        columns.push(quote! {
+            out.push(::klickhouse::ColumnDef {
+                name: #name,
+                type_: #type_,
+                default: #default,
+                codec: #codec,
+            });
+        });
+    }
+    let order_by = cont.attrs.order_by().map(|order_by| {
+        quote! {
+            fn order_by() -> ::std::option::Option<&'static str> {
+                ::std::option::Option::Some(#order_by)
+            }
+        }
+    });
+    let partition_by = cont.attrs.partition_by().map(|partition_by| {
+        quote! {
+            fn partition_by() -> ::std::option::Option<&'static str> {
+                ::std::option::Option::Some(#partition_by)
+            }
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (schema_impl_generics, _, schema_where_clause) = schema_generics.split_for_impl();
+    quote! {
+        #[automatically_derived]
+        impl #schema_impl_generics ::klickhouse::RowSchema for #ident #ty_generics #schema_where_clause {
+            fn columns() -> Vec<::klickhouse::ColumnDef> {
+                let mut out = vec![];
+                #(#columns)*
+                out
+            }
+
+            #order_by
+            #partition_by
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Column names and types of this row.
+            pub fn clickhouse_schema() -> Vec<(&'static str, ::klickhouse::Type)>
+            where
+                for<'__schema> Self: ::klickhouse::RowSchema,
+            {
+                <Self as ::klickhouse::RowSchema>::columns()
+                    .into_iter()
+                    .map(|column| (column.name, column.type_))
+                    .collect()
+            }
+
+            /// `CREATE TABLE` statement for this row, see `klickhouse::create_table_sql`.
+            pub fn create_table_sql(table: &str, engine: &str) -> String
+            where
+                for<'__schema> Self: ::klickhouse::RowSchema,
+            {
+                ::klickhouse::create_table_sql::<Self>(table, engine)
+            }
+        }
+    }
+}
+
+fn option_str(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
 }
 
 fn serialize_body(cont: &Container, params: &Parameters) -> Fragment {
     if let Some(type_into) = cont.attrs.type_into() {
         serialize_into(params, type_into)
     } else {
-        serialize_struct(params, &cont.data[..], &cont.attrs)
+        serialize_struct(params, &cont.data[..], cont.style, &cont.attrs)
     }
 }
 
 fn serialize_into(params: &Parameters, type_into: &syn::Type) -> Fragment {
     let self_var = &params.self_var;
     quote_block! {
-        ::klickhouse::Row::serialize_row(
-            &::std::convert::Into::<#type_into>::into(#self_var)
+        <#type_into as ::klickhouse::Row>::serialize_row(
+            ::std::convert::Into::<#type_into>::into(#self_var)
         )
     }
 }
 
-fn serialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Container) -> Fragment {
-    assert!(fields.len() as u64 <= u64::from(u32::max_value()));
+fn serialize_struct(
+    params: &Parameters,
+    fields: &[Field],
+    style: Style,
+    cattrs: &attr::Container,
+) -> Fragment {
+    assert!(fields.len() as u64 <= u64::from(u32::MAX));
 
-    serialize_struct_as_struct(params, fields, cattrs)
+    serialize_struct_as_struct(params, fields, style, cattrs)
 }
 
 fn serialize_struct_as_struct(
     params: &Parameters,
     fields: &[Field],
+    style: Style,
     _cattrs: &attr::Container,
 ) -> Fragment {
-    let serialize_fields = serialize_struct_visitor(fields, params);
+    let serialize_fields = serialize_struct_visitor(fields, style, params);
 
     quote_block! {
         let mut out = vec![];
@@ -150,7 +462,11 @@ fn serialize_struct_as_struct(
     }
 }
 
-fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenStream> {
+fn serialize_struct_visitor(
+    fields: &[Field],
+    style: Style,
+    params: &Parameters,
+) -> Vec<TokenStream> {
     fields
         .iter()
         .filter(|&field| !field.attrs.skip_serializing())
@@ -159,7 +475,11 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
 
             let field_expr = get_member(params, member);
 
-            let key_expr = field.attrs.name().name();
+            // positional rows are serialized without names
+            let key_expr = match style {
+                Style::Struct => field.attrs.name().name(),
+                Style::Tuple => String::new(),
+            };
 
             let skip = field
                 .attrs
@@ -167,6 +487,21 @@ fn serialize_struct_visitor(fields: &[Field], params: &Parameters) -> Vec<TokenS
                 .map(|path| quote!(#path(&#field_expr)));
 
             let field_ty = field.ty;
+            if let Some(prefix) = field.attrs.flatten() {
+                return if prefix.is_empty() {
+                    quote! {
+                        out.extend(<#field_ty as ::klickhouse::Row>::serialize_row(#field_expr)?);
+                    }
+                } else {
+                    quote! {{
+                        // prefixed names are built once per field, not per row
+                        static _PREFIXED: ::klickhouse::PrefixedColumnNames = ::klickhouse::PrefixedColumnNames::new(#prefix);
+                        for (_name, _value) in <#field_ty as ::klickhouse::Row>::serialize_row(#field_expr)? {
+                            out.push((_PREFIXED.get(_name), _value));
+                        }
+                    }}
+                };
+            }
             let ser = match field.attrs.serialize_with() {
                 Some(path) => {
                     quote! {
@@ -208,7 +543,7 @@ fn deserialize_body(cont: &Container, params: &Parameters) -> Fragment {
     } else if let Some(type_try_from) = cont.attrs.type_try_from() {
         deserialize_try_from(type_try_from)
     } else {
-        deserialize_struct(params, &cont.data[..], &cont.attrs)
+        deserialize_struct(params, &cont.data[..], cont.style, &cont.attrs)
     }
 }
 
@@ -228,7 +563,12 @@ fn deserialize_try_from(type_try_from: &syn::Type) -> Fragment {
     }
 }
 
-fn deserialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Container) -> Fragment {
+fn deserialize_struct(
+    params: &Parameters,
+    fields: &[Field],
+    style: Style,
+    cattrs: &attr::Container,
+) -> Fragment {
     let this = &params.this;
     // let (de_impl_generics, de_ty_generics, ty_generics, where_clause) =
     //     split_with_de_lifetime(params);
@@ -237,7 +577,7 @@ fn deserialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Cont
 
     let type_path = construct;
 
-    let visit_map = deserialize_map(&type_path, fields, cattrs);
+    let visit_map = deserialize_map(&type_path, fields, style, cattrs);
     let visit_map = Stmts(visit_map);
 
     quote_block! {
@@ -245,13 +585,14 @@ fn deserialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Cont
     }
 }
 
-fn field_i(i: usize) -> Ident {
+pub(crate) fn field_i(i: usize) -> Ident {
     Ident::new(&format!("__field{}", i), Span::call_site())
 }
 
 fn deserialize_map(
     struct_path: &TokenStream,
     fields: &[Field],
+    style: Style,
     cattrs: &attr::Container,
 ) -> Fragment {
     // Create the field names for the fields.
@@ -261,10 +602,17 @@ fn deserialize_map(
         .map(|(i, field)| (field, field_i(i)))
         .collect();
 
+    // Flattened fields are deserialized from the columns matching their prefix.
+    let flatten_names: Vec<_> = fields_names
+        .iter()
+        .filter(|&&(field, _)| !field.attrs.skip_deserializing())
+        .filter_map(|(field, name)| Some((*field, name, field.attrs.flatten()?)))
+        .collect();
+
     // Declare each field that will be deserialized.
     let let_values = fields_names
         .iter()
-        .filter(|&&(field, _)| !field.attrs.skip_deserializing())
+        .filter(|&&(field, _)| !field.attrs.skip_deserializing() && field.attrs.flatten().is_none())
         .map(|(field, name)| {
             let field_ty = field.ty;
             quote! {
@@ -275,9 +623,15 @@ fn deserialize_map(
     // Match arms to extract a value for a field.
     let value_arms = fields_names
         .iter()
-        .filter(|&&(field, _)| !field.attrs.skip_deserializing())
-        .map(|(field, name)| {
+        .filter(|&&(field, _)| !field.attrs.skip_deserializing() && field.attrs.flatten().is_none())
+        .enumerate()
+        .map(|(index, (field, name))| {
             let deser_name = field.attrs.name().name();
+            // positional rows are matched by column index
+            let key = match style {
+                Style::Struct => quote!(#deser_name),
+                Style::Tuple => quote!(#index),
+            };
 
             let visit = match field.attrs.deserialize_with() {
                 None => {
@@ -290,8 +644,13 @@ fn deserialize_map(
                     quote_spanned!(span=> #path(_type_, _value)?)
                 }
             };
+            let visit = if field.attrs.lenient() || cattrs.lenient() {
+                quote!(::klickhouse::with_lenient_coercion(|| -> ::klickhouse::Result<_> { ::klickhouse::Result::Ok(#visit) })?)
+            } else {
+                visit
+            };
             quote! {
-                #deser_name => {
+                #key => {
                     if ::std::option::Option::is_some(&#name) {
                         return ::klickhouse::Result::Err(::klickhouse::errors::duplicate_field(#deser_name));
                     }
@@ -301,30 +660,79 @@ fn deserialize_map(
         });
 
     // Visit ignored values to consume them
-    let ignored_arm = if cattrs.deny_unknown_fields() {
+    let ignored_arm = if style == Style::Tuple {
         quote! {
             _ => {
-                return ::klickhouse::Result::Err(::klickhouse::errors::unknown_field(_name));
+                return ::klickhouse::Result::Err(::klickhouse::errors::unexpected_column(_index, _name));
             }
         }
     } else {
-        quote! {
-            _ => { }
+        let unknown = if cattrs.deny_unknown_fields() {
+            quote! {
+                return ::klickhouse::Result::Err(::klickhouse::errors::unknown_field(_name));
+            }
+        } else {
+            quote!()
+        };
+        if flatten_names.is_empty() {
+            quote! {
+                _ => {
+                    #unknown
+                }
+            }
+        } else {
+            // each flattened field whose prefix matches gets the column, cloned for all but the last
+            let prefixes = flatten_names.iter().map(|(_, _, prefix)| prefix);
+            let dispatch = flatten_names
+                .iter()
+                .enumerate()
+                .map(|(i, (_, name, prefix))| {
+                    quote! {
+                        if let ::std::option::Option::Some(_column) = _name.strip_prefix(#prefix) {
+                            let _value = if _last == ::std::option::Option::Some(#i) {
+                                _value.take()
+                            } else {
+                                ::std::clone::Clone::clone(&_value)
+                            };
+                            #name.push((_column, _type_, _value.unwrap()));
+                        }
+                    }
+                });
+            quote! {
+                _ => {
+                    let _last = [#(#prefixes),*].iter().rposition(|prefix| _name.starts_with(prefix));
+                    if _last.is_none() {
+                        #unknown
+                    }
+                    let mut _value = ::std::option::Option::Some(_value);
+                    #(#dispatch)*
+                }
+            }
         }
     };
 
-    let match_keys = quote! {
-        for (_name, _type_, _value) in map {
-            match _name {
-                #(#value_arms)*
-                #ignored_arm
+    let match_keys = match style {
+        Style::Struct => quote! {
+            for (_name, _type_, _value) in map {
+                match _name {
+                    #(#value_arms)*
+                    #ignored_arm
+                }
             }
-        }
+        },
+        Style::Tuple => quote! {
+            for (_index, (_name, _type_, _value)) in ::std::iter::IntoIterator::into_iter(map).enumerate() {
+                match _index {
+                    #(#value_arms)*
+                    #ignored_arm
+                }
+            }
+        },
     };
 
     let extract_values = fields_names
         .iter()
-        .filter(|&&(field, _)| !field.attrs.skip_deserializing())
+        .filter(|&&(field, _)| !field.attrs.skip_deserializing() && field.attrs.flatten().is_none())
         .map(|(field, name)| {
             let missing_expr = Match(expr_is_missing(field, cattrs));
 
@@ -346,6 +754,25 @@ fn deserialize_map(
         }
     });
 
+    let let_flatten = flatten_names.iter().map(|(_, name, _)| {
+        quote! {
+            let mut #name: ::std::vec::Vec<(&str, &::klickhouse::Type, ::klickhouse::Value)> = ::std::vec::Vec::new();
+        }
+    });
+
+    let extract_flatten = flatten_names.iter().map(|(field, name, _)| {
+        let field_ty = field.ty;
+        let visit = quote!(<#field_ty as ::klickhouse::Row>::deserialize_row(#name)?);
+        let visit = if field.attrs.lenient() || cattrs.lenient() {
+            quote!(::klickhouse::with_lenient_coercion(|| -> ::klickhouse::Result<_> { ::klickhouse::Result::Ok(#visit) })?)
+        } else {
+            visit
+        };
+        quote! {
+            let #name = #visit;
+        }
+    });
+
     let let_default = match cattrs.default() {
         attr::Default::Default => Some(quote!(
             let __default: Self = ::std::default::Default::default();
@@ -365,17 +792,21 @@ fn deserialize_map(
     quote_block! {
         #(#let_values)*
 
+        #(#let_flatten)*
+
         #match_keys
 
         #let_default
 
         #(#extract_values)*
 
+        #(#extract_flatten)*
+
         ::klickhouse::Result::Ok(#result)
     }
 }
 
-fn expr_is_missing(field: &Field, cattrs: &attr::Container) -> Fragment {
+pub(crate) fn expr_is_missing(field: &Field, cattrs: &attr::Container) -> Fragment {
     match field.attrs.default() {
         attr::Default::Default => {
             let span = field.original.span();
@@ -0,0 +1,194 @@
+use crate::ast::{Container, Style};
+use crate::ctxt::Ctxt;
+use crate::fragment::{Expr, Match};
+use crate::receiver::replace_receiver;
+use crate::row::{expr_is_missing, field_i};
+use crate::{attr, bound, dummy};
+use proc_macro2::TokenStream;
+use syn::spanned::Spanned;
+
+pub fn expand_derive_row_ref(input: &mut syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    replace_receiver(input);
+
+    let ctxt = Ctxt::new();
+    let cont = match Container::from_ast(&ctxt, input) {
+        Some(cont) => cont,
+        None => return Err(ctxt.check().unwrap_err()),
+    };
+    check_row_ref(&ctxt, &cont);
+    ctxt.check()?;
+
+    let ident = &cont.ident;
+    let generics = bound::without_defaults(cont.generics);
+    let (_, ty_generics, _) = generics.split_for_impl();
+    // rows borrow with the lifetime of the struct, or an unused one if it has none
+    let mut impl_generics = generics.clone();
+    let lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime: syn::Lifetime = parse_quote!('__a);
+            impl_generics.params.insert(
+                0,
+                syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime.clone())),
+            );
+            lifetime
+        }
+    };
+
+    let fields: Vec<_> = cont
+        .data
+        .iter()
+        .enumerate()
+        .map(|(i, field)| (field, field_i(i)))
+        .collect();
+    let deserialized = || {
+        fields
+            .iter()
+            .filter(|(field, _)| !field.attrs.skip_deserializing())
+    };
+
+    {
+        let where_clause = impl_generics.make_where_clause();
+        for (field, _) in deserialized() {
+            let field_ty = field.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(#field_ty: ::klickhouse::FromSqlRef<#lifetime>));
+        }
+    }
+    let (impl_generics, _, where_clause) = impl_generics.split_for_impl();
+
+    let let_values = deserialized().map(|(field, name)| {
+        let field_ty = field.ty;
+        quote! {
+            let mut #name: ::std::option::Option<#field_ty> = ::std::option::Option::None;
+        }
+    });
+
+    let value_arms = deserialized().enumerate().map(|(index, (field, name))| {
+        let deser_name = field.attrs.name().name();
+        // positional rows are matched by column index
+        let key = match cont.style {
+            Style::Struct => quote!(#deser_name),
+            Style::Tuple => quote!(#index),
+        };
+        let field_ty = field.ty;
+        let span = field.original.span();
+        let visit = quote_spanned!(span=> <#field_ty as ::klickhouse::FromSqlRef<#lifetime>>::from_sql_ref(_type_, _value)?);
+        quote! {
+            #key => {
+                if ::std::option::Option::is_some(&#name) {
+                    return ::klickhouse::Result::Err(::klickhouse::errors::duplicate_field(#deser_name));
+                }
+                #name = ::std::option::Option::Some(#visit);
+            }
+        }
+    });
+
+    let ignored_arm = match cont.style {
+        Style::Tuple => quote! {
+            _ => {
+                return ::klickhouse::Result::Err(::klickhouse::errors::unexpected_column(_index, _name));
+            }
+        },
+        Style::Struct if cont.attrs.deny_unknown_fields() => quote! {
+            _ => {
+                return ::klickhouse::Result::Err(::klickhouse::errors::unknown_field(_name));
+            }
+        },
+        Style::Struct => quote!(_ => {}),
+    };
+
+    let key = match cont.style {
+        Style::Struct => quote!(_name),
+        Style::Tuple => quote!(_index),
+    };
+
+    let let_default = match cont.attrs.default() {
+        attr::Default::Default => Some(quote!(
+            let __default: Self = ::std::default::Default::default();
+        )),
+        attr::Default::Path(path) => Some(quote!(
+            let __default: Self = #path();
+        )),
+        attr::Default::None => None,
+    };
+
+    let extract_values = deserialized().map(|(field, name)| {
+        let missing_expr = Match(expr_is_missing(field, &cont.attrs));
+        quote! {
+            let #name = match #name {
+                ::std::option::Option::Some(#name) => #name,
+                ::std::option::Option::None => #missing_expr
+            };
+        }
+    });
+
+    let result = fields.iter().map(|(field, name)| {
+        let member = &field.member;
+        if field.attrs.skip_deserializing() {
+            let value = Expr(expr_is_missing(field, &cont.attrs));
+            quote!(#member: #value)
+        } else {
+            quote!(#member: #name)
+        }
+    });
+
+    let impl_block = quote! {
+        #[automatically_derived]
+        impl #impl_generics ::klickhouse::RowRef<#lifetime> for #ident #ty_generics #where_clause {
+            fn deserialize_row_ref(row: &[(&#lifetime str, &#lifetime ::klickhouse::Type, &#lifetime ::klickhouse::Value)]) -> ::klickhouse::Result<Self> {
+                #(#let_values)*
+
+                for (_index, &(_name, _type_, _value)) in row.iter().enumerate() {
+                    match #key {
+                        #(#value_arms)*
+                        #ignored_arm
+                    }
+                }
+
+                #let_default
+
+                #(#extract_values)*
+
+                ::klickhouse::Result::Ok(#ident { #(#result),* })
+            }
+        }
+    };
+
+    Ok(dummy::wrap_in_const(impl_block))
+}
+
+/// Rejects attributes that only apply to owned rows.
+fn check_row_ref(cx: &Ctxt, cont: &Container) {
+    if cont.generics.lifetimes().count() > 1 {
+        cx.error_spanned_by(
+            cont.generics,
+            "#[derive(RowRef)] supports at most one lifetime parameter",
+        );
+    }
+    if cont.attrs.type_from().is_some() || cont.attrs.type_try_from().is_some() {
+        cx.error_spanned_by(
+            cont.original,
+            "#[klickhouse(from = \"...\")] and #[klickhouse(try_from = \"...\")] are not supported by #[derive(RowRef)]",
+        );
+    }
+    for field in &cont.data {
+        let unsupported = [
+            ("flatten", field.attrs.flatten().is_some()),
+            ("deserialize_with", field.attrs.deserialize_with().is_some()),
+            ("lenient", field.attrs.lenient() || cont.attrs.lenient()),
+        ];
+        for (name, unsupported) in unsupported {
+            if unsupported {
+                cx.error_spanned_by(
+                    field.original,
+                    format!(
+                        "#[klickhouse({})] is not supported by #[derive(RowRef)]",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+}
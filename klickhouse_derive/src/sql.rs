@@ -0,0 +1,268 @@
+use crate::ast::{Container, Field};
+use crate::ctxt::Ctxt;
+use crate::receiver::replace_receiver;
+use crate::{bound, dummy};
+use proc_macro2::TokenStream;
+
+/// Which of `ToSql` or `FromSql` is derived.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Direction {
+    To,
+    From,
+}
+
+pub fn expand_derive_to_sql(input: &mut syn::DeriveInput) -> Result<TokenStream, Vec<syn::Error>> {
+    expand(input, Direction::To)
+}
+
+pub fn expand_derive_from_sql(
+    input: &mut syn::DeriveInput,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    expand(input, Direction::From)
+}
+
+fn expand(
+    input: &mut syn::DeriveInput,
+    direction: Direction,
+) -> Result<TokenStream, Vec<syn::Error>> {
+    replace_receiver(input);
+
+    let ctxt = Ctxt::new();
+    let cont = match Container::from_ast(&ctxt, input) {
+        Some(cont) => cont,
+        None => return Err(ctxt.check().unwrap_err()),
+    };
+    check_sql(&ctxt, &cont, direction);
+    ctxt.check()?;
+
+    let ident = &cont.ident;
+    let mut generics = bound::without_defaults(cont.generics);
+    let (_, ty_generics, _) = cont.generics.split_for_impl();
+    let trait_path = match direction {
+        Direction::To => quote!(::klickhouse::ToSql),
+        Direction::From => quote!(::klickhouse::FromSql),
+    };
+    match cont.attrs.bound() {
+        Some(predicates) => generics = bound::with_where_predicates(&generics, predicates),
+        None => {
+            let where_clause = generics.make_where_clause();
+            for field in fields_with_trait(&cont, direction) {
+                let field_ty = field.ty;
+                where_clause
+                    .predicates
+                    .push(parse_quote!(#field_ty: #trait_path));
+            }
+        }
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let impl_block = match direction {
+        Direction::To => {
+            let body = to_sql_body(&cont);
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::klickhouse::ToSql for #ident #ty_generics #where_clause {
+                    fn to_sql(self) -> ::klickhouse::Result<::klickhouse::Value> {
+                        #body
+                    }
+                }
+            }
+        }
+        Direction::From => {
+            let body = from_sql_body(&cont);
+            let accepts_type_body = accepts_type_body(&cont);
+            quote! {
+                #[automatically_derived]
+                impl #impl_generics ::klickhouse::FromSql for #ident #ty_generics #where_clause {
+                    fn from_sql(type_: &::klickhouse::Type, value: ::klickhouse::Value) -> ::klickhouse::Result<Self> {
+                        #body
+                    }
+
+                    fn accepts_type(type_: &::klickhouse::Type) -> bool {
+                        #accepts_type_body
+                    }
+                }
+            }
+        }
+    };
+    Ok(dummy::wrap_in_const(impl_block))
+}
+
+/// Fields converted with the derived trait, rather than a `with` function or a container conversion.
+fn fields_with_trait<'a>(
+    cont: &'a Container<'a>,
+    direction: Direction,
+) -> impl Iterator<Item = &'a Field<'a>> {
+    let converted = match direction {
+        Direction::To => cont.attrs.type_into().is_none(),
+        Direction::From => cont.attrs.type_from().is_none() && cont.attrs.type_try_from().is_none(),
+    };
+    cont.data.iter().filter(move |field| {
+        converted
+            && match direction {
+                Direction::To => field.attrs.serialize_with().is_none(),
+                Direction::From => field.attrs.deserialize_with().is_none(),
+            }
+    })
+}
+
+fn to_sql_body(cont: &Container) -> TokenStream {
+    if let Some(type_into) = cont.attrs.type_into() {
+        return quote! {
+            <#type_into as ::klickhouse::ToSql>::to_sql(::std::convert::Into::<#type_into>::into(self))
+        };
+    }
+    let visit = |field: &Field| {
+        let member = &field.member;
+        match field.attrs.serialize_with() {
+            Some(path) => quote!(#path(self.#member)),
+            None => {
+                let field_ty = field.ty;
+                quote!(<#field_ty as ::klickhouse::ToSql>::to_sql(self.#member))
+            }
+        }
+    };
+    if cont.attrs.transparent() {
+        return visit(&cont.data[0]);
+    }
+    let values = cont.data.iter().map(visit);
+    quote!(::klickhouse::Result::Ok(::klickhouse::Value::Tuple(
+        vec![#(#values?),*]
+    )))
+}
+
+fn from_sql_body(cont: &Container) -> TokenStream {
+    if let Some(type_from) = cont.attrs.type_from() {
+        return quote! {
+            ::klickhouse::Result::map(
+                <#type_from as ::klickhouse::FromSql>::from_sql(type_, value),
+                ::std::convert::From::from)
+        };
+    }
+    if let Some(type_try_from) = cont.attrs.type_try_from() {
+        return quote! {
+            ::klickhouse::Result::and_then(
+                <#type_try_from as ::klickhouse::FromSql>::from_sql(type_, value),
+                |v| ::std::convert::TryFrom::try_from(v).map_err(::std::convert::Into::into))
+        };
+    }
+    let ident = &cont.ident;
+    let visit = |field: &Field, type_: TokenStream, value: TokenStream| match field
+        .attrs
+        .deserialize_with()
+    {
+        Some(path) => quote!(#path(#type_, #value)?),
+        None => {
+            let field_ty = field.ty;
+            quote!(<#field_ty as ::klickhouse::FromSql>::from_sql(#type_, #value)?)
+        }
+    };
+    if cont.attrs.transparent() {
+        let field = &cont.data[0];
+        let member = &field.member;
+        let value = visit(field, quote!(type_), quote!(value));
+        return quote!(::klickhouse::Result::Ok(#ident { #member: #value }));
+    }
+    let len = cont.data.len();
+    let values = cont.data.iter().enumerate().map(|(i, field)| {
+        let member = &field.member;
+        let value = visit(
+            field,
+            quote!(_types[#i].strip_low_cardinality()),
+            quote!(_values.next().unwrap()),
+        );
+        quote!(#member: #value)
+    });
+    quote! {
+        let _types = match type_ {
+            ::klickhouse::Type::Tuple(types) => types,
+            type_ => return ::klickhouse::Result::Err(::klickhouse::errors::unexpected_type(type_)),
+        };
+        let _values = match value {
+            ::klickhouse::Value::Tuple(values) => values,
+            value => return ::klickhouse::Result::Err(::klickhouse::errors::unexpected_value(type_, &value)),
+        };
+        if _types.len() != #len || _values.len() != #len {
+            return ::klickhouse::Result::Err(::klickhouse::errors::tuple_length(_values.len(), #len));
+        }
+        let mut _values = ::std::iter::IntoIterator::into_iter(_values);
+        ::klickhouse::Result::Ok(#ident { #(#values),* })
+    }
+}
+
+/// Mirrors `from_sql_body`. Fields with a `deserialize_with` function accept any type.
+fn accepts_type_body(cont: &Container) -> TokenStream {
+    if let Some(type_from) = cont
+        .attrs
+        .type_from()
+        .or_else(|| cont.attrs.type_try_from())
+    {
+        return quote!(<#type_from as ::klickhouse::FromSql>::accepts_type(type_));
+    }
+    let visit = |field: &Field, type_: TokenStream| match field.attrs.deserialize_with() {
+        Some(_) => quote!(true),
+        None => {
+            let field_ty = field.ty;
+            quote!(<#field_ty as ::klickhouse::FromSql>::accepts_type(#type_))
+        }
+    };
+    if cont.attrs.transparent() {
+        return visit(&cont.data[0], quote!(type_));
+    }
+    let len = cont.data.len();
+    let fields = cont
+        .data
+        .iter()
+        .enumerate()
+        .map(|(i, field)| visit(field, quote!(_types[#i].strip_low_cardinality())));
+    quote! {
+        match type_ {
+            ::klickhouse::Type::Tuple(_types) if _types.len() == #len => #(#fields)&&*,
+            _ => false,
+        }
+    }
+}
+
+/// Rejects row attributes that have no meaning for a single value.
+fn check_sql(cx: &Ctxt, cont: &Container, direction: Direction) {
+    let converted = match direction {
+        Direction::To => cont.attrs.type_into().is_some(),
+        Direction::From => cont.attrs.type_from().is_some() || cont.attrs.type_try_from().is_some(),
+    };
+    if cont.data.is_empty() && !converted {
+        cx.error_spanned_by(
+            cont.original,
+            "#[derive(ToSql)] and #[derive(FromSql)] need at least one field",
+        );
+    }
+    if cont.attrs.transparent() && cont.data.len() != 1 {
+        cx.error_spanned_by(
+            cont.original,
+            "#[klickhouse(transparent)] requires a struct with exactly one field",
+        );
+    }
+    for field in &cont.data {
+        let unsupported = [
+            ("flatten", field.attrs.flatten().is_some()),
+            (
+                "skip",
+                field.attrs.skip_serializing() || field.attrs.skip_deserializing(),
+            ),
+            (
+                "skip_serializing_if",
+                field.attrs.skip_serializing_if().is_some(),
+            ),
+        ];
+        for (name, unsupported) in unsupported {
+            if unsupported {
+                cx.error_spanned_by(
+                    field.original,
+                    format!(
+                        "#[klickhouse({})] is not supported by #[derive(ToSql)] and #[derive(FromSql)], as fields are converted by position",
+                        name
+                    ),
+                );
+            }
+        }
+    }
+}
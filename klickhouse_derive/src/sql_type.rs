@@ -0,0 +1,177 @@
+//! Compile time validation of `#[klickhouse(type = "...")]` attributes.
+//! Mirrors the grammar of `klickhouse::Type::from_str`, which parses the type at runtime.
+
+/// Checks that `input` parses as a `klickhouse::Type`, returning the reason if not.
+pub fn validate(input: &str) -> Result<(), String> {
+    let (ident, following) = eat_identifier(input);
+    if ident.is_empty() {
+        return Err(format!("invalid empty identifier for type: '{}'", input));
+    }
+    let following = following.trim();
+    if following.is_empty() {
+        return match ident {
+            "Int8" | "Int16" | "Int32" | "Int64" | "Int128" | "Int256" | "UInt8" | "UInt16"
+            | "UInt32" | "UInt64" | "UInt128" | "UInt256" | "Float32" | "Float64" | "String"
+            | "UUID" | "Date" | "DateTime" | "IPv4" | "IPv6" => Ok(()),
+            _ => Err(format!("invalid type name: '{}'", ident)),
+        };
+    }
+    let args = parse_args(following)?;
+    let arg_count = |count: usize| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(format!("bad arg count for {}", ident))
+        }
+    };
+    match ident {
+        "Decimal" => {
+            arg_count(2)?;
+            let precision = parse_usize(args[0])?;
+            parse_usize(args[1])?;
+            if precision > 76 {
+                return Err("bad decimal spec".to_string());
+            }
+        }
+        "Decimal32" | "Decimal64" | "Decimal128" | "Decimal256" | "FixedString" => {
+            arg_count(1)?;
+            parse_usize(args[0])?;
+        }
+        "DateTime" => {
+            arg_count(1)?;
+            parse_timezone(args[0], ident)?;
+        }
+        "DateTime64" => {
+            if args.len() != 1 && args.len() != 2 {
+                return Err(format!("bad arg count for {}", ident));
+            }
+            parse_usize(args[0])?;
+            if let Some(timezone) = args.get(1) {
+                parse_timezone(timezone, ident)?;
+            }
+        }
+        "Enum8" => validate_enum_entries(&args, |x| x.parse::<i8>().is_ok())?,
+        "Enum16" => validate_enum_entries(&args, |x| x.parse::<i16>().is_ok())?,
+        "LowCardinality" | "Array" | "Nullable" => {
+            arg_count(1)?;
+            validate(args[0])?;
+        }
+        "Map" => {
+            arg_count(2)?;
+            validate(args[0])?;
+            validate(args[1])?;
+        }
+        "Tuple" => {
+            for arg in args {
+                validate(arg)?;
+            }
+        }
+        "Nested" => return Err("unsupported type: Nested".to_string()),
+        _ => return Err(format!("invalid type with arguments: '{}'", ident)),
+    }
+    Ok(())
+}
+
+fn eat_identifier(input: &str) -> (&str, &str) {
+    for (i, c) in input.char_indices() {
+        if !(c.is_alphabetic() || c == '_' || c == '$' || (i > 0 && c.is_numeric())) {
+            return (&input[..i], &input[i..]);
+        }
+    }
+    (input, "")
+}
+
+/// Splits the parenthesized arguments of a type, skipping commas and parens in quoted enum names.
+fn parse_args(input: &str) -> Result<Vec<&str>, String> {
+    if !input.starts_with('(') || !input.ends_with(')') {
+        return Err("malformed arguments to type".to_string());
+    }
+    let input = input[1..input.len() - 1].trim();
+    let mut out = vec![];
+    let mut in_parens = 0usize;
+    let mut last_start = 0;
+    let mut in_quotes = false;
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if in_quotes {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' => in_quotes = false,
+                _ => (),
+            }
+            continue;
+        }
+        match c {
+            '\'' => in_quotes = true,
+            ',' if in_parens == 0 => {
+                out.push(input[last_start..i].trim());
+                last_start = i + 1;
+            }
+            '(' => in_parens += 1,
+            ')' => {
+                in_parens = in_parens
+                    .checked_sub(1)
+                    .ok_or_else(|| "mismatched parenthesis".to_string())?;
+            }
+            _ => (),
+        }
+    }
+    if in_parens != 0 {
+        return Err("mismatched parenthesis".to_string());
+    }
+    if in_quotes {
+        return Err("unterminated quoted string".to_string());
+    }
+    if last_start != input.len() {
+        out.push(input[last_start..].trim());
+    }
+    Ok(out)
+}
+
+fn parse_usize(arg: &str) -> Result<usize, String> {
+    arg.parse().map_err(|e| format!("{}: '{}'", e, arg))
+}
+
+fn parse_timezone(arg: &str, ident: &str) -> Result<(), String> {
+    let timezone = arg
+        .strip_prefix('\'')
+        .and_then(|x| x.strip_suffix('\''))
+        .and_then(|x| x.parse::<chrono_tz::Tz>().ok());
+    match timezone {
+        Some(_) => Ok(()),
+        None => Err(format!("failed to parse timezone for {}", ident)),
+    }
+}
+
+/// Checks the `'name' = value` arguments of an `Enum8` or `Enum16` type.
+fn validate_enum_entries(args: &[&str], valid_value: fn(&str) -> bool) -> Result<(), String> {
+    for arg in args {
+        let malformed = || format!("malformed enum entry: {}", arg);
+        let quoted = arg.strip_prefix('\'').ok_or_else(malformed)?;
+        let mut chars = quoted.char_indices();
+        let mut rest = None;
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next().ok_or_else(malformed)?;
+                }
+                '\'' => {
+                    rest = Some(&quoted[i + 1..]);
+                    break;
+                }
+                _ => (),
+            }
+        }
+        let value = rest
+            .ok_or_else(|| "unterminated quoted identifier".to_string())?
+            .trim()
+            .strip_prefix('=')
+            .ok_or_else(malformed)?;
+        if !valid_value(value.trim()) {
+            return Err(malformed());
+        }
+    }
+    Ok(())
+}
@@ -5,11 +5,17 @@ use syn::{Ident, Path};
 pub struct Symbol(&'static str);
 
 pub const BOUND: Symbol = Symbol("bound");
+pub const CODEC: Symbol = Symbol("codec");
 pub const DEFAULT: Symbol = Symbol("default");
 pub const DENY_UNKNOWN_FIELDS: Symbol = Symbol("deny_unknown_fields");
 pub const DESERIALIZE_WITH: Symbol = Symbol("deserialize_with");
+pub const FLATTEN: Symbol = Symbol("flatten");
 pub const FROM: Symbol = Symbol("from");
 pub const INTO: Symbol = Symbol("into");
+pub const LENIENT: Symbol = Symbol("lenient");
+pub const ORDER_BY: Symbol = Symbol("order_by");
+pub const PARTITION_BY: Symbol = Symbol("partition_by");
+pub const PREFIX: Symbol = Symbol("prefix");
 pub const RENAME: Symbol = Symbol("rename");
 pub const RENAME_ALL: Symbol = Symbol("rename_all");
 pub const KLICKHOUSE: Symbol = Symbol("klickhouse");
@@ -18,7 +24,9 @@ pub const SKIP: Symbol = Symbol("skip");
 pub const SKIP_DESERIALIZING: Symbol = Symbol("skip_deserializing");
 pub const SKIP_SERIALIZING: Symbol = Symbol("skip_serializing");
 pub const SKIP_SERIALIZING_IF: Symbol = Symbol("skip_serializing_if");
+pub const TRANSPARENT: Symbol = Symbol("transparent");
 pub const TRY_FROM: Symbol = Symbol("try_from");
+pub const TYPE: Symbol = Symbol("type");
 pub const WITH: Symbol = Symbol("with");
 
 impl PartialEq<Symbol> for Ident {
@@ -27,7 +35,7 @@ impl PartialEq<Symbol> for Ident {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Ident {
+impl PartialEq<Symbol> for &Ident {
     fn eq(&self, word: &Symbol) -> bool {
         *self == word.0
     }
@@ -39,7 +47,7 @@ impl PartialEq<Symbol> for Path {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Path {
+impl PartialEq<Symbol> for &Path {
     fn eq(&self, word: &Symbol) -> bool {
         self.is_ident(word.0)
     }